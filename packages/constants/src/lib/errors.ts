@@ -228,6 +228,41 @@ export const LIT_ERROR_CODE = {
   NODE_NOT_AUTHORIZED: 'NodeNotAuthorized',
 };
 
+/**
+ * Codes of the errors thrown by `@lit-protocol/wasm`.
+ * Each thrown error also carries a `kind` from {@link LIT_ERROR_KIND}, a `message` and a `details` object.
+ */
+export const LIT_CRYPTO_ERROR_CODE = {
+  CANNOT_DESERIALIZE: 'cannot_deserialize',
+  CANNOT_SERIALIZE: 'cannot_serialize',
+  INVALID_SHARE: 'invalid_share',
+  INVALID_POINT: 'invalid_point',
+  INVALID_SCALAR: 'invalid_scalar',
+  DIGEST_LENGTH: 'digest_length',
+  INVALID_DIGEST: 'invalid_digest',
+  NO_SHARES: 'no_shares',
+  INVALID_SIGNATURE: 'invalid_signature',
+  SIGNATURE_MISMATCH: 'signature_mismatch',
+  DECRYPTION_FAILED: 'decryption_failed',
+  ATTESTATION_CHAIN: 'attestation_chain',
+  CERTIFICATE_REVOKED: 'certificate_revoked',
+  CERTIFICATE_VALIDITY: 'certificate_validity',
+  INVALID_CRL: 'invalid_crl',
+  REPORT_DATA_MISMATCH: 'report_data_mismatch',
+  INVALID_ATTESTATION_REPORT: 'invalid_attestation_report',
+  UNKNOWN_PRODUCT: 'unknown_product',
+  ATTESTATION_POLICY: 'attestation_policy',
+  INVALID_MEASUREMENT_INPUT: 'invalid_measurement_input',
+  INVALID_COLLATERAL: 'invalid_collateral',
+  TCB_STATUS: 'tcb_status',
+  INVALID_TYPED_DATA: 'invalid_typed_data',
+  INVALID_TRANSACTION: 'invalid_transaction',
+  INVALID_USER_OPERATION: 'invalid_user_operation',
+  INVALID_ADDRESS_PREFIX: 'invalid_address_prefix',
+  INVALID_SERIALIZED_SIGNATURE: 'invalid_serialized_signature',
+  BLS_ERROR: 'bls_error',
+} as const;
+
 export abstract class LitError extends VError {
   protected constructor(
     options: Error | Options,
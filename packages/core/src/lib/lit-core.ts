@@ -42,7 +42,7 @@ import {
   LogLevel,
 } from '@lit-protocol/constants';
 import { LitContracts } from '@lit-protocol/contracts-sdk';
-import { checkSevSnpAttestation, computeHDPubKey } from '@lit-protocol/crypto';
+import { checkSevSnpAttestations, computeHDPubKey } from '@lit-protocol/crypto';
 import {
   bootstrapLogManager,
   isBrowser,
@@ -66,6 +66,7 @@ import {
   JsonHandshakeResponse,
   LitNodeClientConfig,
   MultipleAccessControlConditions,
+  NodeAttestation,
   NodeClientErrorV0,
   NodeClientErrorV1,
   NodeCommandServerKeysResponse,
@@ -115,6 +116,7 @@ export type LitNodeClientConfigWithDefaults = Required<
       LitNodeClientConfig,
       | 'storageProvider'
       | 'contractContext'
+      | 'sevSnpProduct'
       | 'rpcUrl'
       | 'logFormat'
       | 'serviceName'
@@ -580,13 +582,17 @@ export class LitCore extends EventEmitter {
     }
   }
 
-  private async _handshakeAndVerifyNodeAttestation({
+  private async _handshakeWithNode({
     url,
     requestId,
   }: {
     url: string;
     requestId: string;
-  }): Promise<JsonHandshakeResponse> {
+  }): Promise<{
+    keys: JsonHandshakeResponse;
+    attestation?: NodeAttestation;
+    challenge: string;
+  }> {
     const challenge = this.getRandomHexString(64);
 
     const handshakeResult = await this.handshakeWithNode(
@@ -606,7 +612,7 @@ export class LitCore extends EventEmitter {
     // Nodes that have just bootstrapped will not have negotiated their keys, yet
     // They will return ERR for those values until they reach consensus
 
-    // Note that if node attestation checks are disabled or checkSevSnpAttestation() succeeds, we will still track the
+    // Note that if node attestation checks are disabled or the node attests, we will still track the
     // node, even though its keys may be "ERR".
     // Should we really track servers with ERR as keys?
     if (
@@ -631,46 +637,83 @@ export class LitCore extends EventEmitter {
       );
     }
 
+    return { keys, attestation: handshakeResult.attestation, challenge };
+  }
+
+  /**
+   * Checks the attestations of all nodes we handshook with at once, when attestation checks are enabled
+   * @private
+   *
+   * @returns {Promise<Set<string>>} The urls of the nodes we can trust, throws if fewer than `minNodeCount` attested
+   */
+  private async _verifyNodeAttestations(
+    handshakes: Record<
+      string,
+      { attestation?: NodeAttestation; challenge: string }
+    >
+  ): Promise<Set<string>> {
+    const urls = Object.keys(handshakes);
+
     // We force SEV checks on some networks even if the caller attempts to construct the client with them disabled
     if (
-      this.config.checkNodeAttestation ||
-      NETWORKS_REQUIRING_SEV.includes(this.config.litNetwork)
+      !this.config.checkNodeAttestation &&
+      !NETWORKS_REQUIRING_SEV.includes(this.config.litNetwork)
     ) {
-      const attestation = handshakeResult.attestation;
-
-      if (!attestation) {
-        throw new InvalidNodeAttestation(
-          {},
-          `Missing attestation in handshake response from %s`,
-          url
+      if (this.config.litNetwork === LIT_NETWORK.Custom) {
+        log(
+          `Node attestation SEV verification is disabled. You must explicitly set "checkNodeAttestation" to true when using 'custom' network`
         );
       }
+      return new Set(urls);
+    }
 
-      // actually verify the attestation by checking the signature against AMD certs
-      log('Checking attestation against amd certs...');
+    const missing = urls.filter((url) => !handshakes[url].attestation);
+    if (missing.length) {
+      log(`Missing attestation in handshake response from ${missing}`);
+    }
 
-      try {
-        // ensure we won't try to use a node with an invalid attestation response
-        await checkSevSnpAttestation(attestation, challenge, url);
-        log(`Lit Node Attestation verified for ${url}`);
-        // eslint-disable-next-line @typescript-eslint/no-explicit-any
-      } catch (e: any) {
-        throw new InvalidNodeAttestation(
-          {
-            cause: e,
-          },
-          `Lit Node Attestation failed verification for %s - %s`,
-          url,
-          e.message
+    // actually verify the attestations by checking their signatures against AMD certs
+    log('Checking attestations against amd certs...');
+
+    try {
+      const verdict = await checkSevSnpAttestations(
+        urls
+          .filter((url) => handshakes[url].attestation)
+          .map((url) => ({
+            attestation: handshakes[url].attestation!,
+            challengeHex: handshakes[url].challenge,
+            url,
+          })),
+        this.config.minNodeCount,
+        this.config.sevSnpProduct
+      );
+
+      for (const node of verdict.nodes.filter((node) => !node.valid)) {
+        log(
+          `Lit Node Attestation failed verification for ${node.url}`,
+          node.reasons
         );
       }
-    } else if (this.config.litNetwork === LIT_NETWORK.Custom) {
       log(
-        `Node attestation SEV verification is disabled. You must explicitly set "checkNodeAttestation" to true when using 'custom' network`
+        `Lit Node Attestation verified for ${verdict.attested} of ${urls.length} nodes`
       );
-    }
 
-    return keys;
+      return new Set(
+        verdict.nodes.filter((node) => node.valid).map((node) => node.url)
+      );
+      // eslint-disable-next-line @typescript-eslint/no-explicit-any
+    } catch (e: any) {
+      throw new InvalidNodeAttestation(
+        {
+          cause: e,
+          info: {
+            missing,
+          },
+        },
+        `Lit Node Attestation failed verification for too many nodes - %s`,
+        e.message
+      );
+    }
   }
 
   /** Handshakes with all nodes that are in `bootstrapUrls`
@@ -691,6 +734,14 @@ export class LitCore extends EventEmitter {
     // track connectedNodes for the new handshake operation
     const connectedNodes = new Set<string>();
     const serverKeys: Record<string, JsonHandshakeResponse> = {};
+    const handshakes: Record<
+      string,
+      {
+        keys: JsonHandshakeResponse;
+        attestation?: NodeAttestation;
+        challenge: string;
+      }
+    > = {};
 
     let timeoutHandle: ReturnType<typeof setTimeout>;
     await Promise.race([
@@ -698,7 +749,7 @@ export class LitCore extends EventEmitter {
         timeoutHandle = setTimeout(() => {
           const msg = `Error: Could not handshake with nodes after timeout of ${
             this.config.connectTimeout
-          }ms. Could only connect to ${Object.keys(serverKeys).length} of ${
+          }ms. Could only connect to ${Object.keys(handshakes).length} of ${
             this.config.bootstrapUrls.length
           } nodes. Please check your network connection and try again. Note that you can control this timeout with the connectTimeout config option which takes milliseconds.`;
 
@@ -712,15 +763,23 @@ export class LitCore extends EventEmitter {
       }),
       Promise.all(
         this.config.bootstrapUrls.map(async (url) => {
-          serverKeys[url] = await this._handshakeAndVerifyNodeAttestation({
+          handshakes[url] = await this._handshakeWithNode({
             url,
             requestId,
           });
-          connectedNodes.add(url);
         })
-      ).finally(() => {
-        clearTimeout(timeoutHandle);
-      }),
+      )
+        .then(() => this._verifyNodeAttestations(handshakes))
+        .then((attestedNodes) => {
+          // only the nodes that attested are used
+          for (const url of attestedNodes) {
+            serverKeys[url] = handshakes[url].keys;
+            connectedNodes.add(url);
+          }
+        })
+        .finally(() => {
+          clearTimeout(timeoutHandle);
+        }),
     ]);
 
     const coreNodeConfig = this._getCoreNodeConfigFromHandshakeResults({
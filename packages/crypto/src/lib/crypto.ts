@@ -23,6 +23,10 @@ import {
 } from '@lit-protocol/uint8arrays';
 import {
   EcdsaVariant,
+  NodeAttestationInput,
+  NodeAttestationResult,
+  NodeSetVerdict,
+  SevProduct,
   blsCombine,
   blsDecrypt,
   blsEncrypt,
@@ -30,8 +34,12 @@ import {
   ecdsaCombine,
   ecdsaDeriveKey,
   ecdsaVerify,
+  sevSnpCertBundle,
+  sevSnpDetectProduct,
+  sevSnpGetCrlUrl,
   sevSnpGetVcekUrl,
-  sevSnpVerify,
+  sevSnpVerifyNodeAttestation,
+  sevSnpVerifyNodeAttestations,
 } from '@lit-protocol/wasm';
 
 /** ---------- Exports ---------- */
@@ -343,6 +351,112 @@ async function getAmdCert(url: string): Promise<Uint8Array> {
   }
 }
 
+/**
+ * How long a fetched CRL is reused before fetching it again.
+ * The CRL is only valid until its next update, so it can't be cached like certificates
+ */
+const AMD_CRL_CACHE_TTL_MS = 60 * 60 * 1000;
+
+/**
+ * Fetches the certificate revocation list of the product, cached in memory.
+ * The pending fetch is cached too, so the nodes of a connect share a single KDS request
+ */
+async function getAmdCrl(crlUrl: string): Promise<Uint8Array> {
+  const cache = ((
+    globalThis as unknown as {
+      amdCrlStore: Record<
+        string,
+        { crl: Promise<Uint8Array>; fetchedAt: number }
+      >;
+    }
+  ).amdCrlStore ??= {});
+
+  const cached = cache[crlUrl];
+  if (cached && Date.now() - cached.fetchedAt < AMD_CRL_CACHE_TTL_MS) {
+    return cached.crl;
+  }
+
+  const entry = { crl: getAmdCert(crlUrl), fetchedAt: Date.now() };
+  cache[crlUrl] = entry;
+  try {
+    return await entry.crl;
+  } catch (e) {
+    // don't keep a failed fetch, unless another caller already replaced it
+    if (cache[crlUrl] === entry) {
+      delete cache[crlUrl];
+    }
+    throw e;
+  }
+}
+
+/**
+ * Gets the certificate chain of the node's VCEK as a blob, verified before it is cached.
+ * The VCEK is only fetched once per chip and TCB, and the blob is verified again on every use
+ */
+async function getAmdCertBundle(
+  attestation: NodeAttestation,
+  report: Uint8Array,
+  crl: Uint8Array,
+  product?: SevProduct
+): Promise<Uint8Array> {
+  const vcekUrl = await sevSnpGetVcekUrl(report, product);
+
+  // use local storage if we have one available
+  if (globalThis.localStorage) {
+    log('Using local storage for certificate caching');
+    const cached = localStorage.getItem(vcekUrl);
+    if (cached) {
+      return uint8arrayFromString(cached, 'base64');
+    }
+  } else {
+    const cached = (
+      globalThis as unknown as { amdCertStore?: Record<string, Uint8Array> }
+    ).amdCertStore?.[vcekUrl];
+    if (cached) {
+      return cached;
+    }
+  }
+
+  const vcekCert = await getAmdCert(vcekUrl);
+  if (!vcekCert || vcekCert.length === 0 || vcekCert.length < 256) {
+    throw new UnknownError(
+      {
+        info: {
+          attestation,
+          report,
+          vcekUrl,
+        },
+      },
+      'Unable to retrieve VCEK certificate from AMD'
+    );
+  }
+
+  let certBundle: Uint8Array;
+  try {
+    certBundle = await sevSnpCertBundle(report, vcekCert, { crl, product });
+  } catch (e) {
+    throw new NetworkError(
+      {
+        info: {
+          attestation,
+          vcekUrl,
+        },
+        cause: e,
+      },
+      'VCEK certificate from AMD does not verify'
+    );
+  }
+
+  if (globalThis.localStorage) {
+    localStorage.setItem(vcekUrl, uint8arrayToString(certBundle, 'base64'));
+  } else {
+    ((
+      globalThis as unknown as { amdCertStore: Record<string, Uint8Array> }
+    ).amdCertStore ??= {})[vcekUrl] = certBundle;
+  }
+  return certBundle;
+}
+
 /**
  *
  * Check the attestation against AMD certs
@@ -350,127 +464,146 @@ async function getAmdCert(url: string): Promise<Uint8Array> {
  * @param { NodeAttestation } attestation The actual attestation object, which includes the signature and report
  * @param { string } challengeHex The challenge we sent
  * @param { string } url The URL we talked to
+ * @param { SevProduct } [product] The EPYC product of the node, detected from the report when omitted
  *
  * @returns { Promise<undefined> } A promise that throws if the attestation is invalid
  */
 export const checkSevSnpAttestation = async (
   attestation: NodeAttestation,
   challengeHex: string,
-  url: string
+  url: string,
+  product?: SevProduct
 ) => {
-  const noonce = Buffer.from(attestation.noonce, 'base64');
   const challenge = Buffer.from(challengeHex, 'hex');
-  const data = Object.fromEntries(
-    Object.entries(attestation.data).map(([k, v]) => [
-      k,
-      Buffer.from(v, 'base64'),
-    ])
+  const report = Buffer.from(attestation.report, 'base64');
+
+  // the ASK and VCEK must not be revoked by AMD
+  const crl = await getAmdCrl(
+    await sevSnpGetCrlUrl(product ?? (await sevSnpDetectProduct(report)))
   );
-  const signatures = attestation.signatures.map((s) =>
-    Buffer.from(s, 'base64')
+
+  const certBundle = await getAmdCertBundle(attestation, report, crl, product);
+
+  // the nonce, external address, certificate chain, revocation and report data are all checked in wasm
+  const verdict = await sevSnpVerifyNodeAttestation(
+    attestation,
+    challenge,
+    url,
+    certBundle,
+    { crl, product }
   );
-  const report = Buffer.from(attestation.report, 'base64');
 
-  if (!noonce.equals(challenge)) {
+  if (!verdict.valid) {
+    const failed = verdict.checks.filter((check) => !check.passed);
     throw new NetworkError(
       {
         info: {
           attestation,
           challengeHex,
-          noonce,
-          challenge,
+          url,
+          verdict,
         },
       },
-      `Attestation noonce ${noonce} does not match challenge ${challenge}`
+      `Attestation check failed: ${failed
+        .map(
+          (check) =>
+            check.message ??
+            `${check.check} expected ${check.expected}, got ${check.actual}`
+        )
+        .join(', ')}`
     );
   }
+};
 
-  const parsedUrl = new URL(url);
-  const ipWeTalkedTo = parsedUrl.hostname;
-  let portWeTalkedTo = parsedUrl.port;
-  if (portWeTalkedTo === '') {
-    // if we're on HTTP or HTTPS, the port will be empty
-    if (url.startsWith('https://')) {
-      portWeTalkedTo = '443';
-    } else if (url.startsWith('http://')) {
-      portWeTalkedTo = '80';
-    } else {
-      throw new NetworkError(
-        {
-          info: {
-            url,
-          },
-        },
-        `Unknown port in URL ${url}`
-      );
-    }
-  }
+/**
+ * Data keys whose value is specific to each node, no two attested nodes may share one
+ */
+const UNIQUE_ATTESTATION_DATA_KEYS = ['INSTANCE_ID'];
 
-  const ipAndAddrFromReport = data['EXTERNAL_ADDR'].toString('utf8');
-  const ipFromReport = ipAndAddrFromReport.split(':')[0];
-  const portFromReport = ipAndAddrFromReport.split(':')[1];
+/**
+ *
+ * Check the attestations of every node we handshook with against AMD certs, at once
+ *
+ * @param { { attestation: NodeAttestation; challengeHex: string; url: string }[] } nodes The attestation, challenge and URL of each node
+ * @param { number } threshold How many nodes must attest
+ * @param { SevProduct } [product] The EPYC product of the nodes, needed for version 2 reports which don't tell Milan and Genoa apart
+ *
+ * @returns { Promise<NodeSetVerdict> } Which nodes attested, throws if fewer than the threshold did
+ */
+export const checkSevSnpAttestations = async (
+  nodes: { attestation: NodeAttestation; challengeHex: string; url: string }[],
+  threshold: number,
+  product?: SevProduct
+): Promise<NodeSetVerdict> => {
+  // nodes whose certificates can't be fetched fail without being verified
+  const fetched = await Promise.allSettled(
+    nodes.map(async ({ attestation, challengeHex, url }) => {
+      const report = Buffer.from(attestation.report, 'base64');
+
+      // the ASK and VCEK must not be revoked by AMD, and each product has its own CRL
+      const crl = await getAmdCrl(
+        await sevSnpGetCrlUrl(product ?? (await sevSnpDetectProduct(report)))
+      );
 
-  if (ipWeTalkedTo !== ipFromReport) {
-    throw new NetworkError(
-      {
-        info: {
+      return {
+        url,
+        attestation,
+        challenge: Buffer.from(challengeHex, 'hex'),
+        certificates: await getAmdCertBundle(
           attestation,
-          ipWeTalkedTo,
-          ipFromReport,
-        },
-      },
-      `Attestation external address ${ipFromReport} does not match IP we talked to ${ipWeTalkedTo}`
-    );
-  }
-  if (portWeTalkedTo !== portFromReport) {
-    throw new NetworkError(
-      {
-        info: {
-          attestation,
-          portWeTalkedTo,
-          portFromReport,
-        },
-      },
-      `Attestation external port ${portFromReport} does not match port we talked to ${portWeTalkedTo}`
-    );
-  }
+          report,
+          crl,
+          product
+        ),
+        crl,
+      };
+    })
+  );
 
-  // get the VCEK certificate
-  let vcekCert;
-  const vcekUrl = await sevSnpGetVcekUrl(report);
-  // use local storage if we have one available
-  if (globalThis.localStorage) {
-    log('Using local storage for certificate caching');
-    vcekCert = localStorage.getItem(vcekUrl);
-    if (vcekCert) {
-      vcekCert = uint8arrayFromString(vcekCert, 'base64');
+  const inputs: NodeAttestationInput[] = [];
+  const unverified: NodeAttestationResult[] = [];
+  fetched.forEach((result, i) => {
+    if (result.status === 'fulfilled') {
+      inputs.push(result.value);
     } else {
-      vcekCert = await getAmdCert(vcekUrl);
-      localStorage.setItem(vcekUrl, uint8arrayToString(vcekCert, 'base64'));
+      unverified.push({
+        url: nodes[i].url,
+        valid: false,
+        reasons: [result.reason?.message ?? String(result.reason)],
+      });
     }
-  } else {
-    const cache = ((
-      globalThis as unknown as { amdCertStore: Record<string, Uint8Array> }
-    ).amdCertStore ??= {});
-    cache[vcekUrl] ??= await getAmdCert(vcekUrl);
-    vcekCert = cache[vcekUrl];
-  }
+  });
+
+  // certificates shared by nodes are only verified once
+  const verdict = await sevSnpVerifyNodeAttestations(
+    inputs,
+    threshold,
+    { product },
+    UNIQUE_ATTESTATION_DATA_KEYS
+  );
+  const summary: NodeSetVerdict = {
+    ...verdict,
+    nodes: [...verdict.nodes, ...unverified],
+    failed: [...verdict.failed, ...unverified.map((node) => node.url)],
+  };
 
-  if (!vcekCert || vcekCert.length === 0 || vcekCert.length < 256) {
-    throw new UnknownError(
+  if (!summary.thresholdMet) {
+    throw new NetworkError(
       {
         info: {
-          attestation,
-          report,
-          vcekUrl,
+          threshold,
+          attested: summary.attested,
+          failed: summary.nodes.filter((node) => !node.valid),
         },
       },
-      'Unable to retrieve VCEK certificate from AMD'
+      `Only ${summary.attested} of ${nodes.length} nodes attested, ${threshold} are required: ${summary.nodes
+        .filter((node) => !node.valid)
+        .map((node) => `${node.url} (${node.reasons.join(', ')})`)
+        .join(', ')}`
     );
   }
 
-  // pass base64 encoded report to wasm wrapper
-  return sevSnpVerify(report, data, signatures, challenge, vcekCert);
+  return summary;
 };
 
 declare global {
@@ -184,6 +184,11 @@ export interface LitNodeClientConfig {
   debug?: boolean;
   connectTimeout?: number;
   checkNodeAttestation?: boolean;
+  /**
+   * EPYC product of the nodes, required to check attestations when they send version 2 reports,
+   * which don't tell Milan and Genoa apart
+   */
+  sevSnpProduct?: 'Milan' | 'Genoa' | 'Turin';
   contractContext?: LitContractContext | LitContractResolverContext;
   storageProvider?: StorageProvider;
   defaultAuthCallback?: (authSigParams: AuthCallbackParams) => Promise<AuthSig>;
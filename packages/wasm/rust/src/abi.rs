@@ -5,19 +5,22 @@ use std::convert::TryFrom;
 use js_sys::Uint8Array;
 use serde::{de::DeserializeOwned, Serialize};
 use serde_bytes::Bytes;
-use wasm_bindgen::{JsCast, JsError, JsValue};
+use wasm_bindgen::{JsCast, JsValue};
 
-pub type JsResult<T> = Result<T, JsError>;
+use crate::error::LitCryptoError;
+
+pub type JsResult<T> = Result<T, LitCryptoError>;
 
 pub fn from_js<T: DeserializeOwned>(value: impl Into<JsValue>) -> JsResult<T> {
     serde_wasm_bindgen::from_value::<T>(value.into()).map_err(Into::into)
 }
 
 pub fn into_js<T: JsCast>(value: &(impl Serialize + ?Sized)) -> JsResult<T> {
-    let value = serde_wasm_bindgen::to_value(value)?;
-    let value = value
-        .dyn_into()
-        .map_err(|v| JsError::new(&format!("unexpected serializer output type: {:?}", v)))?;
+    let value = serde_wasm_bindgen::to_value(value)
+        .map_err(|e| LitCryptoError::Serialize(e.to_string()))?;
+    let value = value.dyn_into().map_err(|v| {
+        LitCryptoError::Serialize(format!("unexpected serializer output type: {:?}", v))
+    })?;
     Ok(value)
 }
 
@@ -26,7 +29,7 @@ pub fn from_uint8array<T: TryFrom<Vec<u8>>>(value: Uint8Array) -> JsResult<T> {
     let value = T::try_from(value);
     let value = value
         .ok()
-        .ok_or_else(|| JsError::new("cannot deserialize"))?;
+        .ok_or_else(|| LitCryptoError::Deserialize("invalid encoding".to_string()))?;
 
     Ok(value)
 }
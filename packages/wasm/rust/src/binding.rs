@@ -0,0 +1,123 @@
+//! How a node binds the challenge, its data and its signatures into the report data
+//! of its SEV-SNP report or TDX quote, both have 64 bytes of it
+//!
+//! V0 is the layout nodes have always used: tags and fields hashed back to back, which is ambiguous
+//! and leaves out the last signature. V1 length-prefixes every field, counts the entries and
+//! starts with the version, so each report data commits to exactly one set of inputs.
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha512};
+use tsify::Tsify;
+
+use crate::abi::JsResult;
+use crate::error::LitCryptoError;
+
+/// Separates V1 report data from any other SHA-512 a node may put in a report.
+/// TDX quotes use the same domain, the binding doesn't depend on the TEE. The bytes predate TDX
+/// support and are frozen, changing them would break every V1 report data already produced
+const REPORT_DATA_V1_DOMAIN: &[u8] = b"LIT_SEV_SNP_REPORT_DATA";
+
+#[derive(Tsify, Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[tsify(into_wasm_abi, from_wasm_abi)]
+pub enum ReportDataVersion {
+    /// Legacy layout, only use it for nodes that don't support V1
+    #[default]
+    V0,
+    V1,
+}
+
+impl ReportDataVersion {
+    pub const ALL: [Self; 2] = [Self::V0, Self::V1];
+
+    pub fn as_u8(&self) -> u8 {
+        match self {
+            Self::V0 => 0,
+            Self::V1 => 1,
+        }
+    }
+
+    pub fn report_data(
+        &self,
+        challenge: &[u8],
+        data: &BTreeMap<String, Vec<u8>>,
+        signatures: &[Vec<u8>],
+    ) -> [u8; 64] {
+        let mut hasher = Sha512::new();
+        match self {
+            Self::V0 => {
+                hasher.update("noonce");
+                hasher.update(challenge);
+
+                hasher.update("data");
+                for (key, value) in data {
+                    hasher.update(key);
+                    hasher.update(value);
+                }
+
+                if let Some((_last, signatures)) = signatures.split_last() {
+                    hasher.update("signatures");
+                    // Nodes have always left out the last signature, we have to as well
+                    for signature in signatures {
+                        hasher.update(signature);
+                    }
+                }
+            }
+            Self::V1 => {
+                hasher.update([self.as_u8()]);
+                update_field(&mut hasher, REPORT_DATA_V1_DOMAIN);
+                update_field(&mut hasher, challenge);
+
+                update_len(&mut hasher, data.len());
+                for (key, value) in data {
+                    update_field(&mut hasher, key.as_bytes());
+                    update_field(&mut hasher, value);
+                }
+
+                update_len(&mut hasher, signatures.len());
+                for signature in signatures {
+                    update_field(&mut hasher, signature);
+                }
+            }
+        }
+
+        hasher.finalize().into()
+    }
+
+    /// Checks the report data of a report or quote against the inputs the node was meant to bind
+    pub fn verify(
+        &self,
+        report_data: &[u8; 64],
+        challenge: &[u8],
+        data: &BTreeMap<String, Vec<u8>>,
+        signatures: &[Vec<u8>],
+    ) -> JsResult<()> {
+        let expected = self.report_data(challenge, data, signatures);
+
+        if *report_data != expected {
+            // Tells a node on another binding version apart from bad inputs
+            let matching_version = Self::ALL
+                .into_iter()
+                .find(|v| v != self && *report_data == v.report_data(challenge, data, signatures))
+                .map(|v| v.as_u8());
+
+            return Err(LitCryptoError::ReportDataMismatch {
+                version: self.as_u8(),
+                expected: expected.to_vec(),
+                actual: report_data.to_vec(),
+                matching_version,
+            });
+        }
+        Ok(())
+    }
+}
+
+fn update_len(hasher: &mut Sha512, len: usize) {
+    hasher.update((len as u32).to_be_bytes());
+}
+
+fn update_field(hasher: &mut Sha512, field: &[u8]) {
+    update_len(hasher, field.len());
+    hasher.update(field);
+}
@@ -0,0 +1,221 @@
+//! Bitcoin sighashes of PSBT inputs, DER signatures and addresses of secp256k1 keys
+
+mod psbt;
+mod transaction;
+
+use bech32::{hrp, Hrp};
+use elliptic_curve::sec1::ToEncodedPoint;
+use js_sys::Uint8Array;
+use k256::ecdsa::{signature::hazmat::PrehashVerifier, Signature, VerifyingKey};
+use k256::Secp256k1;
+use serde::{Deserialize, Serialize};
+use tsify::Tsify;
+use wasm_bindgen::prelude::*;
+
+use crate::abi::{from_js, into_uint8array, JsResult};
+use crate::ecdsa::{Ecdsa, EcdsaSignature};
+use crate::error::LitCryptoError;
+
+use psbt::{check_ecdsa_sighash_type, hash160, Psbt};
+use transaction::SIGHASH_ALL;
+
+pub use psbt::SighashKind;
+
+#[derive(Tsify, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[tsify(from_wasm_abi)]
+#[serde(rename_all = "lowercase")]
+pub enum BtcNetwork {
+    Mainnet,
+    Testnet,
+    Regtest,
+}
+
+impl BtcNetwork {
+    fn p2pkh_version(self) -> u8 {
+        match self {
+            Self::Mainnet => 0x00,
+            Self::Testnet | Self::Regtest => 0x6f,
+        }
+    }
+
+    fn p2sh_version(self) -> u8 {
+        match self {
+            Self::Mainnet => 0x05,
+            Self::Testnet | Self::Regtest => 0xc4,
+        }
+    }
+
+    fn hrp(self) -> Hrp {
+        match self {
+            Self::Mainnet => hrp::BC,
+            Self::Testnet => hrp::TB,
+            Self::Regtest => hrp::BCRT,
+        }
+    }
+}
+
+/// The outputs a single key spends, all of them committing to its compressed public key
+#[derive(Tsify, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[tsify(from_wasm_abi)]
+pub enum BtcAddressType {
+    #[serde(rename = "P2PKH")]
+    P2pkh,
+    #[serde(rename = "P2WPKH")]
+    P2wpkh,
+    #[serde(rename = "P2SH-P2WPKH")]
+    P2shP2wpkh,
+}
+
+/// The hash to sign for an input, to pass as `message_hash`
+#[derive(Tsify, Serialize, Debug, Clone)]
+#[tsify(into_wasm_abi)]
+#[serde(rename_all = "camelCase")]
+pub struct BtcSighash {
+    pub kind: SighashKind,
+    /// The sighash type the hash commits to, which the signature is to carry
+    pub sighash_type: u8,
+    #[tsify(type = "Uint8Array")]
+    #[serde(with = "serde_bytes")]
+    pub hash: Vec<u8>,
+}
+
+#[derive(Tsify, Serialize, Debug, Clone)]
+#[tsify(into_wasm_abi)]
+#[serde(rename_all = "camelCase")]
+pub struct BtcSignedTransaction {
+    #[tsify(type = "Uint8Array")]
+    #[serde(with = "serde_bytes")]
+    pub transaction: Vec<u8>,
+    /// Hex, in the byte order explorers display
+    pub txid: String,
+}
+
+fn invalid(reason: String) -> LitCryptoError {
+    LitCryptoError::Transaction(reason)
+}
+
+fn psbt_from_js(psbt: Uint8Array) -> JsResult<Psbt> {
+    Psbt::decode(&from_js::<Vec<u8>>(psbt)?).map_err(invalid)
+}
+
+fn compressed_public_key(public_key: Uint8Array) -> JsResult<Vec<u8>> {
+    let public_key = from_js::<Vec<u8>>(public_key)?;
+    let public_key =
+        k256::PublicKey::from_sec1_bytes(&public_key).map_err(|_| LitCryptoError::InvalidPoint)?;
+    Ok(public_key.to_encoded_point(true).as_bytes().to_vec())
+}
+
+/// DER encoding of a combined signature with a low `s`, followed by its sighash type
+fn der_signature(signature: EcdsaSignature, sighash_type: u8) -> JsResult<(Signature, Vec<u8>)> {
+    check_ecdsa_sighash_type(sighash_type).map_err(invalid)?;
+    let (rs, _) = Ecdsa::<Secp256k1>::low_s_signature(signature)?;
+    let signature = Signature::from_slice(&rs).map_err(|_| LitCryptoError::InvalidSignature)?;
+    let der = [signature.to_der().as_bytes(), &[sighash_type]].concat();
+    Ok((signature, der))
+}
+
+/// Signature hash of a PSBT input, legacy, BIP-143 or BIP-341 depending on the output it spends.
+/// The sighash type is that of the input, else `sighash_type`, else `SIGHASH_ALL` or `SIGHASH_DEFAULT` for taproot.
+#[wasm_bindgen(js_name = "btcSighash")]
+pub fn btc_sighash(
+    psbt: Uint8Array,
+    input_index: usize,
+    sighash_type: Option<u8>,
+) -> JsResult<BtcSighash> {
+    let psbt = psbt_from_js(psbt)?;
+    let (kind, hash, sighash_type) = psbt.sighash(input_index, sighash_type).map_err(invalid)?;
+
+    Ok(BtcSighash {
+        kind,
+        sighash_type,
+        hash: hash.to_vec(),
+    })
+}
+
+/// DER encoding of a combined K256 signature with a low `s`, followed by its sighash type, `SIGHASH_ALL` by default
+#[wasm_bindgen(js_name = "btcDerSignature")]
+pub fn btc_der_signature(
+    signature: EcdsaSignature,
+    sighash_type: Option<u8>,
+) -> JsResult<Uint8Array> {
+    let (_, der) = der_signature(signature, sighash_type.unwrap_or(SIGHASH_ALL))?;
+    into_uint8array(der)
+}
+
+/// Adds the signature of an input to a PSBT, once verified against the input's sighash and the compressed public key
+#[wasm_bindgen(js_name = "btcPsbtAddSignature")]
+pub fn btc_psbt_add_signature(
+    psbt: Uint8Array,
+    input_index: usize,
+    public_key: Uint8Array,
+    signature: EcdsaSignature,
+    sighash_type: Option<u8>,
+) -> JsResult<Uint8Array> {
+    let mut psbt = psbt_from_js(psbt)?;
+    let public_key = compressed_public_key(public_key)?;
+    let (kind, hash, sighash_type) = psbt.sighash(input_index, sighash_type).map_err(invalid)?;
+    if kind == SighashKind::Taproot {
+        return Err(invalid(format!(
+            "input {input_index} is a taproot key path spend, signed with Schnorr signatures"
+        )));
+    }
+
+    let (signature, der) = der_signature(signature, sighash_type)?;
+    VerifyingKey::from_sec1_bytes(&public_key)
+        .map_err(|_| LitCryptoError::InvalidPoint)?
+        .verify_prehash(&hash, &signature)
+        .map_err(|_| LitCryptoError::SignatureMismatch)?;
+
+    psbt.add_partial_signature(input_index, &public_key, der);
+    into_uint8array(psbt.encode())
+}
+
+/// Finalizes the P2PKH, P2WPKH and P2SH-P2WPKH inputs of a PSBT that are not yet, from their partial signatures.
+/// The inputs it can't finalize are left as they are, for other signers
+#[wasm_bindgen(js_name = "btcPsbtFinalize")]
+pub fn btc_psbt_finalize(psbt: Uint8Array) -> JsResult<Uint8Array> {
+    let mut psbt = psbt_from_js(psbt)?;
+    psbt.finalize();
+    into_uint8array(psbt.encode())
+}
+
+/// The signed transaction of a finalized PSBT, ready to broadcast
+#[wasm_bindgen(js_name = "btcPsbtExtract")]
+pub fn btc_psbt_extract(psbt: Uint8Array) -> JsResult<BtcSignedTransaction> {
+    let transaction = psbt_from_js(psbt)?.extract().map_err(invalid)?;
+    let mut txid = transaction.txid();
+    txid.reverse();
+
+    Ok(BtcSignedTransaction {
+        transaction: transaction.encode(true),
+        txid: hex::encode(txid),
+    })
+}
+
+/// Address of a K256 public key, of its compressed encoding
+#[wasm_bindgen(js_name = "btcAddress")]
+pub fn btc_address(
+    public_key: Uint8Array,
+    address_type: BtcAddressType,
+    network: BtcNetwork,
+) -> JsResult<String> {
+    let key_hash = hash160(&compressed_public_key(public_key)?);
+    let base58check = |version: u8, hash: &[u8]| {
+        bs58::encode([&[version], hash].concat())
+            .with_check()
+            .into_string()
+    };
+
+    match address_type {
+        BtcAddressType::P2pkh => Ok(base58check(network.p2pkh_version(), &key_hash)),
+        BtcAddressType::P2wpkh => bech32::segwit::encode_v0(network.hrp(), &key_hash)
+            .map_err(|e| LitCryptoError::Serialize(e.to_string())),
+        BtcAddressType::P2shP2wpkh => {
+            let redeem_script = [&[0x00, 0x14][..], &key_hash].concat();
+            Ok(base58check(
+                network.p2sh_version(),
+                &hash160(&redeem_script),
+            ))
+        }
+    }
+}
@@ -0,0 +1,427 @@
+//! Partially signed transactions of BIP-174, version 0
+
+use ripemd::Ripemd160;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use tsify::Tsify;
+
+use super::transaction::{
+    write_var_bytes, write_witness, Reader, Transaction, TxOut, SIGHASH_ALL, SIGHASH_DEFAULT,
+};
+
+const MAGIC: &[u8] = b"psbt\xff";
+
+const GLOBAL_UNSIGNED_TX: u8 = 0x00;
+const GLOBAL_VERSION: u8 = 0xfb;
+
+const IN_NON_WITNESS_UTXO: u8 = 0x00;
+const IN_WITNESS_UTXO: u8 = 0x01;
+const IN_PARTIAL_SIG: u8 = 0x02;
+const IN_SIGHASH_TYPE: u8 = 0x03;
+const IN_REDEEM_SCRIPT: u8 = 0x04;
+const IN_WITNESS_SCRIPT: u8 = 0x05;
+const IN_FINAL_SCRIPTSIG: u8 = 0x07;
+const IN_FINAL_SCRIPTWITNESS: u8 = 0x08;
+/// The last input type BIP-174 and its extensions define, the finalizer keeps the unknown ones after it
+const IN_LAST_KNOWN: u8 = 0x18;
+
+/// Which scheme the signature hash of an input follows
+#[derive(Tsify, Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SighashKind {
+    /// The original scheme of P2PKH and P2SH inputs
+    Legacy,
+    /// BIP-143
+    SegwitV0,
+    /// BIP-341, for key path spends
+    Taproot,
+}
+
+/// The key-value pairs of a map, in the order they are read
+#[derive(Debug, Clone, Default)]
+struct Map(Vec<(Vec<u8>, Vec<u8>)>);
+
+impl Map {
+    fn decode(reader: &mut Reader) -> Result<Self, String> {
+        let mut map = Self::default();
+        loop {
+            let key = reader.var_bytes()?;
+            if key.is_empty() {
+                return Ok(map);
+            }
+            if map.get(key).is_some() {
+                return Err(format!("duplicate key {}", hex::encode(key)));
+            }
+            map.0.push((key.to_vec(), reader.var_bytes()?.to_vec()));
+        }
+    }
+
+    fn encode(&self, bytes: &mut Vec<u8>) {
+        for (key, value) in &self.0 {
+            write_var_bytes(key, bytes);
+            write_var_bytes(value, bytes);
+        }
+        bytes.push(0x00);
+    }
+
+    fn get(&self, key: &[u8]) -> Option<&[u8]> {
+        self.0
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, value)| value.as_slice())
+    }
+
+    fn of_type(&self, key_type: u8) -> impl Iterator<Item = (&[u8], &[u8])> {
+        self.0
+            .iter()
+            .filter(move |(key, _)| key[0] == key_type)
+            .map(|(key, value)| (&key[1..], value.as_slice()))
+    }
+
+    fn insert(&mut self, key: Vec<u8>, value: Vec<u8>) {
+        match self.0.iter_mut().find(|(k, _)| *k == key) {
+            Some((_, existing)) => *existing = value,
+            None => self.0.push((key, value)),
+        }
+    }
+}
+
+pub struct Psbt {
+    pub unsigned_tx: Transaction,
+    global: Map,
+    inputs: Vec<Map>,
+    outputs: Vec<Map>,
+}
+
+pub fn hash160(data: &[u8]) -> [u8; 20] {
+    Ripemd160::digest(Sha256::digest(data)).into()
+}
+
+/// `OP_DUP OP_HASH160 <hash> OP_EQUALVERIFY OP_CHECKSIG`
+pub fn p2pkh_script(hash: &[u8]) -> Vec<u8> {
+    [&[0x76, 0xa9, 0x14][..], hash, &[0x88, 0xac]].concat()
+}
+
+fn p2pkh_hash(script: &[u8]) -> Option<&[u8]> {
+    match script {
+        [0x76, 0xa9, 0x14, hash @ .., 0x88, 0xac] if hash.len() == 20 => Some(hash),
+        _ => None,
+    }
+}
+
+fn p2sh_hash(script: &[u8]) -> Option<&[u8]> {
+    match script {
+        [0xa9, 0x14, hash @ .., 0x87] if hash.len() == 20 => Some(hash),
+        _ => None,
+    }
+}
+
+/// Version and program of a witness program of BIP-141
+fn witness_program(script: &[u8]) -> Option<(u8, &[u8])> {
+    match script {
+        [0x00, length, program @ ..] if usize::from(*length) == program.len() => Some((0, program)),
+        [version @ 0x51..=0x60, length, program @ ..]
+            if usize::from(*length) == program.len() && (2..=40).contains(length) =>
+        {
+            Some((version - 0x50, program))
+        }
+        _ => None,
+    }
+}
+
+/// Hash of the key a P2PKH, P2WPKH or P2SH-P2WPKH output is spent with, whether the spend is segwit,
+/// and the script sig pushing the redeem script of P2SH-P2WPKH
+fn single_key_spend<'a>(
+    script_pubkey: &'a [u8],
+    redeem_script: Option<&'a [u8]>,
+) -> Option<(&'a [u8], bool, Vec<u8>)> {
+    if let Some(hash) = p2pkh_hash(script_pubkey) {
+        return Some((hash, false, Vec::new()));
+    }
+    if let Some((0, hash)) = witness_program(script_pubkey) {
+        return (hash.len() == 20).then_some((hash, true, Vec::new()));
+    }
+
+    let script_hash = p2sh_hash(script_pubkey)?;
+    let redeem_script = redeem_script.filter(|script| hash160(script) == script_hash)?;
+    match witness_program(redeem_script) {
+        Some((0, hash)) if hash.len() == 20 => {
+            let mut script_sig = Vec::new();
+            push(redeem_script, &mut script_sig);
+            Some((hash, true, script_sig))
+        }
+        _ => None,
+    }
+}
+
+/// Pushes data short enough for a single opcode, as signatures, public keys and P2SH-P2WPKH redeem scripts are
+fn push(data: &[u8], script: &mut Vec<u8>) {
+    script.push(data.len() as u8);
+    script.extend_from_slice(data);
+}
+
+impl Psbt {
+    pub fn decode(data: &[u8]) -> Result<Self, String> {
+        let mut reader = Reader::new(data);
+        if reader.take(MAGIC.len()).ok() != Some(MAGIC) {
+            return Err("missing the psbt magic bytes".to_string());
+        }
+
+        let global = Map::decode(&mut reader)?;
+        if let Some(version) = global.get(&[GLOBAL_VERSION]) {
+            if version != [0, 0, 0, 0] {
+                return Err("only version 0 is supported".to_string());
+            }
+        }
+        let unsigned_tx = global
+            .get(&[GLOBAL_UNSIGNED_TX])
+            .ok_or("missing the unsigned transaction")?;
+        let unsigned_tx = Transaction::decode(unsigned_tx)
+            .map_err(|e| format!("invalid unsigned transaction: {e}"))?;
+        if unsigned_tx
+            .inputs
+            .iter()
+            .any(|input| !input.script_sig.is_empty() || !input.witness.is_empty())
+        {
+            return Err("the unsigned transaction has signatures".to_string());
+        }
+
+        let inputs = (0..unsigned_tx.inputs.len())
+            .map(|_| Map::decode(&mut reader))
+            .collect::<Result<Vec<_>, _>>()?;
+        let outputs = (0..unsigned_tx.outputs.len())
+            .map(|_| Map::decode(&mut reader))
+            .collect::<Result<Vec<_>, _>>()?;
+        if !reader.is_empty() {
+            return Err("trailing bytes after the outputs".to_string());
+        }
+
+        Ok(Self {
+            unsigned_tx,
+            global,
+            inputs,
+            outputs,
+        })
+    }
+
+    pub fn encode(&self) -> Vec<u8> {
+        let mut bytes = MAGIC.to_vec();
+        self.global.encode(&mut bytes);
+        for map in self.inputs.iter().chain(&self.outputs) {
+            map.encode(&mut bytes);
+        }
+        bytes
+    }
+
+    fn input(&self, index: usize) -> Result<&Map, String> {
+        self.inputs
+            .get(index)
+            .ok_or_else(|| format!("no input {index}"))
+    }
+
+    /// The output an input spends, from its witness UTXO or else its previous transaction
+    pub fn spent_output(&self, index: usize) -> Result<TxOut, String> {
+        let input = self.input(index)?;
+        if let Some(utxo) = input.get(&[IN_WITNESS_UTXO]) {
+            let mut reader = Reader::new(utxo);
+            let output = TxOut::decode(&mut reader)?;
+            return match reader.is_empty() {
+                true => Ok(output),
+                false => Err(format!("invalid witness UTXO of input {index}")),
+            };
+        }
+
+        let previous = input
+            .get(&[IN_NON_WITNESS_UTXO])
+            .ok_or_else(|| format!("input {index} has no UTXO"))?;
+        let previous = Transaction::decode(previous)
+            .map_err(|e| format!("invalid previous transaction of input {index}: {e}"))?;
+        let outpoint = &self.unsigned_tx.inputs[index];
+        if previous.txid() != outpoint.previous_txid {
+            return Err(format!(
+                "the previous transaction of input {index} is not the one it spends"
+            ));
+        }
+        previous
+            .outputs
+            .get(outpoint.previous_vout as usize)
+            .cloned()
+            .ok_or_else(|| format!("the previous transaction of input {index} has no such output"))
+    }
+
+    /// The sighash type of an input, that of the PSBT if it has one and `requested` must agree with it
+    fn sighash_type(&self, index: usize, requested: Option<u8>, default: u8) -> Result<u8, String> {
+        let field = match self.input(index)?.get(&[IN_SIGHASH_TYPE]) {
+            Some(&[sighash_type, 0, 0, 0]) => Some(sighash_type),
+            Some(_) => return Err(format!("invalid sighash type of input {index}")),
+            None => None,
+        };
+        match (field, requested) {
+            (Some(field), Some(requested)) if field != requested => Err(format!(
+                "input {index} is to be signed with sighash type {field:#04x}"
+            )),
+            (field, requested) => Ok(field.or(requested).unwrap_or(default)),
+        }
+    }
+
+    /// Signature hash of an input, and the sighash type it commits to
+    pub fn sighash(
+        &self,
+        index: usize,
+        requested: Option<u8>,
+    ) -> Result<(SighashKind, [u8; 32], u8), String> {
+        let input = self.input(index)?;
+        let spent = self.spent_output(index)?;
+
+        let script = match p2sh_hash(&spent.script_pubkey) {
+            Some(hash) => {
+                let redeem_script = input
+                    .get(&[IN_REDEEM_SCRIPT])
+                    .ok_or_else(|| format!("input {index} has no redeem script"))?;
+                if hash160(redeem_script) != hash {
+                    return Err(format!("the redeem script of input {index} does not match"));
+                }
+                redeem_script
+            }
+            None => &spent.script_pubkey,
+        };
+
+        match witness_program(script) {
+            Some((0, program)) => {
+                let script_code = match program.len() {
+                    20 => p2pkh_script(program),
+                    32 => {
+                        let witness_script = input
+                            .get(&[IN_WITNESS_SCRIPT])
+                            .ok_or_else(|| format!("input {index} has no witness script"))?;
+                        if Sha256::digest(witness_script).as_slice() != program {
+                            return Err(format!(
+                                "the witness script of input {index} does not match"
+                            ));
+                        }
+                        witness_script.to_vec()
+                    }
+                    _ => return Err(format!("invalid witness program of input {index}")),
+                };
+                let sighash_type = self.sighash_type(index, requested, SIGHASH_ALL)?;
+                check_ecdsa_sighash_type(sighash_type)?;
+                let hash = self.unsigned_tx.segwit_v0_sighash(
+                    index,
+                    &script_code,
+                    spent.value,
+                    sighash_type,
+                );
+                Ok((SighashKind::SegwitV0, hash, sighash_type))
+            }
+            Some((1, program))
+                if program.len() == 32 && script == spent.script_pubkey.as_slice() =>
+            {
+                let spent_outputs = (0..self.inputs.len())
+                    .map(|i| self.spent_output(i))
+                    .collect::<Result<Vec<_>, _>>()?;
+                let sighash_type = self.sighash_type(index, requested, SIGHASH_DEFAULT)?;
+                let hash = self
+                    .unsigned_tx
+                    .taproot_sighash(index, &spent_outputs, sighash_type)?;
+                Ok((SighashKind::Taproot, hash, sighash_type))
+            }
+            Some((version, _)) => Err(format!(
+                "input {index} spends an unsupported witness version {version}"
+            )),
+            None => {
+                let sighash_type = self.sighash_type(index, requested, SIGHASH_ALL)?;
+                check_ecdsa_sighash_type(sighash_type)?;
+                let hash = self.unsigned_tx.legacy_sighash(index, script, sighash_type);
+                Ok((SighashKind::Legacy, hash, sighash_type))
+            }
+        }
+    }
+
+    /// Adds the partial signature of a public key, a DER signature followed by its sighash type
+    pub fn add_partial_signature(&mut self, index: usize, public_key: &[u8], signature: Vec<u8>) {
+        self.inputs[index].insert([&[IN_PARTIAL_SIG], public_key].concat(), signature);
+    }
+
+    /// Finalizes the P2PKH, P2WPKH and P2SH-P2WPKH inputs that are not yet, from the partial signature of their key.
+    /// Other inputs, or those without a signature of their key, are left for other signers and `extract` reports them
+    pub fn finalize(&mut self) {
+        for index in 0..self.inputs.len() {
+            let input = &self.inputs[index];
+            if input.get(&[IN_FINAL_SCRIPTSIG]).is_some()
+                || input.get(&[IN_FINAL_SCRIPTWITNESS]).is_some()
+            {
+                continue;
+            }
+
+            let Ok(spent) = self.spent_output(index) else {
+                continue;
+            };
+            let Some((hash, segwit, script_sig)) =
+                single_key_spend(&spent.script_pubkey, input.get(&[IN_REDEEM_SCRIPT]))
+            else {
+                continue;
+            };
+            let Some((public_key, signature)) =
+                input.of_type(IN_PARTIAL_SIG).find(|(public_key, _)| {
+                    hash160(public_key) == hash && (!segwit || public_key.len() == 33)
+                })
+            else {
+                continue;
+            };
+
+            let mut finalized = Map(input
+                .0
+                .iter()
+                .filter(|(key, _)| {
+                    matches!(key[0], IN_NON_WITNESS_UTXO | IN_WITNESS_UTXO)
+                        || key[0] > IN_LAST_KNOWN
+                })
+                .cloned()
+                .collect());
+            if segwit {
+                let mut witness = Vec::new();
+                write_witness(&[signature.to_vec(), public_key.to_vec()], &mut witness);
+                if !script_sig.is_empty() {
+                    finalized.insert(vec![IN_FINAL_SCRIPTSIG], script_sig);
+                }
+                finalized.insert(vec![IN_FINAL_SCRIPTWITNESS], witness);
+            } else {
+                let mut script_sig = Vec::new();
+                push(signature, &mut script_sig);
+                push(public_key, &mut script_sig);
+                finalized.insert(vec![IN_FINAL_SCRIPTSIG], script_sig);
+            }
+            self.inputs[index] = finalized;
+        }
+    }
+
+    /// The signed transaction of a PSBT whose inputs are all finalized
+    pub fn extract(&self) -> Result<Transaction, String> {
+        let mut transaction = self.unsigned_tx.clone();
+        for (index, (input, map)) in transaction.inputs.iter_mut().zip(&self.inputs).enumerate() {
+            let script_sig = map.get(&[IN_FINAL_SCRIPTSIG]);
+            let witness = map.get(&[IN_FINAL_SCRIPTWITNESS]);
+            if script_sig.is_none() && witness.is_none() {
+                return Err(format!("input {index} is not finalized"));
+            }
+            input.script_sig = script_sig.unwrap_or_default().to_vec();
+            if let Some(witness) = witness {
+                let mut reader = Reader::new(witness);
+                input.witness = (0..reader.count()?)
+                    .map(|_| reader.var_bytes().map(<[u8]>::to_vec))
+                    .collect::<Result<_, String>>()?;
+                if !reader.is_empty() {
+                    return Err(format!("invalid final witness of input {index}"));
+                }
+            }
+        }
+        Ok(transaction)
+    }
+}
+
+/// The types ECDSA signatures can have, which the signature itself carries
+pub fn check_ecdsa_sighash_type(sighash_type: u8) -> Result<(), String> {
+    match sighash_type {
+        0x01..=0x03 | 0x81..=0x83 => Ok(()),
+        _ => Err(format!("invalid sighash type {sighash_type:#04x}")),
+    }
+}
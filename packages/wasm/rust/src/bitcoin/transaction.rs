@@ -0,0 +1,427 @@
+//! Transactions in their consensus encoding and the hashes their inputs sign
+
+use sha2::{Digest, Sha256};
+
+pub const SIGHASH_DEFAULT: u8 = 0x00;
+pub const SIGHASH_ALL: u8 = 0x01;
+pub const SIGHASH_NONE: u8 = 0x02;
+pub const SIGHASH_SINGLE: u8 = 0x03;
+pub const SIGHASH_ANYONECANPAY: u8 = 0x80;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TxIn {
+    /// Internal byte order, reversed from how txids are displayed
+    pub previous_txid: [u8; 32],
+    pub previous_vout: u32,
+    pub script_sig: Vec<u8>,
+    pub sequence: u32,
+    pub witness: Vec<Vec<u8>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TxOut {
+    pub value: u64,
+    pub script_pubkey: Vec<u8>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Transaction {
+    pub version: i32,
+    pub inputs: Vec<TxIn>,
+    pub outputs: Vec<TxOut>,
+    pub lock_time: u32,
+}
+
+/// Reads the little endian integers and length prefixed data of consensus encodings
+pub struct Reader<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    pub fn new(data: &'a [u8]) -> Self {
+        Self { data }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    pub fn peek(&self) -> Option<u8> {
+        self.data.first().copied()
+    }
+
+    pub fn take(&mut self, length: usize) -> Result<&'a [u8], String> {
+        if self.data.len() < length {
+            return Err("unexpected end of data".to_string());
+        }
+        let (taken, rest) = self.data.split_at(length);
+        self.data = rest;
+        Ok(taken)
+    }
+
+    fn array<const N: usize>(&mut self) -> Result<[u8; N], String> {
+        let mut array = [0u8; N];
+        array.copy_from_slice(self.take(N)?);
+        Ok(array)
+    }
+
+    pub fn u8(&mut self) -> Result<u8, String> {
+        Ok(self.take(1)?[0])
+    }
+
+    pub fn u32(&mut self) -> Result<u32, String> {
+        self.array().map(u32::from_le_bytes)
+    }
+
+    pub fn u64(&mut self) -> Result<u64, String> {
+        self.array().map(u64::from_le_bytes)
+    }
+
+    /// A `CompactSize`, rejecting non canonical encodings
+    pub fn compact_size(&mut self) -> Result<u64, String> {
+        let (size, minimum) = match self.u8()? {
+            0xfd => (u64::from(u16::from_le_bytes(self.array()?)), 0xfd),
+            0xfe => (u64::from(self.u32()?), 0x1_0000),
+            0xff => (self.u64()?, 0x1_0000_0000),
+            size => return Ok(u64::from(size)),
+        };
+        if size < minimum {
+            return Err(format!("non canonical compact size {size}"));
+        }
+        Ok(size)
+    }
+
+    pub fn var_bytes(&mut self) -> Result<&'a [u8], String> {
+        let length = self.compact_size()?;
+        let length = usize::try_from(length).map_err(|_| format!("invalid length {length}"))?;
+        self.take(length)
+    }
+
+    /// A `CompactSize` count, bounded by the remaining data so it cannot exhaust memory
+    pub fn count(&mut self) -> Result<usize, String> {
+        let count = self.compact_size()?;
+        usize::try_from(count)
+            .ok()
+            .filter(|count| *count <= self.data.len())
+            .ok_or_else(|| format!("invalid count {count}"))
+    }
+}
+
+pub fn write_compact_size(size: usize, bytes: &mut Vec<u8>) {
+    match size {
+        0..=0xfc => bytes.push(size as u8),
+        0xfd..=0xffff => {
+            bytes.push(0xfd);
+            bytes.extend_from_slice(&(size as u16).to_le_bytes());
+        }
+        0x1_0000..=0xffff_ffff => {
+            bytes.push(0xfe);
+            bytes.extend_from_slice(&(size as u32).to_le_bytes());
+        }
+        _ => {
+            bytes.push(0xff);
+            bytes.extend_from_slice(&(size as u64).to_le_bytes());
+        }
+    }
+}
+
+pub fn write_var_bytes(data: &[u8], bytes: &mut Vec<u8>) {
+    write_compact_size(data.len(), bytes);
+    bytes.extend_from_slice(data);
+}
+
+pub fn write_witness(witness: &[Vec<u8>], bytes: &mut Vec<u8>) {
+    write_compact_size(witness.len(), bytes);
+    for item in witness {
+        write_var_bytes(item, bytes);
+    }
+}
+
+pub fn sha256d(data: &[u8]) -> [u8; 32] {
+    Sha256::digest(Sha256::digest(data)).into()
+}
+
+/// `sha256(sha256(tag) ‖ sha256(tag) ‖ data)` of BIP-340
+pub fn tagged_hash(tag: &str, data: &[u8]) -> [u8; 32] {
+    let tag = Sha256::digest(tag);
+    Sha256::new()
+        .chain_update(tag)
+        .chain_update(tag)
+        .chain_update(data)
+        .finalize()
+        .into()
+}
+
+impl TxOut {
+    fn encode(&self, bytes: &mut Vec<u8>) {
+        bytes.extend_from_slice(&self.value.to_le_bytes());
+        write_var_bytes(&self.script_pubkey, bytes);
+    }
+
+    pub fn decode(reader: &mut Reader) -> Result<Self, String> {
+        Ok(Self {
+            value: reader.u64()?,
+            script_pubkey: reader.var_bytes()?.to_vec(),
+        })
+    }
+}
+
+impl TxIn {
+    fn encode_outpoint(&self, bytes: &mut Vec<u8>) {
+        bytes.extend_from_slice(&self.previous_txid);
+        bytes.extend_from_slice(&self.previous_vout.to_le_bytes());
+    }
+}
+
+impl Transaction {
+    /// Consensus encoding, with the BIP-144 marker and witnesses when asked and any input has one
+    pub fn encode(&self, with_witness: bool) -> Vec<u8> {
+        let with_witness =
+            with_witness && self.inputs.iter().any(|input| !input.witness.is_empty());
+        let mut bytes = self.version.to_le_bytes().to_vec();
+        if with_witness {
+            bytes.extend_from_slice(&[0x00, 0x01]);
+        }
+        write_compact_size(self.inputs.len(), &mut bytes);
+        for input in &self.inputs {
+            input.encode_outpoint(&mut bytes);
+            write_var_bytes(&input.script_sig, &mut bytes);
+            bytes.extend_from_slice(&input.sequence.to_le_bytes());
+        }
+        write_compact_size(self.outputs.len(), &mut bytes);
+        for output in &self.outputs {
+            output.encode(&mut bytes);
+        }
+        if with_witness {
+            for input in &self.inputs {
+                write_witness(&input.witness, &mut bytes);
+            }
+        }
+        bytes.extend_from_slice(&self.lock_time.to_le_bytes());
+        bytes
+    }
+
+    /// Decodes a transaction spanning all the data, with or without witnesses
+    pub fn decode(data: &[u8]) -> Result<Self, String> {
+        let mut reader = Reader::new(data);
+        let version = i32::from_le_bytes(reader.array()?);
+
+        let with_witness = reader.peek() == Some(0x00);
+        if with_witness && reader.take(2)? != [0x00, 0x01] {
+            return Err("invalid segwit marker".to_string());
+        }
+
+        let mut inputs = (0..reader.count()?)
+            .map(|_| {
+                Ok(TxIn {
+                    previous_txid: reader.array()?,
+                    previous_vout: reader.u32()?,
+                    script_sig: reader.var_bytes()?.to_vec(),
+                    sequence: reader.u32()?,
+                    witness: Vec::new(),
+                })
+            })
+            .collect::<Result<Vec<_>, String>>()?;
+        let outputs = (0..reader.count()?)
+            .map(|_| TxOut::decode(&mut reader))
+            .collect::<Result<Vec<_>, String>>()?;
+        if with_witness {
+            for input in &mut inputs {
+                input.witness = (0..reader.count()?)
+                    .map(|_| reader.var_bytes().map(<[u8]>::to_vec))
+                    .collect::<Result<_, String>>()?;
+            }
+            if inputs.iter().all(|input| input.witness.is_empty()) {
+                return Err("segwit marker without witnesses".to_string());
+            }
+        }
+        let lock_time = reader.u32()?;
+        if !reader.is_empty() {
+            return Err("trailing bytes after the transaction".to_string());
+        }
+
+        Ok(Self {
+            version,
+            inputs,
+            outputs,
+            lock_time,
+        })
+    }
+
+    /// Internal byte order, reversed from how txids are displayed
+    pub fn txid(&self) -> [u8; 32] {
+        sha256d(&self.encode(false))
+    }
+
+    /// Signature hash of the original, pre segwit, scheme. Code separators are not removed from the script code.
+    pub fn legacy_sighash(&self, index: usize, script_code: &[u8], sighash_type: u8) -> [u8; 32] {
+        let base_type = sighash_type & 0x1f;
+        // The infamous hash of one, kept for consensus
+        if base_type == SIGHASH_SINGLE && index >= self.outputs.len() {
+            let mut one = [0u8; 32];
+            one[0] = 1;
+            return one;
+        }
+
+        let mut transaction = self.clone();
+        for (i, input) in transaction.inputs.iter_mut().enumerate() {
+            input.script_sig = if i == index {
+                script_code.to_vec()
+            } else {
+                Vec::new()
+            };
+            input.witness.clear();
+            if i != index && matches!(base_type, SIGHASH_NONE | SIGHASH_SINGLE) {
+                input.sequence = 0;
+            }
+        }
+        match base_type {
+            SIGHASH_NONE => transaction.outputs.clear(),
+            SIGHASH_SINGLE => {
+                transaction.outputs.truncate(index + 1);
+                for output in &mut transaction.outputs[..index] {
+                    output.value = u64::MAX;
+                    output.script_pubkey.clear();
+                }
+            }
+            _ => {}
+        }
+        if sighash_type & SIGHASH_ANYONECANPAY != 0 {
+            transaction.inputs = vec![transaction.inputs.swap_remove(index)];
+        }
+
+        let mut preimage = transaction.encode(false);
+        preimage.extend_from_slice(&u32::from(sighash_type).to_le_bytes());
+        sha256d(&preimage)
+    }
+
+    /// Signature hash of segwit v0 inputs, BIP-143
+    pub fn segwit_v0_sighash(
+        &self,
+        index: usize,
+        script_code: &[u8],
+        value: u64,
+        sighash_type: u8,
+    ) -> [u8; 32] {
+        let base_type = sighash_type & 0x1f;
+        let anyone_can_pay = sighash_type & SIGHASH_ANYONECANPAY != 0;
+        let input = &self.inputs[index];
+
+        let hash_prevouts = if anyone_can_pay {
+            [0u8; 32]
+        } else {
+            sha256d(&self.prevouts())
+        };
+        let hash_sequence = if anyone_can_pay || matches!(base_type, SIGHASH_NONE | SIGHASH_SINGLE)
+        {
+            [0u8; 32]
+        } else {
+            sha256d(&self.sequences())
+        };
+        let hash_outputs = match base_type {
+            SIGHASH_NONE => [0u8; 32],
+            SIGHASH_SINGLE if index >= self.outputs.len() => [0u8; 32],
+            SIGHASH_SINGLE => {
+                let mut output = Vec::new();
+                self.outputs[index].encode(&mut output);
+                sha256d(&output)
+            }
+            _ => sha256d(&self.encoded_outputs()),
+        };
+
+        let mut preimage = self.version.to_le_bytes().to_vec();
+        preimage.extend_from_slice(&hash_prevouts);
+        preimage.extend_from_slice(&hash_sequence);
+        input.encode_outpoint(&mut preimage);
+        write_var_bytes(script_code, &mut preimage);
+        preimage.extend_from_slice(&value.to_le_bytes());
+        preimage.extend_from_slice(&input.sequence.to_le_bytes());
+        preimage.extend_from_slice(&hash_outputs);
+        preimage.extend_from_slice(&self.lock_time.to_le_bytes());
+        preimage.extend_from_slice(&u32::from(sighash_type).to_le_bytes());
+        sha256d(&preimage)
+    }
+
+    /// Signature hash of a taproot key path spend, BIP-341.
+    /// `spent_outputs` are the outputs every input spends, in order.
+    pub fn taproot_sighash(
+        &self,
+        index: usize,
+        spent_outputs: &[TxOut],
+        sighash_type: u8,
+    ) -> Result<[u8; 32], String> {
+        if !matches!(sighash_type, 0x00..=0x03 | 0x81..=0x83) {
+            return Err(format!("invalid taproot sighash type {sighash_type:#04x}"));
+        }
+        if spent_outputs.len() != self.inputs.len() {
+            return Err("the outputs spent by every input are needed".to_string());
+        }
+        let base_type = sighash_type & 0x03;
+        let anyone_can_pay = sighash_type & SIGHASH_ANYONECANPAY != 0;
+        let sha256 = |data: &[u8]| <[u8; 32]>::from(Sha256::digest(data));
+
+        let mut message = vec![0x00, sighash_type];
+        message.extend_from_slice(&self.version.to_le_bytes());
+        message.extend_from_slice(&self.lock_time.to_le_bytes());
+        if !anyone_can_pay {
+            let mut amounts = Vec::new();
+            let mut script_pubkeys = Vec::new();
+            for output in spent_outputs {
+                amounts.extend_from_slice(&output.value.to_le_bytes());
+                write_var_bytes(&output.script_pubkey, &mut script_pubkeys);
+            }
+            message.extend_from_slice(&sha256(&self.prevouts()));
+            message.extend_from_slice(&sha256(&amounts));
+            message.extend_from_slice(&sha256(&script_pubkeys));
+            message.extend_from_slice(&sha256(&self.sequences()));
+        }
+        if !matches!(base_type, SIGHASH_NONE | SIGHASH_SINGLE) {
+            message.extend_from_slice(&sha256(&self.encoded_outputs()));
+        }
+
+        // Key path spend without an annex
+        message.push(0x00);
+        if anyone_can_pay {
+            let input = &self.inputs[index];
+            input.encode_outpoint(&mut message);
+            spent_outputs[index].encode(&mut message);
+            message.extend_from_slice(&input.sequence.to_le_bytes());
+        } else {
+            message.extend_from_slice(&(index as u32).to_le_bytes());
+        }
+        if base_type == SIGHASH_SINGLE {
+            let output = self
+                .outputs
+                .get(index)
+                .ok_or("SIGHASH_SINGLE without a matching output")?;
+            let mut encoded = Vec::new();
+            output.encode(&mut encoded);
+            message.extend_from_slice(&sha256(&encoded));
+        }
+
+        Ok(tagged_hash("TapSighash", &message))
+    }
+
+    fn prevouts(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        for input in &self.inputs {
+            input.encode_outpoint(&mut bytes);
+        }
+        bytes
+    }
+
+    fn sequences(&self) -> Vec<u8> {
+        self.inputs
+            .iter()
+            .flat_map(|input| input.sequence.to_le_bytes())
+            .collect()
+    }
+
+    fn encoded_outputs(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        for output in &self.outputs {
+            output.encode(&mut bytes);
+        }
+        bytes
+    }
+}
@@ -11,6 +11,7 @@ use tsify::Tsify;
 use wasm_bindgen::prelude::*;
 
 use crate::abi::{from_js, from_uint8array, into_uint8array, JsResult};
+use crate::error::LitCryptoError;
 
 #[derive(Tsify, Deserialize)]
 #[tsify(from_wasm_abi)]
@@ -30,7 +31,10 @@ where
     pub fn combine(signature_shares: Vec<Uint8Array>) -> JsResult<Uint8Array> {
         let signature_shares = signature_shares
             .into_iter()
-            .map(from_uint8array)
+            .enumerate()
+            .map(|(index, share)| {
+                from_uint8array(share).map_err(|_| LitCryptoError::InvalidShare(index))
+            })
             .collect::<JsResult<Vec<_>>>()?;
 
         let signature = C::core_combine_signature_shares(&signature_shares)?;
@@ -49,7 +53,9 @@ where
 
         let signature = Signature::<C>::ProofOfPossession(signature);
 
-        signature.verify(&PublicKey(public_key), message)?;
+        signature
+            .verify(&PublicKey(public_key), message)
+            .map_err(|_| LitCryptoError::SignatureMismatch)?;
 
         Ok(())
     }
@@ -70,7 +76,8 @@ where
             message,
             identity,
         )?;
-        let ciphertext = serde_bare::to_vec(&ciphertext)?;
+        let ciphertext = serde_bare::to_vec(&ciphertext)
+            .map_err(|e| LitCryptoError::Serialize(e.to_string()))?;
 
         into_uint8array(ciphertext)
     }
@@ -79,11 +86,11 @@ where
         let decryption_key = from_uint8array(decryption_key)?;
 
         let ciphertext = from_js::<Vec<u8>>(ciphertext)?;
-        let ciphertext = serde_bare::from_slice::<TimeCryptCiphertext<C>>(&ciphertext)?;
+        let ciphertext = serde_bare::from_slice::<TimeCryptCiphertext<C>>(&ciphertext)
+            .map_err(|e| LitCryptoError::Deserialize(e.to_string()))?;
 
         let message = ciphertext.decrypt(&Signature::ProofOfPossession(decryption_key));
-        let message =
-            Option::<Vec<u8>>::from(message).ok_or_else(|| JsError::new("decryption failed"))?;
+        let message = Option::<Vec<u8>>::from(message).ok_or(LitCryptoError::DecryptionFailed)?;
 
         into_uint8array(message)
     }
@@ -0,0 +1,247 @@
+//! Cosmos SDK sign docs, signatures and addresses of secp256k1 keys
+
+use base64_light::base64_encode_bytes;
+use bech32::{Bech32, Hrp};
+use elliptic_curve::{scalar::IsHigh as _, sec1::ToEncodedPoint};
+use js_sys::Uint8Array;
+use k256::ecdsa::{signature::hazmat::PrehashVerifier, Signature, VerifyingKey};
+use k256::Secp256k1;
+use ripemd::Ripemd160;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use sha2::{Digest, Sha256};
+use tsify::Tsify;
+use wasm_bindgen::prelude::*;
+
+use crate::abi::{from_js, into_uint8array, JsResult};
+use crate::ecdsa::{Ecdsa, EcdsaSignature};
+use crate::error::LitCryptoError;
+
+/// A `SignDoc` of `SIGN_MODE_DIRECT`, its body and auth info already protobuf encoded
+#[derive(Tsify, Deserialize, Debug, Clone)]
+#[tsify(from_wasm_abi)]
+#[serde(rename_all = "camelCase")]
+pub struct DirectSignDoc {
+    #[tsify(type = "Uint8Array")]
+    #[serde(with = "serde_bytes")]
+    pub body_bytes: Vec<u8>,
+    #[tsify(type = "Uint8Array")]
+    #[serde(with = "serde_bytes")]
+    pub auth_info_bytes: Vec<u8>,
+    pub chain_id: String,
+    /// A decimal string past `Number.MAX_SAFE_INTEGER`
+    #[tsify(type = "number | string")]
+    #[serde(deserialize_with = "u64_from_number_or_string")]
+    pub account_number: u64,
+}
+
+/// A `StdSignDoc` of `SIGN_MODE_LEGACY_AMINO_JSON`
+#[derive(Tsify, Serialize, Deserialize, Debug, Clone)]
+#[tsify(from_wasm_abi)]
+pub struct AminoSignDoc {
+    pub chain_id: String,
+    pub account_number: String,
+    pub sequence: String,
+    #[tsify(
+        type = "{ amount: { denom: string; amount: string }[]; gas: string; granter?: string; payer?: string }"
+    )]
+    pub fee: Value,
+    #[tsify(type = "{ type: string; value: unknown }[]")]
+    pub msgs: Vec<Value>,
+    pub memo: String,
+    #[tsify(optional)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout_height: Option<String>,
+}
+
+/// The bytes a sign doc is signed as, and their SHA-256 digest to pass as `message_hash`
+#[derive(Tsify, Serialize, Debug, Clone)]
+#[tsify(into_wasm_abi)]
+#[serde(rename_all = "camelCase")]
+pub struct CosmosSignBytes {
+    #[tsify(type = "Uint8Array")]
+    #[serde(with = "serde_bytes")]
+    pub sign_bytes: Vec<u8>,
+    #[tsify(type = "Uint8Array")]
+    #[serde(with = "serde_bytes")]
+    pub hash: Vec<u8>,
+}
+
+impl CosmosSignBytes {
+    fn new(sign_bytes: Vec<u8>) -> Self {
+        let hash = Sha256::digest(&sign_bytes).to_vec();
+        Self { sign_bytes, hash }
+    }
+}
+
+/// A signature as amino JSON carries it, what cosmjs `encodeSecp256k1Signature` returns
+#[derive(Tsify, Serialize, Debug, Clone)]
+#[tsify(into_wasm_abi)]
+pub struct StdSignature {
+    pub pub_key: StdPubKey,
+    /// Base64 of `r ‖ s`
+    pub signature: String,
+}
+
+#[derive(Tsify, Serialize, Debug, Clone)]
+pub struct StdPubKey {
+    /// Always `tendermint/PubKeySecp256k1`
+    #[serde(rename = "type")]
+    pub kind: String,
+    /// Base64 of the compressed key
+    pub value: String,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum NumberOrString {
+    Number(u64),
+    String(String),
+}
+
+fn u64_from_number_or_string<'de, D: serde::Deserializer<'de>>(
+    deserializer: D,
+) -> Result<u64, D::Error> {
+    match NumberOrString::deserialize(deserializer)? {
+        NumberOrString::Number(number) => Ok(number),
+        NumberOrString::String(number) => number.parse().map_err(serde::de::Error::custom),
+    }
+}
+
+impl DirectSignDoc {
+    /// Protobuf encoding of the doc, without the fields holding their default value as proto3 does
+    pub fn sign_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        let fields = [
+            (1, self.body_bytes.as_slice()),
+            (2, self.auth_info_bytes.as_slice()),
+            (3, self.chain_id.as_bytes()),
+        ];
+        for (number, value) in fields.into_iter().filter(|(_, value)| !value.is_empty()) {
+            // Length delimited
+            bytes.push(number << 3 | 2);
+            encode_varint(value.len() as u64, &mut bytes);
+            bytes.extend_from_slice(value);
+        }
+        if self.account_number != 0 {
+            bytes.push(4 << 3);
+            encode_varint(self.account_number, &mut bytes);
+        }
+        bytes
+    }
+}
+
+fn encode_varint(mut value: u64, bytes: &mut Vec<u8>) {
+    while value >= 0x80 {
+        bytes.push(value as u8 | 0x80);
+        value >>= 7;
+    }
+    bytes.push(value as u8);
+}
+
+impl AminoSignDoc {
+    /// JSON of the doc with its keys sorted and `&`, `<` and `>` escaped, as cosmjs `serializeSignDoc` does
+    pub fn sign_bytes(&self) -> JsResult<Vec<u8>> {
+        let value =
+            serde_json::to_value(self).map_err(|e| LitCryptoError::Serialize(e.to_string()))?;
+        let json = sort_keys(value)
+            .to_string()
+            .replace('&', "\\u0026")
+            .replace('<', "\\u003c")
+            .replace('>', "\\u003e");
+        Ok(json.into_bytes())
+    }
+}
+
+fn sort_keys(value: Value) -> Value {
+    match value {
+        Value::Object(object) => {
+            let mut entries = object.into_iter().collect::<Vec<_>>();
+            entries.sort_by(|(a, _), (b, _)| a.cmp(b));
+            Value::Object(
+                entries
+                    .into_iter()
+                    .map(|(key, value)| (key, sort_keys(value)))
+                    .collect::<Map<_, _>>(),
+            )
+        }
+        Value::Array(items) => Value::Array(items.into_iter().map(sort_keys).collect()),
+        value => value,
+    }
+}
+
+fn public_key_from_js(public_key: Uint8Array) -> JsResult<k256::PublicKey> {
+    let public_key = from_js::<Vec<u8>>(public_key)?;
+    k256::PublicKey::from_sec1_bytes(&public_key).map_err(|_| LitCryptoError::InvalidPoint)
+}
+
+fn compressed(public_key: &k256::PublicKey) -> Vec<u8> {
+    public_key.to_encoded_point(true).as_bytes().to_vec()
+}
+
+/// Sign bytes of a `SIGN_MODE_DIRECT` sign doc and their digest
+#[wasm_bindgen(js_name = "cosmosDirectSignBytes")]
+pub fn cosmos_direct_sign_bytes(sign_doc: DirectSignDoc) -> JsResult<CosmosSignBytes> {
+    Ok(CosmosSignBytes::new(sign_doc.sign_bytes()))
+}
+
+/// Sign bytes of an amino JSON sign doc and their digest
+#[wasm_bindgen(js_name = "cosmosAminoSignBytes")]
+pub fn cosmos_amino_sign_bytes(sign_doc: AminoSignDoc) -> JsResult<CosmosSignBytes> {
+    Ok(CosmosSignBytes::new(sign_doc.sign_bytes()?))
+}
+
+/// 64 bytes `r ‖ s` of a combined K256 signature with a low `s`, as Cosmos SDK chains require
+#[wasm_bindgen(js_name = "cosmosSignature")]
+pub fn cosmos_signature(signature: EcdsaSignature) -> JsResult<Uint8Array> {
+    into_uint8array(Ecdsa::<Secp256k1>::low_s_signature(signature)?.0)
+}
+
+/// A combined K256 signature with its public key, as amino JSON carries them
+#[wasm_bindgen(js_name = "cosmosStdSignature")]
+pub fn cosmos_std_signature(
+    public_key: Uint8Array,
+    signature: EcdsaSignature,
+) -> JsResult<StdSignature> {
+    let public_key = public_key_from_js(public_key)?;
+    let (rs, _) = Ecdsa::<Secp256k1>::low_s_signature(signature)?;
+
+    Ok(StdSignature {
+        pub_key: StdPubKey {
+            kind: "tendermint/PubKeySecp256k1".to_string(),
+            value: base64_encode_bytes(&compressed(&public_key)),
+        },
+        signature: base64_encode_bytes(&rs),
+    })
+}
+
+/// Bech32 account address of a secp256k1 public key, `ripemd160(sha256(compressed key))`
+#[wasm_bindgen(js_name = "cosmosAddress")]
+pub fn cosmos_address(public_key: Uint8Array, prefix: String) -> JsResult<String> {
+    let public_key = public_key_from_js(public_key)?;
+    let hrp = Hrp::parse(&prefix).map_err(|e| LitCryptoError::AddressPrefix(e.to_string()))?;
+
+    let hash = Ripemd160::digest(Sha256::digest(compressed(&public_key)));
+    bech32::encode::<Bech32>(hrp, &hash).map_err(|e| LitCryptoError::Serialize(e.to_string()))
+}
+
+/// Verifies a 64 bytes `r ‖ s` signature of sign bytes as Cosmos SDK chains do, high `s` values are rejected
+#[wasm_bindgen(js_name = "cosmosVerify")]
+pub fn cosmos_verify(
+    sign_bytes: Uint8Array,
+    signature: Uint8Array,
+    public_key: Uint8Array,
+) -> JsResult<()> {
+    let sign_bytes = from_js::<Vec<u8>>(sign_bytes)?;
+    let signature = from_js::<Vec<u8>>(signature)?;
+    let public_key = VerifyingKey::from(public_key_from_js(public_key)?);
+
+    let signature =
+        Signature::from_slice(&signature).map_err(|_| LitCryptoError::InvalidSignature)?;
+    if signature.s().is_high().into() {
+        return Err(LitCryptoError::InvalidSignature);
+    }
+    public_key
+        .verify_prehash(&Sha256::digest(&sign_bytes), &signature)
+        .map_err(|_| LitCryptoError::SignatureMismatch)
+}
@@ -12,23 +12,27 @@ use hd_keys_curves_wasm::{HDDerivable, HDDeriver};
 use js_sys::Uint8Array;
 use k256::Secp256k1;
 use p256::NistP256;
+use p384::NistP384;
 use serde::Deserialize;
 use serde_bytes::Bytes;
 use tsify::Tsify;
-use wasm_bindgen::{prelude::*, JsError};
+use wasm_bindgen::prelude::*;
 
 use crate::abi::{from_js, into_js, into_uint8array, JsResult};
+use crate::error::LitCryptoError;
+use crate::hash::MessageHasher;
 
-#[derive(Tsify, Deserialize)]
+#[derive(Tsify, Deserialize, Debug, Clone, Copy)]
 #[tsify(from_wasm_abi)]
 pub enum EcdsaVariant {
     K256,
     P256,
+    P384,
 }
 
-struct Ecdsa<C>(C);
+pub(crate) struct Ecdsa<C>(C);
 
-trait HdCtx {
+pub(crate) trait HdCtx {
     const CTX: &'static [u8];
 }
 
@@ -40,6 +44,10 @@ impl HdCtx for NistP256 {
     const CTX: &'static [u8] = b"LIT_HD_KEY_ID_P256_XMD:SHA-256_SSWU_RO_NUL_";
 }
 
+impl HdCtx for NistP384 {
+    const CTX: &'static [u8] = b"LIT_HD_KEY_ID_P384_XMD:SHA-384_SSWU_RO_NUL_";
+}
+
 #[wasm_bindgen]
 extern "C" {
     #[wasm_bindgen(typescript_type = "[Uint8Array, Uint8Array, number]")]
@@ -49,7 +57,7 @@ extern "C" {
 impl<C> Ecdsa<C>
 where
     C: PrimeCurve + CurveArithmetic + HdCtx,
-    C::AffinePoint: GroupEncoding + FromEncodedPoint<C>,
+    C::AffinePoint: GroupEncoding + FromEncodedPoint<C> + ToEncodedPoint<C>,
     C::Scalar: HDDeriver,
     C::FieldBytesSize: ModulusSize,
     C::ProjectivePoint: CofactorGroup + HDDerivable + FromEncodedPoint<C> + ToEncodedPoint<C>,
@@ -68,7 +76,10 @@ where
     ) -> JsResult<(C::ProjectivePoint, C::Scalar, bool)> {
         let signature_shares = signature_shares
             .into_iter()
-            .map(Self::scalar_from_js)
+            .enumerate()
+            .map(|(index, share)| {
+                Self::scalar_from_js(share).map_err(|_| LitCryptoError::InvalidShare(index))
+            })
             .collect::<JsResult<Vec<_>>>()?;
 
         let big_r: C::AffinePoint = Self::point_from_js(presignature)?;
@@ -87,14 +98,14 @@ where
         let public_key: C::ProjectivePoint = Self::point_from_js(public_key)?;
 
         if r.is_zero().into() {
-            return Err(JsError::new("invalid signature"));
+            return Err(LitCryptoError::InvalidSignature);
         }
         // This will fail if s == 0
         let s_inv = Option::<C::Scalar>::from(s.invert_vartime())
-            .ok_or_else(|| JsError::new("invalid signature"))?;
+            .ok_or(LitCryptoError::InvalidSignature)?;
 
         if z.is_zero().into() {
-            return Err(JsError::new("invalid message digest"));
+            return Err(LitCryptoError::InvalidDigest);
         }
 
         let reproduced =
@@ -102,7 +113,7 @@ where
         let reproduced_x = Self::x_coordinate(&reproduced.to_affine());
 
         if reproduced_x != r {
-            return Err(JsError::new("invalid signature"));
+            return Err(LitCryptoError::SignatureMismatch);
         }
 
         Ok(())
@@ -110,7 +121,7 @@ where
 
     fn sum_scalars(values: Vec<C::Scalar>) -> JsResult<(C::Scalar, bool)> {
         if values.is_empty() {
-            return Err(JsError::new("no shares provided"));
+            return Err(LitCryptoError::NoShares);
         }
         let mut acc: C::Scalar = values.into_iter().sum();
         let acc_flipped = acc.is_high().into();
@@ -118,6 +129,21 @@ where
         Ok((acc, acc_flipped))
     }
 
+    fn hd_table(public_keys: Vec<Uint8Array>) -> JsResult<HdTable<C>> {
+        let public_keys = public_keys
+            .into_iter()
+            .map(Self::point_from_js::<C::ProjectivePoint>)
+            .collect::<JsResult<Vec<_>>>()?;
+        Ok(HdTable::new(&public_keys))
+    }
+
+    fn encode_points(points: &[C::ProjectivePoint]) -> JsResult<Vec<Uint8Array>> {
+        normalize::<C>(points)
+            .into_iter()
+            .map(|point| into_uint8array(point.to_encoded_point(false).as_bytes()))
+            .collect()
+    }
+
     pub fn derive_key(id: Uint8Array, public_keys: Vec<Uint8Array>) -> JsResult<Uint8Array> {
         let k = Self::derive_key_inner(id, public_keys)?;
         let k = k.to_encoded_point(false);
@@ -130,12 +156,28 @@ where
         public_keys: Vec<Uint8Array>,
     ) -> JsResult<C::ProjectivePoint> {
         let id = from_js::<Vec<u8>>(id)?;
+        Self::derive_point(&id, public_keys)
+    }
+
+    pub(crate) fn derive_point(
+        id: &[u8],
+        public_keys: Vec<Uint8Array>,
+    ) -> JsResult<C::ProjectivePoint> {
         let public_keys = public_keys
             .into_iter()
             .map(Self::point_from_js::<C::ProjectivePoint>)
             .collect::<JsResult<Vec<_>>>()?;
 
-        let deriver = C::Scalar::create(&id, C::CTX);
+        let deriver = C::Scalar::create(id, C::CTX);
+        // The sum of products of hd_derive_public_key only reads the low 256 bits of the powers of the tweak
+        if <C::Scalar as PrimeField>::NUM_BITS > 256 && public_keys.len() > 1 {
+            return Ok(public_keys
+                .iter()
+                .rev()
+                .fold(C::ProjectivePoint::identity(), |acc, key| {
+                    acc * deriver + key
+                }));
+        }
         Ok(deriver.hd_derive_public_key(&public_keys))
     }
 
@@ -145,19 +187,23 @@ where
     }
 
     fn scalar_from_bytes(s: Vec<u8>) -> JsResult<C::Scalar> {
+        // from_slice panics on any other length
+        if s.len() != C::FieldBytesSize::to_usize() {
+            return Err(LitCryptoError::InvalidScalar);
+        }
         let s = C::Scalar::from_repr(<C::Scalar as PrimeField>::Repr::from_slice(&s).clone());
         let s = Option::from(s);
-        let s = s.ok_or_else(|| JsError::new("cannot deserialize"))?;
+        let s = s.ok_or(LitCryptoError::InvalidScalar)?;
 
         Ok(s)
     }
 
     fn point_from_js<T: FromEncodedPoint<C>>(q: Uint8Array) -> JsResult<T> {
         let q = from_js::<Vec<u8>>(q)?;
-        let q = EncodedPoint::<C>::from_bytes(q)?;
+        let q = EncodedPoint::<C>::from_bytes(q).map_err(|_| LitCryptoError::InvalidPoint)?;
         let q = T::from_encoded_point(&q);
         let q = Option::<T>::from(q);
-        let q = q.ok_or_else(|| JsError::new("cannot deserialize"))?;
+        let q = q.ok_or(LitCryptoError::InvalidPoint)?;
 
         Ok(q)
     }
@@ -169,7 +215,38 @@ where
         Ok((r, s, v))
     }
 
-    fn signature_into_js(big_r: C::AffinePoint, s: C::Scalar, was_flipped: bool) -> JsResult<EcdsaSignature> {
+    /// `r ‖ s` and the recovery id of a combined signature, with `s` normalized to its low value
+    #[cfg(any(
+        feature = "bitcoin",
+        feature = "cosmos",
+        feature = "ethereum",
+        feature = "sui"
+    ))]
+    pub(crate) fn low_s_signature(signature: EcdsaSignature) -> JsResult<(Vec<u8>, u8)> {
+        let (r, s, v) = Self::signature_from_js(signature)?;
+        let recovery_id = match v {
+            0 | 1 => v,
+            27 | 28 => v - 27,
+            _ => return Err(LitCryptoError::InvalidSignature),
+        };
+        if (r.is_zero() | s.is_zero()).into() {
+            return Err(LitCryptoError::InvalidSignature);
+        }
+
+        let flipped = bool::from(s.is_high());
+        let s = if flipped { -s } else { s };
+        let (r, s) = (r.to_repr(), s.to_repr());
+        Ok((
+            [r.as_slice(), s.as_slice()].concat(),
+            recovery_id ^ u8::from(flipped),
+        ))
+    }
+
+    fn signature_into_js(
+        big_r: C::AffinePoint,
+        s: C::Scalar,
+        was_flipped: bool,
+    ) -> JsResult<EcdsaSignature> {
         let r = Self::x_coordinate(&big_r).to_repr();
         let s = s.to_repr();
         let mut v = u8::conditional_select(&0, &1, big_r.y_is_odd());
@@ -190,11 +267,17 @@ where
 
     pub fn scalar_from_hash(msg_digest: Uint8Array) -> JsResult<C::Scalar> {
         let digest = from_js::<Vec<u8>>(msg_digest)?;
+        Self::scalar_from_digest(&digest)
+    }
+
+    fn scalar_from_digest(digest: &[u8]) -> JsResult<C::Scalar> {
         if digest.len() != C::FieldBytesSize::to_usize() {
-            return Err(JsError::new("invalid message digest length"));
+            return Err(LitCryptoError::DigestLength {
+                expected: C::FieldBytesSize::to_usize(),
+                actual: digest.len(),
+            });
         }
-        let z_bytes =
-            <C::Scalar as Reduce<<C as ECurve>::Uint>>::Bytes::from_slice(digest.as_slice());
+        let z_bytes = <C::Scalar as Reduce<<C as ECurve>::Uint>>::Bytes::from_slice(digest);
         Ok(<C::Scalar as Reduce<<C as ECurve>::Uint>>::reduce_bytes(
             z_bytes,
         ))
@@ -207,8 +290,9 @@ where
         id: Uint8Array,
         public_keys: Vec<Uint8Array>,
     ) -> JsResult<EcdsaSignature> {
+        let z = Self::scalar_from_hash(message_hash)?;
         let public_key = Self::derive_key_inner(id, public_keys)?;
-        Self::combine_and_verify(pre_signature, signature_shares, message_hash, public_key)
+        Self::combine_and_verify(pre_signature, signature_shares, z, public_key)
     }
 
     pub fn combine_and_verify_with_specified_key(
@@ -217,39 +301,211 @@ where
         message_hash: Uint8Array,
         public_key: Uint8Array,
     ) -> JsResult<EcdsaSignature> {
+        let z = Self::scalar_from_hash(message_hash)?;
+        let public_key: C::ProjectivePoint = Self::point_from_js(public_key)?;
+        Self::combine_and_verify(pre_signature, signature_shares, z, public_key)
+    }
+
+    pub fn combine_and_verify_message_with_derived_key(
+        pre_signature: Uint8Array,
+        signature_shares: Vec<Uint8Array>,
+        message: Uint8Array,
+        hasher: MessageHasher,
+        id: Uint8Array,
+        public_keys: Vec<Uint8Array>,
+    ) -> JsResult<EcdsaSignature> {
+        let z = Self::scalar_from_message(message, hasher)?;
+        let public_key = Self::derive_key_inner(id, public_keys)?;
+        Self::combine_and_verify(pre_signature, signature_shares, z, public_key)
+    }
+
+    pub fn combine_and_verify_message_with_specified_key(
+        pre_signature: Uint8Array,
+        signature_shares: Vec<Uint8Array>,
+        message: Uint8Array,
+        hasher: MessageHasher,
+        public_key: Uint8Array,
+    ) -> JsResult<EcdsaSignature> {
+        let z = Self::scalar_from_message(message, hasher)?;
         let public_key: C::ProjectivePoint = Self::point_from_js(public_key)?;
-        Self::combine_and_verify(pre_signature, signature_shares, message_hash, public_key)
+        Self::combine_and_verify(pre_signature, signature_shares, z, public_key)
+    }
+
+    fn scalar_from_message(message: Uint8Array, hasher: MessageHasher) -> JsResult<C::Scalar> {
+        let message = from_js::<Vec<u8>>(message)?;
+        Self::scalar_from_digest(&hasher.hash(&message)?)
     }
 
     fn combine_and_verify(
         pre_signature: Uint8Array,
         signature_shares: Vec<Uint8Array>,
-        message_hash: Uint8Array,
+        z: C::Scalar,
         public_key: C::ProjectivePoint,
     ) -> JsResult<EcdsaSignature> {
-        let z = Self::scalar_from_hash(message_hash)?;
         let (big_r, s, was_flipped) = Self::combine_inner(pre_signature, signature_shares)?;
         let r = Self::x_coordinate(&big_r.to_affine());
 
         if z.is_zero().into() {
-            return Err(JsError::new("invalid message digest"));
+            return Err(LitCryptoError::InvalidDigest);
         }
         if (s.is_zero() | big_r.is_identity()).into() {
-            return Err(JsError::new("invalid signature"));
+            return Err(LitCryptoError::InvalidSignature);
         }
         if r.is_zero().into() {
-            return Err(JsError::new("invalid r coordinate"));
+            return Err(LitCryptoError::InvalidSignature);
         }
         // sR == zG * rY =
         // (z + rx/k) * k * G == zG + rxG =
         // (z + rx) G == (z + rx) G
-        if (big_r * s - (public_key * r + C::ProjectivePoint::generator() * z))
-            .is_identity()
-            .into()
-        {
+        // s has been negated if it was high (low-s rule), which negates sR too
+        let expected = public_key * r + C::ProjectivePoint::generator() * z;
+        let expected = if was_flipped { -expected } else { expected };
+        if (big_r * s - expected).is_identity().into() {
             Self::signature_into_js(big_r.to_affine(), s, was_flipped)
         } else {
-            Err(JsError::new("invalid signature"))
+            Err(LitCryptoError::SignatureMismatch)
+        }
+    }
+}
+
+/// Bits of the scalar each precomputed table row covers
+const HD_WINDOW_BITS: usize = 4;
+const HD_WINDOW_DIGITS: usize = (1 << HD_WINDOW_BITS) - 1;
+
+/// Multiples of every root key for each window of the scalar, so deriving a key only adds points.
+/// Row `w` of key `k` holds `d * 16^w * P_k` for the digits `d` from 1 to 15.
+struct HdTable<C: CurveArithmetic> {
+    keys: usize,
+    windows: usize,
+    multiples: Vec<C::AffinePoint>,
+}
+
+impl<C> HdTable<C>
+where
+    C: PrimeCurve + CurveArithmetic + HdCtx,
+    C::Scalar: HDDeriver,
+{
+    fn new(public_keys: &[C::ProjectivePoint]) -> Self {
+        let windows =
+            <C::Scalar as PrimeField>::Repr::default().as_ref().len() * 8 / HD_WINDOW_BITS;
+
+        let mut multiples = Vec::with_capacity(public_keys.len() * windows * HD_WINDOW_DIGITS);
+        for key in public_keys {
+            let mut base = *key;
+            for _ in 0..windows {
+                let mut multiple = base;
+                multiples.push(multiple);
+                for _ in 1..HD_WINDOW_DIGITS {
+                    multiple += base;
+                    multiples.push(multiple);
+                }
+                base += multiple;
+            }
+        }
+
+        Self {
+            keys: public_keys.len(),
+            windows,
+            multiples: normalize::<C>(&multiples),
+        }
+    }
+
+    /// Same as `Ecdsa::derive_point` over the root keys
+    fn derive(&self, id: &[u8]) -> C::ProjectivePoint {
+        let tweak = C::Scalar::create(id, C::CTX);
+        // A single root key is multiplied by the tweak, more are the coefficients of a polynomial evaluated at it
+        let mut coefficient = match self.keys {
+            1 => tweak,
+            _ => C::Scalar::ONE,
+        };
+
+        let mut derived = C::ProjectivePoint::identity();
+        for key in 0..self.keys {
+            let rows = &self.multiples[key * self.windows * HD_WINDOW_DIGITS..];
+            // The scalar repr of the curves we derive on is big endian
+            let repr = coefficient.to_repr();
+            for (window, digit) in repr
+                .as_ref()
+                .iter()
+                .rev()
+                .flat_map(|byte| [byte & 0x0F, byte >> 4])
+                .enumerate()
+            {
+                if digit != 0 {
+                    derived += rows[window * HD_WINDOW_DIGITS + usize::from(digit) - 1];
+                }
+            }
+            coefficient *= tweak;
+        }
+        derived
+    }
+}
+
+/// Converts to affine with a single inversion
+fn normalize<C: CurveArithmetic>(points: &[C::ProjectivePoint]) -> Vec<C::AffinePoint> {
+    let mut affine = vec![C::AffinePoint::default(); points.len()];
+    // batch_normalize panics on empty input
+    if !points.is_empty() {
+        C::ProjectivePoint::batch_normalize(points, &mut affine);
+    }
+    affine
+}
+
+enum HdRootKeys {
+    K256(HdTable<Secp256k1>),
+    P256(HdTable<NistP256>),
+    P384(HdTable<NistP384>),
+}
+
+/// Derives keys from a fixed set of root keys, which are decoded and expanded into tables once.
+/// Cheaper than `ecdsaDeriveKey` when deriving more than a few keys
+#[wasm_bindgen]
+pub struct HdKeyDeriver(HdRootKeys);
+
+#[wasm_bindgen]
+impl HdKeyDeriver {
+    #[wasm_bindgen(constructor)]
+    pub fn new(variant: EcdsaVariant, public_keys: Vec<Uint8Array>) -> JsResult<HdKeyDeriver> {
+        Ok(Self(match variant {
+            EcdsaVariant::K256 => HdRootKeys::K256(Ecdsa::<Secp256k1>::hd_table(public_keys)?),
+            EcdsaVariant::P256 => HdRootKeys::P256(Ecdsa::<NistP256>::hd_table(public_keys)?),
+            EcdsaVariant::P384 => HdRootKeys::P384(Ecdsa::<NistP384>::hd_table(public_keys)?),
+        }))
+    }
+
+    /// Derives the public key of the id, uncompressed like `ecdsaDeriveKey`
+    pub fn derive(&self, id: Uint8Array) -> JsResult<Uint8Array> {
+        let id = from_js::<Vec<u8>>(id)?;
+        match &self.0 {
+            HdRootKeys::K256(table) => {
+                into_uint8array(table.derive(&id).to_encoded_point(false).as_bytes())
+            }
+            HdRootKeys::P256(table) => {
+                into_uint8array(table.derive(&id).to_encoded_point(false).as_bytes())
+            }
+            HdRootKeys::P384(table) => {
+                into_uint8array(table.derive(&id).to_encoded_point(false).as_bytes())
+            }
+        }
+    }
+
+    /// Derives the public keys of all the ids, in order
+    #[wasm_bindgen(js_name = "deriveMany")]
+    pub fn derive_many(&self, ids: Vec<Uint8Array>) -> JsResult<Vec<Uint8Array>> {
+        let ids = ids
+            .into_iter()
+            .map(from_js::<Vec<u8>>)
+            .collect::<JsResult<Vec<_>>>()?;
+        match &self.0 {
+            HdRootKeys::K256(table) => Ecdsa::<Secp256k1>::encode_points(
+                &ids.iter().map(|id| table.derive(id)).collect::<Vec<_>>(),
+            ),
+            HdRootKeys::P256(table) => Ecdsa::<NistP256>::encode_points(
+                &ids.iter().map(|id| table.derive(id)).collect::<Vec<_>>(),
+            ),
+            HdRootKeys::P384(table) => Ecdsa::<NistP384>::encode_points(
+                &ids.iter().map(|id| table.derive(id)).collect::<Vec<_>>(),
+            ),
         }
     }
 }
@@ -279,6 +535,13 @@ pub fn ecdsa_combine_and_verify_with_derived_key(
             id,
             public_keys,
         ),
+        EcdsaVariant::P384 => Ecdsa::<NistP384>::combine_and_verify_with_derived_key(
+            pre_signature,
+            signature_shares,
+            message_hash,
+            id,
+            public_keys,
+        ),
     }
 }
 
@@ -304,6 +567,86 @@ pub fn ecdsa_combine_and_verify(
             message_hash,
             public_key,
         ),
+        EcdsaVariant::P384 => Ecdsa::<NistP384>::combine_and_verify_with_specified_key(
+            pre_signature,
+            signature_shares,
+            message_hash,
+            public_key,
+        ),
+    }
+}
+
+/// Same as `ecdsaCombineAndVerifyWithDerivedKey`, hashing the message first
+#[wasm_bindgen(js_name = "ecdsaCombineAndVerifyMessageWithDerivedKey")]
+pub fn ecdsa_combine_and_verify_message_with_derived_key(
+    variant: EcdsaVariant,
+    pre_signature: Uint8Array,
+    signature_shares: Vec<Uint8Array>,
+    message: Uint8Array,
+    hasher: MessageHasher,
+    id: Uint8Array,
+    public_keys: Vec<Uint8Array>,
+) -> JsResult<EcdsaSignature> {
+    match variant {
+        EcdsaVariant::K256 => Ecdsa::<Secp256k1>::combine_and_verify_message_with_derived_key(
+            pre_signature,
+            signature_shares,
+            message,
+            hasher,
+            id,
+            public_keys,
+        ),
+        EcdsaVariant::P256 => Ecdsa::<NistP256>::combine_and_verify_message_with_derived_key(
+            pre_signature,
+            signature_shares,
+            message,
+            hasher,
+            id,
+            public_keys,
+        ),
+        EcdsaVariant::P384 => Ecdsa::<NistP384>::combine_and_verify_message_with_derived_key(
+            pre_signature,
+            signature_shares,
+            message,
+            hasher,
+            id,
+            public_keys,
+        ),
+    }
+}
+
+/// Same as `ecdsaCombineAndVerify`, hashing the message first
+#[wasm_bindgen(js_name = "ecdsaCombineAndVerifyMessage")]
+pub fn ecdsa_combine_and_verify_message(
+    variant: EcdsaVariant,
+    pre_signature: Uint8Array,
+    signature_shares: Vec<Uint8Array>,
+    message: Uint8Array,
+    hasher: MessageHasher,
+    public_key: Uint8Array,
+) -> JsResult<EcdsaSignature> {
+    match variant {
+        EcdsaVariant::K256 => Ecdsa::<Secp256k1>::combine_and_verify_message_with_specified_key(
+            pre_signature,
+            signature_shares,
+            message,
+            hasher,
+            public_key,
+        ),
+        EcdsaVariant::P256 => Ecdsa::<NistP256>::combine_and_verify_message_with_specified_key(
+            pre_signature,
+            signature_shares,
+            message,
+            hasher,
+            public_key,
+        ),
+        EcdsaVariant::P384 => Ecdsa::<NistP384>::combine_and_verify_message_with_specified_key(
+            pre_signature,
+            signature_shares,
+            message,
+            hasher,
+            public_key,
+        ),
     }
 }
 
@@ -317,6 +660,7 @@ pub fn ecdsa_combine(
     match variant {
         EcdsaVariant::K256 => Ecdsa::<Secp256k1>::combine(presignature, signature_shares),
         EcdsaVariant::P256 => Ecdsa::<NistP256>::combine(presignature, signature_shares),
+        EcdsaVariant::P384 => Ecdsa::<NistP384>::combine(presignature, signature_shares),
     }
 }
 
@@ -330,6 +674,7 @@ pub fn ecdsa_verify(
     match variant {
         EcdsaVariant::K256 => Ecdsa::<Secp256k1>::verify(message_hash, public_key, signature),
         EcdsaVariant::P256 => Ecdsa::<NistP256>::verify(message_hash, public_key, signature),
+        EcdsaVariant::P384 => Ecdsa::<NistP384>::verify(message_hash, public_key, signature),
     }
 }
 
@@ -342,5 +687,6 @@ pub fn ecdsa_derive_key(
     match variant {
         EcdsaVariant::K256 => Ecdsa::<Secp256k1>::derive_key(id, public_keys),
         EcdsaVariant::P256 => Ecdsa::<NistP256>::derive_key(id, public_keys),
+        EcdsaVariant::P384 => Ecdsa::<NistP384>::derive_key(id, public_keys),
     }
 }
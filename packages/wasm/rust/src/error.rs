@@ -0,0 +1,309 @@
+//! Errors surfaced to JS with stable codes
+//!
+//! Every error is thrown as a JS `Error` carrying `code`, `kind`, `message` and `details`.
+//! `code` is stable and `kind` is one of `LIT_ERROR_KIND` from `@lit-protocol/constants`.
+
+use js_sys::{Error as JsErrorObject, Reflect};
+use serde::Serialize;
+use serde_json::{json, Value};
+use tsify::Tsify;
+use wasm_bindgen::JsValue;
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum LitCryptoError {
+    #[error("cannot deserialize: {0}")]
+    Deserialize(String),
+    #[error("cannot serialize: {0}")]
+    Serialize(String),
+    #[error("invalid share at index {0}")]
+    InvalidShare(usize),
+    #[error("invalid point")]
+    InvalidPoint,
+    #[error("invalid scalar")]
+    InvalidScalar,
+    #[error("invalid message digest length: expected {expected} bytes, got {actual}")]
+    DigestLength { expected: usize, actual: usize },
+    #[error("invalid message digest")]
+    InvalidDigest,
+    #[error("no shares provided")]
+    NoShares,
+    #[error("invalid signature")]
+    InvalidSignature,
+    #[error("signature does not match the message and public key")]
+    SignatureMismatch,
+    #[error("decryption failed")]
+    DecryptionFailed,
+    #[error("attestation certificate chain is invalid: {0}")]
+    AttestationChain(String),
+    #[error("{certificate} certificate is revoked")]
+    CertificateRevoked { certificate: String, serial: String },
+    #[error(
+        "{certificate} certificate is not valid at {time}, only from {not_before} to {not_after}"
+    )]
+    CertificateValidity {
+        certificate: String,
+        not_before: u64,
+        not_after: u64,
+        time: u64,
+    },
+    #[error("invalid certificate revocation list: {0}")]
+    Crl(String),
+    #[error("report data does not match the v{version} binding.  This generally indicates that the data, challenge/nonce, or signatures are bad.")]
+    ReportDataMismatch {
+        version: u8,
+        expected: Vec<u8>,
+        actual: Vec<u8>,
+        /// Another binding version the report data matches, when the node used a different one
+        matching_version: Option<u8>,
+    },
+    #[error("invalid attestation report: {0}")]
+    AttestationReport(ReportError),
+    #[error("attestation report violates the policy: {}", display_violations(.0))]
+    PolicyViolations(Vec<PolicyViolation>),
+    #[error("cannot detect the EPYC product that produced the report, pass it explicitly")]
+    UnknownProduct {
+        family: Option<u8>,
+        model: Option<u8>,
+    },
+    #[error("cannot compute the launch measurement: {0}")]
+    Measurement(String),
+    #[error("invalid attestation collateral: {0}")]
+    Collateral(String),
+    #[error("{component} TCB status is {status}")]
+    TcbStatus {
+        component: String,
+        status: String,
+        advisory_ids: Vec<String>,
+    },
+    #[error("invalid typed data: {0}")]
+    TypedData(String),
+    #[error("invalid transaction: {0}")]
+    Transaction(String),
+    #[error("invalid user operation: {0}")]
+    UserOperation(String),
+    #[error("invalid address prefix: {0}")]
+    AddressPrefix(String),
+    #[error("invalid serialized signature: {0}")]
+    SerializedSignature(String),
+    #[error("{0}")]
+    Bls(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum ReportError {
+    #[error("expected at least {expected} bytes, got {actual}")]
+    Length { expected: usize, actual: usize },
+    #[error("unsupported report version {0}")]
+    Version(u32),
+    #[error("reserved bytes at offset {offset:#x} are not zero")]
+    Reserved { offset: usize },
+    #[error("unsupported signature algorithm {0}")]
+    SignatureAlgorithm(u32),
+    #[error("reserved signing key {0}")]
+    SigningKey(u32),
+    #[error("unsupported TEE type {0:#x}")]
+    TeeType(u32),
+    #[error("{0}")]
+    Malformed(String),
+}
+
+/// A single check of an attestation policy that the report failed
+#[derive(Tsify, Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PolicyViolation {
+    pub field: String,
+    pub expected: String,
+    pub actual: String,
+}
+
+fn display_violations(violations: &[PolicyViolation]) -> String {
+    violations
+        .iter()
+        .map(|v| format!("{} (expected {}, got {})", v.field, v.expected, v.actual))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+impl ReportError {
+    fn details(&self) -> Value {
+        match self {
+            Self::Length { expected, actual } => {
+                json!({ "reason": "length", "expected": expected, "actual": actual })
+            }
+            Self::Version(version) => json!({ "reason": "version", "version": version }),
+            Self::Reserved { offset } => json!({ "reason": "reserved", "offset": offset }),
+            Self::SignatureAlgorithm(algorithm) => {
+                json!({ "reason": "signature_algorithm", "algorithm": algorithm })
+            }
+            Self::SigningKey(key) => json!({ "reason": "signing_key", "key": key }),
+            Self::TeeType(tee_type) => json!({ "reason": "tee_type", "teeType": tee_type }),
+            Self::Malformed(reason) => json!({ "reason": "malformed", "message": reason }),
+        }
+    }
+}
+
+/// Mirrors `LIT_ERROR_KIND` in `@lit-protocol/constants`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LitErrorKind {
+    Unexpected,
+    Validation,
+    Parser,
+    Serializer,
+}
+
+impl LitErrorKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Unexpected => "Unexpected",
+            Self::Validation => "Validation",
+            Self::Parser => "Parser",
+            Self::Serializer => "Serializer",
+        }
+    }
+}
+
+impl LitCryptoError {
+    /// Stable code, kept in sync with `LIT_CRYPTO_ERROR_CODE` in `@lit-protocol/constants`
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::Deserialize(_) => "cannot_deserialize",
+            Self::Serialize(_) => "cannot_serialize",
+            Self::InvalidShare(_) => "invalid_share",
+            Self::InvalidPoint => "invalid_point",
+            Self::InvalidScalar => "invalid_scalar",
+            Self::DigestLength { .. } => "digest_length",
+            Self::InvalidDigest => "invalid_digest",
+            Self::NoShares => "no_shares",
+            Self::InvalidSignature => "invalid_signature",
+            Self::SignatureMismatch => "signature_mismatch",
+            Self::DecryptionFailed => "decryption_failed",
+            Self::AttestationChain(_) => "attestation_chain",
+            Self::CertificateRevoked { .. } => "certificate_revoked",
+            Self::CertificateValidity { .. } => "certificate_validity",
+            Self::Crl(_) => "invalid_crl",
+            Self::ReportDataMismatch { .. } => "report_data_mismatch",
+            Self::AttestationReport(_) => "invalid_attestation_report",
+            Self::UnknownProduct { .. } => "unknown_product",
+            Self::PolicyViolations(_) => "attestation_policy",
+            Self::Measurement(_) => "invalid_measurement_input",
+            Self::Collateral(_) => "invalid_collateral",
+            Self::TcbStatus { .. } => "tcb_status",
+            Self::TypedData(_) => "invalid_typed_data",
+            Self::Transaction(_) => "invalid_transaction",
+            Self::UserOperation(_) => "invalid_user_operation",
+            Self::AddressPrefix(_) => "invalid_address_prefix",
+            Self::SerializedSignature(_) => "invalid_serialized_signature",
+            Self::Bls(_) => "bls_error",
+        }
+    }
+
+    pub fn kind(&self) -> LitErrorKind {
+        match self {
+            Self::Deserialize(_)
+            | Self::InvalidPoint
+            | Self::InvalidScalar
+            | Self::AttestationReport(_)
+            | Self::TypedData(_)
+            | Self::Transaction(_)
+            | Self::UserOperation(_)
+            | Self::SerializedSignature(_) => LitErrorKind::Parser,
+            Self::Serialize(_) => LitErrorKind::Serializer,
+            Self::DecryptionFailed | Self::Bls(_) => LitErrorKind::Unexpected,
+            _ => LitErrorKind::Validation,
+        }
+    }
+
+    pub fn details(&self) -> Value {
+        match self {
+            Self::Deserialize(reason) | Self::Serialize(reason) => json!({ "reason": reason }),
+            Self::InvalidShare(index) => json!({ "index": index }),
+            Self::DigestLength { expected, actual } => {
+                json!({ "expected": expected, "actual": actual })
+            }
+            Self::AttestationChain(reason)
+            | Self::Crl(reason)
+            | Self::Collateral(reason)
+            | Self::Measurement(reason)
+            | Self::TypedData(reason)
+            | Self::Transaction(reason)
+            | Self::UserOperation(reason)
+            | Self::AddressPrefix(reason)
+            | Self::SerializedSignature(reason)
+            | Self::Bls(reason) => json!({ "reason": reason }),
+            Self::CertificateRevoked {
+                certificate,
+                serial,
+            } => json!({ "certificate": certificate, "serial": serial }),
+            Self::CertificateValidity {
+                certificate,
+                not_before,
+                not_after,
+                time,
+            } => json!({
+                "certificate": certificate,
+                "notBefore": not_before,
+                "notAfter": not_after,
+                "time": time,
+            }),
+            Self::ReportDataMismatch {
+                version,
+                expected,
+                actual,
+                matching_version,
+            } => json!({
+                "version": version,
+                "expected": hex::encode(expected),
+                "actual": hex::encode(actual),
+                "matchingVersion": matching_version,
+            }),
+            Self::AttestationReport(err) => err.details(),
+            Self::UnknownProduct { family, model } => json!({ "family": family, "model": model }),
+            Self::PolicyViolations(violations) => json!({ "violations": violations }),
+            Self::TcbStatus {
+                component,
+                status,
+                advisory_ids,
+            } => json!({
+                "component": component,
+                "status": status,
+                "advisoryIds": advisory_ids,
+            }),
+            _ => Value::Null,
+        }
+    }
+}
+
+impl From<LitCryptoError> for JsValue {
+    fn from(err: LitCryptoError) -> Self {
+        let obj = JsErrorObject::new(&err.to_string());
+        obj.set_name("LitCryptoError");
+
+        let details = err
+            .details()
+            .serialize(&serde_wasm_bindgen::Serializer::json_compatible())
+            .unwrap_or(JsValue::NULL);
+
+        // Reflect::set only fails on frozen or non-object targets
+        let _ = Reflect::set(&obj, &"code".into(), &err.code().into());
+        let _ = Reflect::set(&obj, &"kind".into(), &err.kind().as_str().into());
+        let _ = Reflect::set(&obj, &"details".into(), &details);
+
+        obj.into()
+    }
+}
+
+impl From<serde_wasm_bindgen::Error> for LitCryptoError {
+    fn from(err: serde_wasm_bindgen::Error) -> Self {
+        Self::Deserialize(err.to_string())
+    }
+}
+
+#[cfg(feature = "bls")]
+impl From<blsful::BlsError> for LitCryptoError {
+    fn from(err: blsful::BlsError) -> Self {
+        match err {
+            blsful::BlsError::InvalidSignature => Self::InvalidSignature,
+            blsful::BlsError::DeserializationError(reason) => Self::Deserialize(reason),
+            err => Self::Bls(err.to_string()),
+        }
+    }
+}
@@ -0,0 +1,102 @@
+//! Ethereum addresses, and transactions signed with combined ECDSA signatures.
+//! Addresses are always built with `ecdsa` for the PKPs, the rest needs the `ethereum` feature
+
+#[cfg(feature = "ethereum")]
+mod rlp;
+#[cfg(feature = "ethereum")]
+mod transaction;
+#[cfg(feature = "ethereum")]
+mod user_operation;
+#[cfg(feature = "ethereum")]
+mod values;
+
+use std::fmt;
+
+use elliptic_curve::sec1::ToEncodedPoint;
+use serde::{de::Error as _, Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::hash::keccak256;
+
+#[cfg(feature = "ethereum")]
+pub use transaction::{AccessListItem, EthSignedTransaction, EthTransaction};
+#[cfg(feature = "ethereum")]
+pub use user_operation::{PackedUserOperation, UserOperationV06, UserOperationV07};
+#[cfg(feature = "ethereum")]
+pub use values::{HexBytes, JsQuantity, Quantity};
+
+/// A 20 bytes address, read from hex and written EIP-55 checksummed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Address(pub [u8; 20]);
+
+impl Address {
+    pub fn from_public_key(public_key: &k256::AffinePoint) -> Self {
+        let uncompressed = public_key.to_encoded_point(false);
+        let mut address = [0u8; 20];
+        address.copy_from_slice(&keccak256(&uncompressed.as_bytes()[1..])[12..]);
+        Self(address)
+    }
+}
+
+impl fmt::Display for Address {
+    /// EIP-55 checksummed
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let address = hex::encode(self.0);
+        let hash = keccak256(&address);
+
+        let checksummed = address
+            .chars()
+            .enumerate()
+            .map(|(i, c)| {
+                let nibble = (hash[i / 2] >> (if i % 2 == 0 { 4 } else { 0 })) & 0xf;
+                if nibble >= 8 {
+                    c.to_ascii_uppercase()
+                } else {
+                    c
+                }
+            })
+            .collect::<String>();
+        write!(f, "0x{checksummed}")
+    }
+}
+
+impl std::str::FromStr for Address {
+    type Err = String;
+
+    /// Mixed case addresses must have a valid checksum
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let digits = value
+            .strip_prefix("0x")
+            .filter(|digits| digits.len() == 40)
+            .ok_or_else(|| format!("{value} is not a 0x-prefixed 20 bytes address"))?;
+        let mut address = [0u8; 20];
+        hex::decode_to_slice(digits, &mut address)
+            .map_err(|_| format!("{value} is not a 0x-prefixed 20 bytes address"))?;
+
+        let address = Self(address);
+        let mixed_case = digits.chars().any(|c| c.is_ascii_lowercase())
+            && digits.chars().any(|c| c.is_ascii_uppercase());
+        if mixed_case && address.to_string() != value {
+            return Err(format!("{value} has an invalid checksum"));
+        }
+        Ok(address)
+    }
+}
+
+impl Serialize for Address {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Address {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(D::Error::custom)
+    }
+}
+
+/// EIP-55 address of a secp256k1 public key
+pub fn eth_address(public_key: &k256::AffinePoint) -> String {
+    Address::from_public_key(public_key).to_string()
+}
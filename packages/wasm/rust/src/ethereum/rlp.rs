@@ -0,0 +1,123 @@
+//! Recursive length prefix encoding, how transactions are serialized
+
+/// A decoded RLP item
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Item {
+    Bytes(Vec<u8>),
+    List(Vec<Item>),
+}
+
+impl Item {
+    pub fn into_bytes(self) -> Result<Vec<u8>, String> {
+        match self {
+            Self::Bytes(bytes) => Ok(bytes),
+            Self::List(_) => Err("expected bytes, got a list".to_string()),
+        }
+    }
+
+    pub fn into_list(self) -> Result<Vec<Item>, String> {
+        match self {
+            Self::List(items) => Ok(items),
+            Self::Bytes(_) => Err("expected a list, got bytes".to_string()),
+        }
+    }
+}
+
+pub fn encode_bytes(bytes: &[u8]) -> Vec<u8> {
+    match bytes {
+        [byte] if *byte < 0x80 => vec![*byte],
+        _ => [header(0x80, bytes.len()), bytes.to_vec()].concat(),
+    }
+}
+
+/// Encodes a list of already encoded items
+pub fn encode_list(items: &[Vec<u8>]) -> Vec<u8> {
+    let payload = items.concat();
+    [header(0xc0, payload.len()), payload].concat()
+}
+
+fn header(offset: u8, length: usize) -> Vec<u8> {
+    if length <= 55 {
+        return vec![offset + length as u8];
+    }
+    let length = length.to_be_bytes();
+    let length = trim_leading_zeros(&length);
+    [&[offset + 55 + length.len() as u8], length].concat()
+}
+
+pub fn trim_leading_zeros(bytes: &[u8]) -> &[u8] {
+    let start = bytes
+        .iter()
+        .position(|byte| *byte != 0)
+        .unwrap_or(bytes.len());
+    &bytes[start..]
+}
+
+/// Lists nest at most this deep in a transaction: the access list, its entries and their storage keys
+const MAX_DEPTH: usize = 4;
+
+/// Decodes a single item spanning all the data, rejecting non canonical encodings
+pub fn decode(data: &[u8]) -> Result<Item, String> {
+    let (item, rest) = decode_item(data, 0)?;
+    if !rest.is_empty() {
+        return Err(format!("{} trailing bytes", rest.len()));
+    }
+    Ok(item)
+}
+
+/// `depth` is the number of lists around the item, bounded so nested lists cannot exhaust the stack
+fn decode_item(data: &[u8], depth: usize) -> Result<(Item, &[u8]), String> {
+    let (&prefix, data) = data.split_first().ok_or("unexpected end of data")?;
+    match prefix {
+        0..=0x7f => Ok((Item::Bytes(vec![prefix]), data)),
+        0x80..=0xbf => {
+            let (payload, rest) = split_payload(prefix - 0x80, data)?;
+            if let [byte] = payload {
+                if *byte < 0x80 {
+                    return Err(format!("single byte {byte:#04x} encoded as a string"));
+                }
+            }
+            Ok((Item::Bytes(payload.to_vec()), rest))
+        }
+        0xc0..=0xff => {
+            if depth == MAX_DEPTH {
+                return Err(format!("lists nested more than {MAX_DEPTH} deep"));
+            }
+            let (mut payload, rest) = split_payload(prefix - 0xc0, data)?;
+            let mut items = Vec::new();
+            while !payload.is_empty() {
+                let (item, remaining) = decode_item(payload, depth + 1)?;
+                items.push(item);
+                payload = remaining;
+            }
+            Ok((Item::List(items), rest))
+        }
+    }
+}
+
+/// Splits the payload whose length is given by the prefix, minus its offset, from the data following it
+fn split_payload(length: u8, data: &[u8]) -> Result<(&[u8], &[u8]), String> {
+    let (length, data) = if length <= 55 {
+        (usize::from(length), data)
+    } else {
+        let size = usize::from(length - 55);
+        if data.len() < size {
+            return Err("unexpected end of data".to_string());
+        }
+        let (length, data) = data.split_at(size);
+        if length[0] == 0 || size > std::mem::size_of::<usize>() {
+            return Err("invalid length".to_string());
+        }
+        let length = length
+            .iter()
+            .fold(0usize, |n, byte| n << 8 | usize::from(*byte));
+        if length <= 55 {
+            return Err("short length in the long form".to_string());
+        }
+        (length, data)
+    };
+    if data.len() < length {
+        return Err("unexpected end of data".to_string());
+    }
+    Ok(data.split_at(length))
+}
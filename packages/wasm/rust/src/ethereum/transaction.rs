@@ -0,0 +1,543 @@
+//! Legacy, EIP-2930, EIP-1559 and EIP-4844 transactions, the latter without their blobs
+
+use js_sys::Uint8Array;
+use k256::ecdsa::{RecoveryId, Signature, VerifyingKey};
+use serde::{Deserialize, Serialize};
+use tsify::Tsify;
+use wasm_bindgen::prelude::*;
+
+use crate::abi::{from_js, into_uint8array, JsResult};
+use crate::ecdsa::EcdsaSignature;
+use crate::error::LitCryptoError;
+use crate::hash::keccak256;
+
+use super::rlp::{self, Item};
+use super::{Address, HexBytes, Quantity};
+
+const LEGACY: u8 = 0;
+const EIP2930: u8 = 1;
+const EIP1559: u8 = 2;
+const EIP4844: u8 = 3;
+
+/// A transaction as ethers describes it, quantities are JS numbers or decimal or 0x-prefixed hex strings
+#[derive(Tsify, Serialize, Deserialize, Debug, Clone, Default)]
+#[tsify(from_wasm_abi)]
+#[serde(rename_all = "camelCase")]
+pub struct EthTransaction {
+    /// 0 legacy, 1 EIP-2930, 2 EIP-1559 or 3 EIP-4844, inferred from the fields when missing
+    #[tsify(optional)]
+    #[serde(rename = "type", default, skip_serializing_if = "Option::is_none")]
+    pub kind: Option<u8>,
+    /// Required by typed transactions, legacy ones are only replay protected (EIP-155) with it
+    #[tsify(optional, type = "number | string")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub chain_id: Option<Quantity>,
+    #[tsify(optional, type = "number | string")]
+    #[serde(default)]
+    pub nonce: Quantity,
+    /// Legacy and EIP-2930 transactions only
+    #[tsify(optional, type = "number | string")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub gas_price: Option<Quantity>,
+    /// EIP-1559 and EIP-4844 transactions only
+    #[tsify(optional, type = "number | string")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_priority_fee_per_gas: Option<Quantity>,
+    /// EIP-1559 and EIP-4844 transactions only
+    #[tsify(optional, type = "number | string")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_fee_per_gas: Option<Quantity>,
+    #[tsify(type = "number | string")]
+    pub gas_limit: Quantity,
+    /// Missing for contract creations
+    #[tsify(optional, type = "string")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub to: Option<Address>,
+    #[tsify(optional, type = "number | string")]
+    #[serde(default)]
+    pub value: Quantity,
+    #[tsify(optional, type = "string")]
+    #[serde(default)]
+    pub data: HexBytes,
+    /// Typed transactions only, empty when missing
+    #[tsify(optional)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub access_list: Option<Vec<AccessListItem>>,
+    /// EIP-4844 transactions only
+    #[tsify(optional, type = "number | string")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_fee_per_blob_gas: Option<Quantity>,
+    /// EIP-4844 transactions only, the blobs themselves travel beside the transaction
+    #[tsify(optional, type = "string[]")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub blob_versioned_hashes: Option<Vec<HexBytes>>,
+    /// The sender, checked against the one recovered from the signature when given
+    #[tsify(optional, type = "string")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub from: Option<Address>,
+}
+
+#[derive(Tsify, Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct AccessListItem {
+    #[tsify(type = "string")]
+    pub address: Address,
+    #[tsify(type = "string[]")]
+    pub storage_keys: Vec<HexBytes>,
+}
+
+/// A raw transaction decoded
+#[derive(Tsify, Serialize, Debug, Clone)]
+#[tsify(into_wasm_abi)]
+#[serde(rename_all = "camelCase")]
+pub struct EthSignedTransaction {
+    /// With its `type`, and its `from` recovered from the signature
+    pub transaction: EthTransaction,
+    /// keccak256 of the raw transaction
+    #[tsify(type = "string")]
+    pub hash: HexBytes,
+    #[tsify(type = "string")]
+    pub r: HexBytes,
+    #[tsify(type = "string")]
+    pub s: HexBytes,
+    pub y_parity: u8,
+    /// `yParity` for typed transactions, `27 + yParity` or the EIP-155 one for legacy transactions
+    #[tsify(type = "string")]
+    pub v: Quantity,
+}
+
+fn invalid(reason: impl Into<String>) -> LitCryptoError {
+    LitCryptoError::Transaction(reason.into())
+}
+
+fn encode_quantity(quantity: &Quantity) -> Vec<u8> {
+    rlp::encode_bytes(quantity.as_minimal_bytes())
+}
+
+impl EthTransaction {
+    /// keccak256 of the unsigned transaction
+    pub fn signing_hash(&self) -> JsResult<[u8; 32]> {
+        Ok(keccak256(self.encode_unsigned()?))
+    }
+
+    /// The unsigned transaction, with the chain id replacing the signature of EIP-155 legacy transactions
+    pub fn encode_unsigned(&self) -> JsResult<Vec<u8>> {
+        let kind = self.transaction_type()?;
+        let mut fields = self.encode_fields(kind);
+        if let (LEGACY, Some(chain_id)) = (kind, &self.chain_id) {
+            fields.extend([
+                encode_quantity(chain_id),
+                rlp::encode_bytes(&[]),
+                rlp::encode_bytes(&[]),
+            ]);
+        }
+        Ok(envelope(kind, &fields))
+    }
+
+    pub fn encode_signed(&self, signature: &TxSignature) -> JsResult<Vec<u8>> {
+        let kind = self.transaction_type()?;
+        let sender = signature.recover(&self.signing_hash()?)?;
+        if self.from.is_some_and(|from| from != sender) {
+            return Err(LitCryptoError::SignatureMismatch);
+        }
+
+        let v = if kind == LEGACY {
+            self.legacy_v(signature.y_parity)?
+        } else {
+            Quantity::from_u64(signature.y_parity.into())
+        };
+        let mut fields = self.encode_fields(kind);
+        fields.extend([
+            encode_quantity(&v),
+            rlp::encode_bytes(rlp::trim_leading_zeros(&signature.r)),
+            rlp::encode_bytes(rlp::trim_leading_zeros(&signature.s)),
+        ]);
+        Ok(envelope(kind, &fields))
+    }
+
+    pub fn decode(raw: &[u8]) -> JsResult<EthSignedTransaction> {
+        let (kind, payload) = match raw.first() {
+            Some(0xc0..) => (LEGACY, raw),
+            Some(&kind @ EIP2930..=EIP4844) => (kind, &raw[1..]),
+            Some(kind) => return Err(invalid(format!("unsupported transaction type {kind}"))),
+            None => return Err(invalid("empty transaction")),
+        };
+        let fields = rlp::decode(payload)
+            .and_then(Item::into_list)
+            .map_err(invalid)?;
+        let mut fields = FieldReader(fields.into_iter());
+
+        let mut transaction = Self {
+            kind: Some(kind),
+            ..Self::default()
+        };
+        if kind != LEGACY {
+            transaction.chain_id = Some(fields.quantity()?);
+        }
+        transaction.nonce = fields.quantity()?;
+        if kind <= EIP2930 {
+            transaction.gas_price = Some(fields.quantity()?);
+        } else {
+            transaction.max_priority_fee_per_gas = Some(fields.quantity()?);
+            transaction.max_fee_per_gas = Some(fields.quantity()?);
+        }
+        transaction.gas_limit = fields.quantity()?;
+        transaction.to = fields.to()?;
+        transaction.value = fields.quantity()?;
+        transaction.data = HexBytes(fields.bytes()?);
+        if kind != LEGACY {
+            transaction.access_list = Some(fields.access_list()?);
+        }
+        if kind == EIP4844 {
+            transaction.max_fee_per_blob_gas = Some(fields.quantity()?);
+            transaction.blob_versioned_hashes = Some(fields.hashes()?);
+        }
+        let v = fields.quantity()?;
+        let r = fields.quantity()?.0;
+        let s = fields.quantity()?.0;
+        fields.end()?;
+
+        let y_parity = match (kind, v.to_u64()) {
+            (LEGACY, Some(v @ (27 | 28))) => v - 27,
+            (LEGACY, Some(v)) if v >= 35 => {
+                transaction.chain_id = Some(Quantity::from_u64((v - 35) / 2));
+                (v - 35) % 2
+            }
+            (EIP2930..=EIP4844, Some(y_parity @ (0 | 1))) => y_parity,
+            _ => return Err(invalid(format!("invalid signature v {v}"))),
+        } as u8;
+
+        let signature = TxSignature { r, s, y_parity };
+        transaction.from = Some(signature.recover(&transaction.signing_hash()?)?);
+        Ok(EthSignedTransaction {
+            transaction,
+            hash: HexBytes(keccak256(raw).to_vec()),
+            r: HexBytes(r.to_vec()),
+            s: HexBytes(s.to_vec()),
+            y_parity,
+            v,
+        })
+    }
+
+    /// The explicit type, or the one the fields are for, checking they all belong to it
+    fn transaction_type(&self) -> JsResult<u8> {
+        let has_fee_market =
+            self.max_fee_per_gas.is_some() || self.max_priority_fee_per_gas.is_some();
+        let has_blob = self.max_fee_per_blob_gas.is_some() || self.blob_versioned_hashes.is_some();
+        let kind = self.kind.unwrap_or(if has_blob {
+            EIP4844
+        } else if has_fee_market {
+            EIP1559
+        } else if self.access_list.is_some() {
+            EIP2930
+        } else {
+            LEGACY
+        });
+        if kind > EIP4844 {
+            return Err(invalid(format!("unsupported transaction type {kind}")));
+        }
+
+        let check = |field: &str, needed: bool, allowed: bool, present: bool| {
+            if needed && !present {
+                Err(invalid(format!("type {kind} transactions need {field}")))
+            } else if !allowed && present {
+                Err(invalid(format!("type {kind} transactions have no {field}")))
+            } else {
+                Ok(())
+            }
+        };
+        let legacy_fees = kind <= EIP2930;
+        let blob = kind == EIP4844;
+
+        check("chainId", kind != LEGACY, true, self.chain_id.is_some())?;
+        check(
+            "gasPrice",
+            legacy_fees,
+            legacy_fees,
+            self.gas_price.is_some(),
+        )?;
+        check(
+            "maxPriorityFeePerGas",
+            !legacy_fees,
+            !legacy_fees,
+            self.max_priority_fee_per_gas.is_some(),
+        )?;
+        check(
+            "maxFeePerGas",
+            !legacy_fees,
+            !legacy_fees,
+            self.max_fee_per_gas.is_some(),
+        )?;
+        check(
+            "accessList",
+            false,
+            kind != LEGACY,
+            self.access_list.is_some(),
+        )?;
+        check(
+            "maxFeePerBlobGas",
+            blob,
+            blob,
+            self.max_fee_per_blob_gas.is_some(),
+        )?;
+        check(
+            "blobVersionedHashes",
+            blob,
+            blob,
+            self.blob_versioned_hashes.is_some(),
+        )?;
+        check("to", blob, true, self.to.is_some())?;
+
+        let storage_keys = self
+            .access_list
+            .iter()
+            .flatten()
+            .flat_map(|item| &item.storage_keys);
+        if storage_keys.into_iter().any(|key| key.0.len() != 32) {
+            return Err(invalid("storage keys must be 32 bytes"));
+        }
+        if let Some(hashes) = &self.blob_versioned_hashes {
+            if hashes.is_empty() || hashes.iter().any(|hash| hash.0.len() != 32) {
+                return Err(invalid(
+                    "expected one or more 32 bytes blob versioned hashes",
+                ));
+            }
+        }
+        Ok(kind)
+    }
+
+    /// RLP items of the fields before the signature, in the order of the type
+    fn encode_fields(&self, kind: u8) -> Vec<Vec<u8>> {
+        let optional = |quantity: &Option<Quantity>| encode_quantity(&quantity.unwrap_or_default());
+        let to = rlp::encode_bytes(self.to.as_ref().map_or(&[][..], |to| &to.0));
+        let data = rlp::encode_bytes(&self.data.0);
+        let access_list = self
+            .access_list
+            .iter()
+            .flatten()
+            .map(|item| {
+                let storage_keys = item
+                    .storage_keys
+                    .iter()
+                    .map(|key| rlp::encode_bytes(&key.0))
+                    .collect::<Vec<_>>();
+                rlp::encode_list(&[
+                    rlp::encode_bytes(&item.address.0),
+                    rlp::encode_list(&storage_keys),
+                ])
+            })
+            .collect::<Vec<_>>();
+        let access_list = rlp::encode_list(&access_list);
+
+        match kind {
+            LEGACY => vec![
+                encode_quantity(&self.nonce),
+                optional(&self.gas_price),
+                encode_quantity(&self.gas_limit),
+                to,
+                encode_quantity(&self.value),
+                data,
+            ],
+            EIP2930 => vec![
+                optional(&self.chain_id),
+                encode_quantity(&self.nonce),
+                optional(&self.gas_price),
+                encode_quantity(&self.gas_limit),
+                to,
+                encode_quantity(&self.value),
+                data,
+                access_list,
+            ],
+            _ => {
+                let mut fields = vec![
+                    optional(&self.chain_id),
+                    encode_quantity(&self.nonce),
+                    optional(&self.max_priority_fee_per_gas),
+                    optional(&self.max_fee_per_gas),
+                    encode_quantity(&self.gas_limit),
+                    to,
+                    encode_quantity(&self.value),
+                    data,
+                    access_list,
+                ];
+                if kind == EIP4844 {
+                    let hashes = self
+                        .blob_versioned_hashes
+                        .iter()
+                        .flatten()
+                        .map(|hash| rlp::encode_bytes(&hash.0))
+                        .collect::<Vec<_>>();
+                    fields.push(optional(&self.max_fee_per_blob_gas));
+                    fields.push(rlp::encode_list(&hashes));
+                }
+                fields
+            }
+        }
+    }
+
+    /// `27 + yParity`, or `35 + 2 * chainId + yParity` with EIP-155
+    fn legacy_v(&self, y_parity: u8) -> JsResult<Quantity> {
+        let Some(chain_id) = self.chain_id else {
+            return Ok(Quantity::from_u64(27 + u64::from(y_parity)));
+        };
+        chain_id
+            .to_u64()
+            .and_then(|chain_id| {
+                chain_id
+                    .checked_mul(2)?
+                    .checked_add(35 + u64::from(y_parity))
+            })
+            .map(Quantity::from_u64)
+            .ok_or_else(|| invalid("chainId is too large for EIP-155"))
+    }
+}
+
+/// The type byte followed by the RLP list of the fields, just the list for legacy transactions
+fn envelope(kind: u8, fields: &[Vec<u8>]) -> Vec<u8> {
+    let list = rlp::encode_list(fields);
+    if kind == LEGACY {
+        list
+    } else {
+        [vec![kind], list].concat()
+    }
+}
+
+struct FieldReader(std::vec::IntoIter<Item>);
+
+impl FieldReader {
+    fn next(&mut self) -> JsResult<Item> {
+        self.0.next().ok_or_else(|| invalid("missing fields"))
+    }
+
+    fn end(&mut self) -> JsResult<()> {
+        match self.0.next() {
+            Some(_) => Err(invalid("unexpected fields")),
+            None => Ok(()),
+        }
+    }
+
+    fn bytes(&mut self) -> JsResult<Vec<u8>> {
+        self.next()?.into_bytes().map_err(invalid)
+    }
+
+    fn list(&mut self) -> JsResult<Vec<Item>> {
+        self.next()?.into_list().map_err(invalid)
+    }
+
+    fn quantity(&mut self) -> JsResult<Quantity> {
+        let bytes = self.bytes()?;
+        if bytes.len() > 32 || bytes.first() == Some(&0) {
+            return Err(invalid(format!(
+                "0x{} is not a canonical quantity",
+                hex::encode(&bytes)
+            )));
+        }
+        let mut quantity = Quantity::default();
+        quantity.0[32 - bytes.len()..].copy_from_slice(&bytes);
+        Ok(quantity)
+    }
+
+    fn to(&mut self) -> JsResult<Option<Address>> {
+        match self.bytes()? {
+            bytes if bytes.is_empty() => Ok(None),
+            bytes => address(bytes).map(Some),
+        }
+    }
+
+    fn access_list(&mut self) -> JsResult<Vec<AccessListItem>> {
+        self.list()?
+            .into_iter()
+            .map(|item| {
+                let mut fields = FieldReader(item.into_list().map_err(invalid)?.into_iter());
+                let address = address(fields.bytes()?)?;
+                let storage_keys = fields.hashes()?;
+                fields.end()?;
+                Ok(AccessListItem {
+                    address,
+                    storage_keys,
+                })
+            })
+            .collect()
+    }
+
+    /// A list of 32 bytes values
+    fn hashes(&mut self) -> JsResult<Vec<HexBytes>> {
+        self.list()?
+            .into_iter()
+            .map(|item| match item.into_bytes().map_err(invalid)? {
+                hash if hash.len() == 32 => Ok(HexBytes(hash)),
+                _ => Err(invalid("expected 32 bytes hashes")),
+            })
+            .collect()
+    }
+}
+
+fn address(bytes: Vec<u8>) -> JsResult<Address> {
+    bytes
+        .try_into()
+        .map(Address)
+        .map_err(|_| invalid("expected a 20 bytes address"))
+}
+
+/// A combined signature of the signing hash of a transaction
+pub struct TxSignature {
+    pub r: [u8; 32],
+    pub s: [u8; 32],
+    pub y_parity: u8,
+}
+
+impl TxSignature {
+    /// `[r, s, v]` as `ecdsaCombine` returns it, `v` being 0 or 1, or 27 or 28
+    pub fn from_js(signature: EcdsaSignature) -> JsResult<Self> {
+        let (r, s, v): (Vec<u8>, Vec<u8>, u8) = from_js(signature)?;
+        let y_parity = match v {
+            0 | 1 => v,
+            27 | 28 => v - 27,
+            _ => return Err(LitCryptoError::InvalidSignature),
+        };
+        Ok(Self {
+            r: r.try_into().map_err(|_| LitCryptoError::InvalidSignature)?,
+            s: s.try_into().map_err(|_| LitCryptoError::InvalidSignature)?,
+            y_parity,
+        })
+    }
+
+    /// The signer, high s signatures are rejected as Ethereum does since EIP-2
+    pub fn recover(&self, hash: &[u8; 32]) -> JsResult<Address> {
+        let signature = Signature::from_scalars(self.r, self.s)
+            .map_err(|_| LitCryptoError::InvalidSignature)?;
+        if signature.normalize_s().is_some() {
+            return Err(LitCryptoError::InvalidSignature);
+        }
+        let recovery_id =
+            RecoveryId::from_byte(self.y_parity).ok_or(LitCryptoError::InvalidSignature)?;
+        let public_key = VerifyingKey::recover_from_prehash(hash, &signature, recovery_id)
+            .map_err(|_| LitCryptoError::InvalidSignature)?;
+        Ok(Address::from_public_key(public_key.as_affine()))
+    }
+}
+
+/// Hash to sign for a transaction, the `message_hash` to combine its signature with
+#[wasm_bindgen(js_name = "ethTxSigningHash")]
+pub fn eth_tx_signing_hash(transaction: EthTransaction) -> JsResult<Uint8Array> {
+    into_uint8array(transaction.signing_hash()?)
+}
+
+/// Raw transaction signed with the combined signature of its signing hash, unsigned without a signature
+#[wasm_bindgen(js_name = "ethTxSerialize")]
+pub fn eth_tx_serialize(
+    transaction: EthTransaction,
+    signature: Option<EcdsaSignature>,
+) -> JsResult<Uint8Array> {
+    let raw = match signature {
+        Some(signature) => transaction.encode_signed(&TxSignature::from_js(signature)?)?,
+        None => transaction.encode_unsigned()?,
+    };
+    into_uint8array(raw)
+}
+
+/// Decodes a signed raw transaction, recovering its sender
+#[wasm_bindgen(js_name = "ethTxDecode")]
+pub fn eth_tx_decode(raw: Uint8Array) -> JsResult<EthSignedTransaction> {
+    EthTransaction::decode(&from_js::<Vec<u8>>(raw)?)
+}
@@ -0,0 +1,338 @@
+//! ERC-4337 user operations of the EntryPoint v0.6 and v0.7, and the signatures their validators expect
+
+use elliptic_curve::sec1::ToEncodedPoint;
+use js_sys::Uint8Array;
+use k256::Secp256k1;
+use p256::ecdsa::signature::hazmat::PrehashVerifier;
+use p256::NistP256;
+use serde::{Deserialize, Serialize};
+use tsify::Tsify;
+use wasm_bindgen::prelude::*;
+
+use crate::abi::{from_js, into_uint8array, JsResult};
+use crate::ecdsa::{Ecdsa, EcdsaSignature, EcdsaVariant};
+use crate::error::LitCryptoError;
+use crate::hash::keccak256;
+
+use super::{Address, HexBytes, JsQuantity, Quantity};
+
+/// A user operation of the EntryPoint v0.6
+#[derive(Tsify, Serialize, Deserialize, Debug, Clone)]
+#[tsify(from_wasm_abi)]
+#[serde(rename_all = "camelCase")]
+pub struct UserOperationV06 {
+    #[tsify(type = "string")]
+    pub sender: Address,
+    #[tsify(type = "number | string")]
+    pub nonce: Quantity,
+    #[tsify(optional, type = "string")]
+    #[serde(default)]
+    pub init_code: HexBytes,
+    #[tsify(type = "string")]
+    pub call_data: HexBytes,
+    #[tsify(type = "number | string")]
+    pub call_gas_limit: Quantity,
+    #[tsify(type = "number | string")]
+    pub verification_gas_limit: Quantity,
+    #[tsify(type = "number | string")]
+    pub pre_verification_gas: Quantity,
+    #[tsify(type = "number | string")]
+    pub max_fee_per_gas: Quantity,
+    #[tsify(type = "number | string")]
+    pub max_priority_fee_per_gas: Quantity,
+    #[tsify(optional, type = "string")]
+    #[serde(default)]
+    pub paymaster_and_data: HexBytes,
+    /// Not part of the hash
+    #[tsify(optional, type = "string")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signature: Option<HexBytes>,
+}
+
+/// A user operation of the EntryPoint v0.7, as bundlers take it over RPC
+#[derive(Tsify, Serialize, Deserialize, Debug, Clone)]
+#[tsify(from_wasm_abi)]
+#[serde(rename_all = "camelCase")]
+pub struct UserOperationV07 {
+    #[tsify(type = "string")]
+    pub sender: Address,
+    #[tsify(type = "number | string")]
+    pub nonce: Quantity,
+    /// Only for the first operation of an account, which it deploys
+    #[tsify(optional, type = "string")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub factory: Option<Address>,
+    #[tsify(optional, type = "string")]
+    #[serde(default)]
+    pub factory_data: HexBytes,
+    #[tsify(type = "string")]
+    pub call_data: HexBytes,
+    #[tsify(type = "number | string")]
+    pub call_gas_limit: Quantity,
+    #[tsify(type = "number | string")]
+    pub verification_gas_limit: Quantity,
+    #[tsify(type = "number | string")]
+    pub pre_verification_gas: Quantity,
+    #[tsify(type = "number | string")]
+    pub max_fee_per_gas: Quantity,
+    #[tsify(type = "number | string")]
+    pub max_priority_fee_per_gas: Quantity,
+    #[tsify(optional, type = "string")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub paymaster: Option<Address>,
+    #[tsify(optional, type = "number | string")]
+    #[serde(default)]
+    pub paymaster_verification_gas_limit: Quantity,
+    #[tsify(optional, type = "number | string")]
+    #[serde(default)]
+    pub paymaster_post_op_gas_limit: Quantity,
+    #[tsify(optional, type = "string")]
+    #[serde(default)]
+    pub paymaster_data: HexBytes,
+    /// Not part of the hash
+    #[tsify(optional, type = "string")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signature: Option<HexBytes>,
+}
+
+/// A user operation of the EntryPoint v0.7 as `handleOps` takes it, its gas limits and fees packed by two
+#[derive(Tsify, Serialize, Deserialize, Debug, Clone)]
+#[tsify(into_wasm_abi, from_wasm_abi)]
+#[serde(rename_all = "camelCase")]
+pub struct PackedUserOperation {
+    #[tsify(type = "string")]
+    pub sender: Address,
+    #[tsify(type = "number | string")]
+    pub nonce: Quantity,
+    /// `factory ‖ factoryData`, empty without a factory
+    #[tsify(optional, type = "string")]
+    #[serde(default)]
+    pub init_code: HexBytes,
+    #[tsify(type = "string")]
+    pub call_data: HexBytes,
+    /// `verificationGasLimit ‖ callGasLimit`, 16 bytes each
+    #[tsify(type = "string")]
+    pub account_gas_limits: HexBytes,
+    #[tsify(type = "number | string")]
+    pub pre_verification_gas: Quantity,
+    /// `maxPriorityFeePerGas ‖ maxFeePerGas`, 16 bytes each
+    #[tsify(type = "string")]
+    pub gas_fees: HexBytes,
+    /// `paymaster ‖ paymasterVerificationGasLimit ‖ paymasterPostOpGasLimit ‖ paymasterData`, empty without a paymaster
+    #[tsify(optional, type = "string")]
+    #[serde(default)]
+    pub paymaster_and_data: HexBytes,
+    #[tsify(optional, type = "string")]
+    #[serde(default)]
+    pub signature: HexBytes,
+}
+
+fn invalid(reason: impl Into<String>) -> LitCryptoError {
+    LitCryptoError::UserOperation(reason.into())
+}
+
+fn address_word(address: &Address) -> [u8; 32] {
+    let mut word = [0u8; 32];
+    word[12..].copy_from_slice(&address.0);
+    word
+}
+
+/// `keccak256(abi.encode(keccak256(packedUserOp), entryPoint, chainId))`
+fn user_op_hash(packed: &[[u8; 32]], entry_point: &Address, chain_id: &Quantity) -> [u8; 32] {
+    keccak256(
+        [
+            keccak256(packed.concat()),
+            address_word(entry_point),
+            chain_id.0,
+        ]
+        .concat(),
+    )
+}
+
+/// Two quantities of up to 128 bits in a single word
+fn pack_u128(high: &Quantity, low: &Quantity, names: [&str; 2]) -> JsResult<HexBytes> {
+    let mut word = [0u8; 32];
+    for (i, (quantity, name)) in [high, low].into_iter().zip(names).enumerate() {
+        if quantity.0[..16] != [0; 16] {
+            return Err(invalid(format!("{name} does not fit in 128 bits")));
+        }
+        word[i * 16..(i + 1) * 16].copy_from_slice(&quantity.0[16..]);
+    }
+    Ok(HexBytes(word.to_vec()))
+}
+
+impl UserOperationV06 {
+    pub fn hash(&self, entry_point: &Address, chain_id: &Quantity) -> [u8; 32] {
+        let packed = [
+            address_word(&self.sender),
+            self.nonce.0,
+            keccak256(&self.init_code.0),
+            keccak256(&self.call_data.0),
+            self.call_gas_limit.0,
+            self.verification_gas_limit.0,
+            self.pre_verification_gas.0,
+            self.max_fee_per_gas.0,
+            self.max_priority_fee_per_gas.0,
+            keccak256(&self.paymaster_and_data.0),
+        ];
+        user_op_hash(&packed, entry_point, chain_id)
+    }
+}
+
+impl UserOperationV07 {
+    pub fn pack(&self) -> JsResult<PackedUserOperation> {
+        let init_code = match &self.factory {
+            Some(factory) => [&factory.0[..], &self.factory_data.0].concat(),
+            None => Vec::new(),
+        };
+        let paymaster_and_data = match &self.paymaster {
+            Some(paymaster) => [
+                &paymaster.0[..],
+                &pack_u128(
+                    &self.paymaster_verification_gas_limit,
+                    &self.paymaster_post_op_gas_limit,
+                    ["paymasterVerificationGasLimit", "paymasterPostOpGasLimit"],
+                )?
+                .0,
+                &self.paymaster_data.0,
+            ]
+            .concat(),
+            None => Vec::new(),
+        };
+
+        Ok(PackedUserOperation {
+            sender: self.sender,
+            nonce: self.nonce,
+            init_code: HexBytes(init_code),
+            call_data: self.call_data.clone(),
+            account_gas_limits: pack_u128(
+                &self.verification_gas_limit,
+                &self.call_gas_limit,
+                ["verificationGasLimit", "callGasLimit"],
+            )?,
+            pre_verification_gas: self.pre_verification_gas,
+            gas_fees: pack_u128(
+                &self.max_priority_fee_per_gas,
+                &self.max_fee_per_gas,
+                ["maxPriorityFeePerGas", "maxFeePerGas"],
+            )?,
+            paymaster_and_data: HexBytes(paymaster_and_data),
+            signature: self.signature.clone().unwrap_or_default(),
+        })
+    }
+}
+
+impl PackedUserOperation {
+    pub fn hash(&self, entry_point: &Address, chain_id: &Quantity) -> JsResult<[u8; 32]> {
+        let word = |bytes: &HexBytes, name: &str| {
+            <[u8; 32]>::try_from(bytes.0.as_slice())
+                .map_err(|_| invalid(format!("{name} must be 32 bytes")))
+        };
+        let packed = [
+            address_word(&self.sender),
+            self.nonce.0,
+            keccak256(&self.init_code.0),
+            keccak256(&self.call_data.0),
+            word(&self.account_gas_limits, "accountGasLimits")?,
+            self.pre_verification_gas.0,
+            word(&self.gas_fees, "gasFees")?,
+            keccak256(&self.paymaster_and_data.0),
+        ];
+        Ok(user_op_hash(&packed, entry_point, chain_id))
+    }
+}
+
+/// Hash the account signs for a user operation of the EntryPoint v0.6.
+/// Most ECDSA validators check a `personal_sign` of it, hash it with `hashMessage('Eip191', ...)` for those.
+#[wasm_bindgen(js_name = "userOpHashV06")]
+pub fn user_op_hash_v06(
+    user_operation: UserOperationV06,
+    entry_point: String,
+    chain_id: JsQuantity,
+) -> JsResult<Uint8Array> {
+    let (entry_point, chain_id) = entry_point_and_chain(entry_point, chain_id)?;
+    into_uint8array(user_operation.hash(&entry_point, &chain_id))
+}
+
+/// Hash the account signs for a user operation of the EntryPoint v0.7
+#[wasm_bindgen(js_name = "userOpHashV07")]
+pub fn user_op_hash_v07(
+    user_operation: UserOperationV07,
+    entry_point: String,
+    chain_id: JsQuantity,
+) -> JsResult<Uint8Array> {
+    let (entry_point, chain_id) = entry_point_and_chain(entry_point, chain_id)?;
+    into_uint8array(user_operation.pack()?.hash(&entry_point, &chain_id)?)
+}
+
+/// Hash the account signs for a packed user operation of the EntryPoint v0.7
+#[wasm_bindgen(js_name = "userOpHashPacked")]
+pub fn user_op_hash_packed(
+    user_operation: PackedUserOperation,
+    entry_point: String,
+    chain_id: JsQuantity,
+) -> JsResult<Uint8Array> {
+    let (entry_point, chain_id) = entry_point_and_chain(entry_point, chain_id)?;
+    into_uint8array(user_operation.hash(&entry_point, &chain_id)?)
+}
+
+/// Packs a user operation of the EntryPoint v0.7 as `handleOps` takes it
+#[wasm_bindgen(js_name = "userOpPack")]
+pub fn user_op_pack(user_operation: UserOperationV07) -> JsResult<PackedUserOperation> {
+    user_operation.pack()
+}
+
+fn entry_point_and_chain(
+    entry_point: String,
+    chain_id: JsQuantity,
+) -> JsResult<(Address, Quantity)> {
+    let entry_point = entry_point
+        .parse()
+        .map_err(|reason: String| invalid(reason))?;
+    Ok((entry_point, from_js(chain_id)?))
+}
+
+/// Encodes a combined signature for the `signature` of a user operation, with a low `s`:
+/// 65 bytes `r ‖ s ‖ v`, `v` being 27 or 28, for K256 validators,
+/// 64 bytes `r ‖ s` (`abi.encode(r, s)`) for P256 validators
+#[wasm_bindgen(js_name = "userOpSignature")]
+pub fn user_op_signature(variant: EcdsaVariant, signature: EcdsaSignature) -> JsResult<Uint8Array> {
+    match variant {
+        EcdsaVariant::K256 => {
+            let (rs, recovery_id) = Ecdsa::<Secp256k1>::low_s_signature(signature)?;
+            into_uint8array([&rs[..], &[27 + recovery_id]].concat())
+        }
+        EcdsaVariant::P256 => into_uint8array(Ecdsa::<NistP256>::low_s_signature(signature)?.0),
+        EcdsaVariant::P384 => Err(invalid("P384 signatures are not verifiable on chain")),
+    }
+}
+
+/// The 160 bytes input of the RIP-7212 P256 precompile, `hash ‖ r ‖ s ‖ x ‖ y`, with a low `s`.
+/// The signature is verified first so the precompile accepts it.
+#[wasm_bindgen(js_name = "userOpRip7212Input")]
+pub fn user_op_rip7212_input(
+    message_hash: Uint8Array,
+    signature: EcdsaSignature,
+    public_key: Uint8Array,
+) -> JsResult<Uint8Array> {
+    let message_hash = from_js::<Vec<u8>>(message_hash)?;
+    if message_hash.len() != 32 {
+        return Err(LitCryptoError::DigestLength {
+            expected: 32,
+            actual: message_hash.len(),
+        });
+    }
+    let public_key = from_js::<Vec<u8>>(public_key)?;
+    let public_key = p256::ecdsa::VerifyingKey::from_sec1_bytes(&public_key)
+        .map_err(|_| LitCryptoError::InvalidPoint)?;
+
+    let (rs, _) = Ecdsa::<NistP256>::low_s_signature(signature)?;
+    let verified = p256::ecdsa::Signature::from_slice(&rs)
+        .and_then(|rs| public_key.verify_prehash(&message_hash, &rs));
+    if verified.is_err() {
+        return Err(LitCryptoError::SignatureMismatch);
+    }
+
+    let public_key = public_key.as_affine().to_encoded_point(false);
+    into_uint8array([&message_hash[..], &rs, &public_key.as_bytes()[1..]].concat())
+}
@@ -0,0 +1,95 @@
+//! Quantities and byte strings as the Ethereum JSON-RPC API writes them
+
+use std::fmt;
+
+use serde::{de::Error as _, Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::Value;
+use wasm_bindgen::prelude::*;
+
+use super::rlp;
+use crate::hash::parse_integer;
+
+#[wasm_bindgen]
+extern "C" {
+    /// A `Quantity` passed on its own
+    #[wasm_bindgen(typescript_type = "number | string")]
+    pub type JsQuantity;
+}
+
+/// An unsigned integer of up to 256 bits, read from a JS number or a decimal or 0x-prefixed hex string
+/// and written as a 0x-prefixed hex string
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Quantity(pub [u8; 32]);
+
+impl Quantity {
+    pub fn from_u64(value: u64) -> Self {
+        let mut quantity = [0u8; 32];
+        quantity[24..].copy_from_slice(&value.to_be_bytes());
+        Self(quantity)
+    }
+
+    /// Big endian without leading zeros, empty for zero
+    pub fn as_minimal_bytes(&self) -> &[u8] {
+        rlp::trim_leading_zeros(&self.0)
+    }
+
+    pub fn to_u64(self) -> Option<u64> {
+        let bytes = self.as_minimal_bytes();
+        (bytes.len() <= 8).then(|| bytes.iter().fold(0, |n, byte| n << 8 | u64::from(*byte)))
+    }
+}
+
+impl fmt::Display for Quantity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let digits = hex::encode(self.as_minimal_bytes());
+        match digits.trim_start_matches('0') {
+            "" => f.write_str("0x0"),
+            digits => write!(f, "0x{digits}"),
+        }
+    }
+}
+
+impl Serialize for Quantity {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Quantity {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let value = Value::deserialize(deserializer)?;
+        match parse_integer(&value) {
+            Some((false, quantity)) => Ok(Self(quantity)),
+            _ => Err(D::Error::custom(format!(
+                "{value} is not an unsigned integer of up to 256 bits"
+            ))),
+        }
+    }
+}
+
+/// Bytes read from and written as a 0x-prefixed hex string
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct HexBytes(pub Vec<u8>);
+
+impl fmt::Display for HexBytes {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "0x{}", hex::encode(&self.0))
+    }
+}
+
+impl Serialize for HexBytes {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for HexBytes {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let value = String::deserialize(deserializer)?;
+        value
+            .strip_prefix("0x")
+            .and_then(|digits| hex::decode(digits).ok())
+            .map(Self)
+            .ok_or_else(|| D::Error::custom(format!("{value} is not 0x-prefixed hex")))
+    }
+}
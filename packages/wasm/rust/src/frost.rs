@@ -0,0 +1,93 @@
+use blsful::inner_types::{G1Projective, G2Projective};
+use curve25519_dalek_ml::{EdwardsPoint, RistrettoPoint};
+use elliptic_curve::group::GroupEncoding;
+use hd_keys_curves_wasm::{HDDerivable, HDDeriver};
+use js_sys::Uint8Array;
+use jubjub_plus::SubgroupPoint;
+use serde::Deserialize;
+use tsify::Tsify;
+use wasm_bindgen::prelude::*;
+
+use crate::abi::{from_js, into_uint8array, JsResult};
+use crate::error::LitCryptoError;
+
+#[derive(Tsify, Deserialize)]
+#[tsify(from_wasm_abi)]
+pub enum FrostVariant {
+    Ed25519,
+    Ristretto255,
+    Bls12381G1,
+    Bls12381G2,
+    Jubjub,
+}
+
+struct Frost<G>(G);
+
+trait HdCtx {
+    const CTX: &'static [u8];
+}
+
+impl HdCtx for EdwardsPoint {
+    const CTX: &'static [u8] = b"LIT_HD_KEY_ID_ED25519_XMD:SHA-512_ELL2_RO_NUL_";
+}
+
+impl HdCtx for RistrettoPoint {
+    const CTX: &'static [u8] = b"LIT_HD_KEY_ID_RISTRETTO255_XMD:SHA-512_ELL2_RO_NUL_";
+}
+
+impl HdCtx for G1Projective {
+    const CTX: &'static [u8] = b"LIT_HD_KEY_ID_BLS12381G1_XMD:SHA-256_SSWU_RO_NUL_";
+}
+
+// The node only derives G1 keys, this context is not shared with it
+impl HdCtx for G2Projective {
+    const CTX: &'static [u8] = b"LIT_HD_KEY_ID_BLS12381G2_XMD:SHA-256_SSWU_RO_NUL_";
+}
+
+impl HdCtx for SubgroupPoint {
+    const CTX: &'static [u8] = b"LIT_HD_KEY_ID_REDJUBJUB_XMD:BLAKE2B-512_ELL2_RO_NUL_";
+}
+
+impl<G> Frost<G>
+where
+    G: HDDerivable + GroupEncoding + HdCtx,
+    G::Scalar: HDDeriver,
+{
+    pub fn derive_key(id: Uint8Array, public_keys: Vec<Uint8Array>) -> JsResult<Uint8Array> {
+        let id = from_js::<Vec<u8>>(id)?;
+        let public_keys = public_keys
+            .into_iter()
+            .map(Self::point_from_js)
+            .collect::<JsResult<Vec<_>>>()?;
+
+        let deriver = G::Scalar::create(&id, G::CTX);
+        into_uint8array(deriver.hd_derive_public_key(&public_keys).to_bytes())
+    }
+
+    fn point_from_js(q: Uint8Array) -> JsResult<G> {
+        let q = from_js::<Vec<u8>>(q)?;
+        let mut repr = G::Repr::default();
+        if q.len() != repr.as_ref().len() {
+            return Err(LitCryptoError::InvalidPoint);
+        }
+        repr.as_mut().copy_from_slice(&q);
+
+        Option::from(G::from_bytes(&repr)).ok_or(LitCryptoError::InvalidPoint)
+    }
+}
+
+/// HD key derivation for the curves of FROST keys, the keys are in their compressed encoding
+#[wasm_bindgen(js_name = "frostDeriveKey")]
+pub fn frost_derive_key(
+    variant: FrostVariant,
+    id: Uint8Array,
+    public_keys: Vec<Uint8Array>,
+) -> JsResult<Uint8Array> {
+    match variant {
+        FrostVariant::Ed25519 => Frost::<EdwardsPoint>::derive_key(id, public_keys),
+        FrostVariant::Ristretto255 => Frost::<RistrettoPoint>::derive_key(id, public_keys),
+        FrostVariant::Bls12381G1 => Frost::<G1Projective>::derive_key(id, public_keys),
+        FrostVariant::Bls12381G2 => Frost::<G2Projective>::derive_key(id, public_keys),
+        FrostVariant::Jubjub => Frost::<SubgroupPoint>::derive_key(id, public_keys),
+    }
+}
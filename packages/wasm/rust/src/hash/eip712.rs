@@ -0,0 +1,316 @@
+//! EIP-712 hashing of typed data, as `eth_signTypedData_v4` does it
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use serde::Deserialize;
+use serde_json::{Map, Value};
+use tsify::Tsify;
+
+use crate::abi::JsResult;
+use crate::error::LitCryptoError;
+
+use super::keccak256;
+
+const DOMAIN_TYPE: &str = "EIP712Domain";
+
+/// Fields a domain may have, in the order of the implicit `EIP712Domain` type
+const DOMAIN_FIELDS: [(&str, &str); 5] = [
+    ("name", "string"),
+    ("version", "string"),
+    ("chainId", "uint256"),
+    ("verifyingContract", "address"),
+    ("salt", "bytes32"),
+];
+
+#[derive(Tsify, Deserialize, Debug, Clone)]
+pub struct TypedDataField {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub kind: String,
+}
+
+/// The object `eth_signTypedData_v4` takes, `types.EIP712Domain` is inferred from the domain when missing
+#[derive(Tsify, Deserialize, Debug, Clone)]
+#[tsify(from_wasm_abi)]
+#[serde(rename_all = "camelCase")]
+pub struct TypedData {
+    pub types: BTreeMap<String, Vec<TypedDataField>>,
+    pub primary_type: String,
+    #[tsify(type = "Record<string, unknown>")]
+    pub domain: Map<String, Value>,
+    /// Not needed when the primary type is `EIP712Domain`
+    #[tsify(optional, type = "Record<string, unknown>")]
+    #[serde(default)]
+    pub message: Map<String, Value>,
+}
+
+fn invalid(reason: impl Into<String>) -> LitCryptoError {
+    LitCryptoError::TypedData(reason.into())
+}
+
+impl TypedData {
+    /// `keccak256(0x1901 ‖ domainSeparator ‖ hashStruct(message))`
+    pub fn hash(&self) -> JsResult<[u8; 32]> {
+        let types = self.types_with_domain();
+        let encoder = Encoder { types: &types };
+
+        let mut payload = vec![0x19, 0x01];
+        payload.extend(encoder.hash_struct(DOMAIN_TYPE, &self.domain)?);
+        // Signing the domain alone leaves the message out
+        if self.primary_type != DOMAIN_TYPE {
+            payload.extend(encoder.hash_struct(&self.primary_type, &self.message)?);
+        }
+        Ok(keccak256(payload))
+    }
+
+    fn types_with_domain(&self) -> BTreeMap<String, Vec<TypedDataField>> {
+        let mut types = self.types.clone();
+        types.entry(DOMAIN_TYPE.to_string()).or_insert_with(|| {
+            DOMAIN_FIELDS
+                .iter()
+                .filter(|(name, _)| self.domain.contains_key(*name))
+                .map(|(name, kind)| TypedDataField {
+                    name: name.to_string(),
+                    kind: kind.to_string(),
+                })
+                .collect()
+        });
+        types
+    }
+}
+
+struct Encoder<'a> {
+    types: &'a BTreeMap<String, Vec<TypedDataField>>,
+}
+
+impl Encoder<'_> {
+    fn fields(&self, name: &str) -> JsResult<&[TypedDataField]> {
+        self.types
+            .get(name)
+            .map(Vec::as_slice)
+            .ok_or_else(|| invalid(format!("unknown type {name}")))
+    }
+
+    /// `Name(type field,...)` of the type, followed by the types it references sorted by name
+    fn encode_type(&self, name: &str) -> JsResult<String> {
+        let mut dependencies = BTreeSet::new();
+        self.collect_dependencies(name, &mut dependencies)?;
+        dependencies.remove(name);
+
+        std::iter::once(name)
+            .chain(dependencies.iter().map(String::as_str))
+            .map(|name| {
+                let fields = self
+                    .fields(name)?
+                    .iter()
+                    .map(|field| format!("{} {}", field.kind, field.name))
+                    .collect::<Vec<_>>();
+                Ok(format!("{name}({})", fields.join(",")))
+            })
+            .collect()
+    }
+
+    fn collect_dependencies(&self, name: &str, found: &mut BTreeSet<String>) -> JsResult<()> {
+        if !found.insert(name.to_string()) {
+            return Ok(());
+        }
+        for field in self.fields(name)? {
+            let base = base_type(&field.kind);
+            if self.types.contains_key(base) {
+                self.collect_dependencies(base, found)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn hash_struct(&self, name: &str, data: &Map<String, Value>) -> JsResult<[u8; 32]> {
+        let mut encoded = keccak256(self.encode_type(name)?).to_vec();
+        for field in self.fields(name)? {
+            let value = data
+                .get(&field.name)
+                .ok_or_else(|| invalid(format!("missing {name}.{}", field.name)))?;
+            encoded.extend(self.encode_value(&field.kind, value)?);
+        }
+        Ok(keccak256(encoded))
+    }
+
+    fn encode_value(&self, kind: &str, value: &Value) -> JsResult<[u8; 32]> {
+        let mismatch = || invalid(format!("{value} is not a valid {kind}"));
+
+        if let Some((item, length)) = array_type(kind) {
+            let items = value.as_array().ok_or_else(mismatch)?;
+            if length.is_some_and(|length| length != items.len()) {
+                return Err(mismatch());
+            }
+            let encoded = items
+                .iter()
+                .map(|item_value| self.encode_value(item, item_value))
+                .collect::<JsResult<Vec<_>>>()?;
+            return Ok(keccak256(encoded.concat()));
+        }
+
+        if self.types.contains_key(kind) {
+            return self.hash_struct(kind, value.as_object().ok_or_else(mismatch)?);
+        }
+
+        let mut word = [0u8; 32];
+        match kind {
+            "string" => return Ok(keccak256(value.as_str().ok_or_else(mismatch)?)),
+            "bytes" => return Ok(keccak256(hex_bytes(value).ok_or_else(mismatch)?)),
+            "bool" => word[31] = value.as_bool().ok_or_else(mismatch)?.into(),
+            "address" => {
+                let address = hex_bytes(value)
+                    .filter(|address| address.len() == 20)
+                    .ok_or_else(mismatch)?;
+                word[12..].copy_from_slice(&address);
+            }
+            _ => word = encode_sized(kind, value)?,
+        }
+        Ok(word)
+    }
+}
+
+/// Encodes `bytesN`, `uintN` and `intN` values
+fn encode_sized(kind: &str, value: &Value) -> JsResult<[u8; 32]> {
+    let mismatch = || invalid(format!("{value} is not a valid {kind}"));
+    let unknown = || invalid(format!("unknown type {kind}"));
+
+    let mut word = [0u8; 32];
+    if let Some(size) = sized_type(kind, "bytes") {
+        if !(1..=32).contains(&size) {
+            return Err(unknown());
+        }
+        let bytes = hex_bytes(value)
+            .filter(|bytes| bytes.len() == size)
+            .ok_or_else(mismatch)?;
+        word[..size].copy_from_slice(&bytes);
+        return Ok(word);
+    }
+
+    let (signed, bits) = match (sized_type(kind, "uint"), sized_type(kind, "int")) {
+        (Some(bits), _) => (false, bits),
+        (None, Some(bits)) => (true, bits),
+        (None, None) => return Err(unknown()),
+    };
+    if bits % 8 != 0 || !(8..=256).contains(&bits) {
+        return Err(unknown());
+    }
+
+    let (negative, magnitude) = parse_integer(value).ok_or_else(mismatch)?;
+    let length = bit_length(&magnitude);
+    let fits = match (signed, negative) {
+        (false, false) => length <= bits,
+        (false, true) => false,
+        (true, false) => length < bits,
+        // -2^(bits-1) is the only value whose magnitude takes all the bits
+        (true, true) => length < bits || (length == bits && is_power_of_two(&magnitude)),
+    };
+    if !fits {
+        return Err(mismatch());
+    }
+
+    Ok(if negative {
+        negate(magnitude)
+    } else {
+        magnitude
+    })
+}
+
+/// The type of the items and the length of an array type, `None` for a dynamic array
+fn array_type(kind: &str) -> Option<(&str, Option<usize>)> {
+    let (item, length) = kind.strip_suffix(']')?.rsplit_once('[')?;
+    match length {
+        "" => Some((item, None)),
+        length => length.parse().ok().map(|length| (item, Some(length))),
+    }
+}
+
+/// The struct or atomic type an array type holds
+fn base_type(kind: &str) -> &str {
+    kind.split_once('[').map_or(kind, |(base, _)| base)
+}
+
+/// `N` of `bytesN`, `uintN` and `intN`, the size defaulting to 256 bits for `uint` and `int`
+fn sized_type(kind: &str, prefix: &str) -> Option<usize> {
+    match kind.strip_prefix(prefix)? {
+        "" if prefix != "bytes" => Some(256),
+        size if !size.starts_with('0') => size.parse().ok(),
+        _ => None,
+    }
+}
+
+fn hex_bytes(value: &Value) -> Option<Vec<u8>> {
+    let value = value.as_str()?;
+    hex::decode(
+        value
+            .strip_prefix("0x")
+            .or_else(|| value.strip_prefix("0X"))?,
+    )
+    .ok()
+}
+
+/// Sign and 256 bits big endian magnitude of a JSON number, or of a decimal or 0x-prefixed hex string
+pub(crate) fn parse_integer(value: &Value) -> Option<(bool, [u8; 32])> {
+    let mut magnitude = [0u8; 32];
+    match value {
+        Value::Number(number) => {
+            let (negative, value) = match (number.as_u64(), number.as_i64()) {
+                (Some(value), _) => (false, value),
+                (None, Some(value)) => (true, value.unsigned_abs()),
+                _ => return None,
+            };
+            magnitude[24..].copy_from_slice(&value.to_be_bytes());
+            Some((negative, magnitude))
+        }
+        Value::String(value) => {
+            let (negative, digits) = match value.strip_prefix('-') {
+                Some(digits) => (true, digits),
+                None => (false, value.as_str()),
+            };
+            let (radix, digits) = match digits.strip_prefix("0x") {
+                Some(digits) => (16, digits),
+                None => (10, digits),
+            };
+            if digits.is_empty() {
+                return None;
+            }
+            for digit in digits.chars() {
+                let digit = digit.to_digit(radix)?;
+                // magnitude = magnitude * radix + digit
+                let mut carry = digit;
+                for byte in magnitude.iter_mut().rev() {
+                    let product = u32::from(*byte) * radix + carry;
+                    *byte = product as u8;
+                    carry = product >> 8;
+                }
+                if carry != 0 {
+                    return None;
+                }
+            }
+            Some((negative && magnitude != [0; 32], magnitude))
+        }
+        _ => None,
+    }
+}
+
+fn bit_length(magnitude: &[u8; 32]) -> usize {
+    magnitude
+        .iter()
+        .position(|byte| *byte != 0)
+        .map_or(0, |i| (32 - i) * 8 - magnitude[i].leading_zeros() as usize)
+}
+
+fn is_power_of_two(magnitude: &[u8; 32]) -> bool {
+    magnitude.iter().map(|byte| byte.count_ones()).sum::<u32>() == 1
+}
+
+/// Two's complement over 256 bits
+fn negate(mut magnitude: [u8; 32]) -> [u8; 32] {
+    let mut carry = true;
+    for byte in magnitude.iter_mut().rev() {
+        let (sum, overflow) = (!*byte).overflowing_add(carry.into());
+        *byte = sum;
+        carry = overflow;
+    }
+    magnitude
+}
@@ -0,0 +1,67 @@
+//! Digests of messages as the ECDSA schemes sign them
+
+mod eip712;
+
+use js_sys::Uint8Array;
+use serde::Deserialize;
+use sha2::{Sha256, Sha384};
+use sha3::{Digest, Keccak256};
+use tsify::Tsify;
+use wasm_bindgen::prelude::*;
+
+use crate::abi::{from_js, into_uint8array, JsResult};
+use crate::error::LitCryptoError;
+
+#[cfg(feature = "ethereum")]
+pub(crate) use eip712::parse_integer;
+pub use eip712::{TypedData, TypedDataField};
+
+#[derive(Tsify, Deserialize, Debug, Clone, Copy)]
+#[tsify(from_wasm_abi)]
+pub enum MessageHasher {
+    Keccak256,
+    Sha256,
+    Sha384,
+    /// `personal_sign`, keccak256 of the message behind `"\x19Ethereum Signed Message:\n" + length`
+    Eip191,
+    /// The message is the JSON encoding of the typed data
+    Eip712,
+}
+
+impl MessageHasher {
+    pub fn hash(&self, message: &[u8]) -> JsResult<Vec<u8>> {
+        Ok(match self {
+            Self::Keccak256 => keccak256(message).to_vec(),
+            Self::Sha256 => Sha256::digest(message).to_vec(),
+            Self::Sha384 => Sha384::digest(message).to_vec(),
+            Self::Eip191 => eip191_hash(message).to_vec(),
+            Self::Eip712 => serde_json::from_slice::<TypedData>(message)
+                .map_err(|e| LitCryptoError::TypedData(e.to_string()))?
+                .hash()?
+                .to_vec(),
+        })
+    }
+}
+
+pub fn keccak256(data: impl AsRef<[u8]>) -> [u8; 32] {
+    Keccak256::digest(data).into()
+}
+
+pub fn eip191_hash(message: &[u8]) -> [u8; 32] {
+    let mut prefixed = format!("\x19Ethereum Signed Message:\n{}", message.len()).into_bytes();
+    prefixed.extend_from_slice(message);
+    keccak256(prefixed)
+}
+
+/// Digest of the message to pass as `message_hash` to the ECDSA functions
+#[wasm_bindgen(js_name = "hashMessage")]
+pub fn hash_message(hasher: MessageHasher, message: Uint8Array) -> JsResult<Uint8Array> {
+    let message = from_js::<Vec<u8>>(message)?;
+    into_uint8array(hasher.hash(&message)?)
+}
+
+/// EIP-712 digest of typed data, what `eth_signTypedData_v4` signs
+#[wasm_bindgen(js_name = "eip712Hash")]
+pub fn eip712_hash(typed_data: TypedData) -> JsResult<Uint8Array> {
+    into_uint8array(typed_data.hash()?)
+}
@@ -1,7 +1,31 @@
 pub mod abi;
+#[cfg(any(feature = "sev-snp", feature = "tdx"))]
+pub mod binding;
+#[cfg(feature = "bitcoin")]
+pub mod bitcoin;
+#[cfg(feature = "bls")]
 pub mod bls;
+#[cfg(feature = "cosmos")]
+pub mod cosmos;
+#[cfg(feature = "ecdsa")]
 pub mod ecdsa;
+pub mod error;
+#[cfg(feature = "ecdsa")]
+pub mod ethereum;
+#[cfg(feature = "frost")]
+pub mod frost;
+#[cfg(feature = "ecdsa")]
+pub mod hash;
+#[cfg(feature = "ecdsa")]
+pub mod pkp;
+#[cfg(feature = "sev-snp")]
 pub mod sev_snp;
+#[cfg(feature = "sui")]
+pub mod sui;
+#[cfg(feature = "tdx")]
+pub mod tdx;
+#[cfg(any(feature = "sev-snp", feature = "tdx"))]
+pub mod x509;
 
 use wasm_bindgen::prelude::*;
 
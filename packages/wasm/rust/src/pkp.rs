@@ -0,0 +1,115 @@
+//! Key ids, public keys and addresses of the PKPs claimable by an auth method
+
+use elliptic_curve::sec1::ToEncodedPoint;
+use js_sys::Uint8Array;
+use k256::Secp256k1;
+use serde::{Deserialize, Serialize};
+use tsify::Tsify;
+use wasm_bindgen::prelude::*;
+
+use crate::abi::{into_uint8array, JsResult};
+use crate::ecdsa::Ecdsa;
+use crate::ethereum::eth_address;
+use crate::hash::keccak256;
+
+/// What the key id of a PKP is derived from
+#[derive(Tsify, Deserialize, Debug, Clone)]
+#[tsify(from_wasm_abi)]
+#[serde(untagged, try_from = "PkpAuthMethodFields")]
+pub enum PkpAuthMethod {
+    /// An auth method, the key id is `keccak256("<authMethodType>:<authMethodId>")`
+    AuthMethod {
+        /// One of `AUTH_METHOD_TYPE`
+        #[serde(rename = "authMethodType")]
+        auth_method_type: u32,
+        /// The user id the auth method resolves to, as the nodes compute it when claiming
+        #[serde(rename = "authMethodId")]
+        auth_method_id: String,
+    },
+    /// Bytes hashed as is into the key id, for conventions other than the claim one
+    KeccakInput {
+        #[tsify(type = "Uint8Array")]
+        #[serde(rename = "keccakInput", with = "serde_bytes")]
+        keccak_input: Vec<u8>,
+    },
+}
+
+/// Byte arrays cannot be buffered to try each variant of an untagged enum, so the fields are read flat
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PkpAuthMethodFields {
+    auth_method_type: Option<u32>,
+    auth_method_id: Option<String>,
+    #[serde(default, with = "serde_bytes")]
+    keccak_input: Option<Vec<u8>>,
+}
+
+impl TryFrom<PkpAuthMethodFields> for PkpAuthMethod {
+    type Error = String;
+
+    fn try_from(fields: PkpAuthMethodFields) -> Result<Self, Self::Error> {
+        match fields {
+            PkpAuthMethodFields {
+                auth_method_type: Some(auth_method_type),
+                auth_method_id: Some(auth_method_id),
+                keccak_input: None,
+            } => Ok(Self::AuthMethod {
+                auth_method_type,
+                auth_method_id,
+            }),
+            PkpAuthMethodFields {
+                auth_method_type: None,
+                auth_method_id: None,
+                keccak_input: Some(keccak_input),
+            } => Ok(Self::KeccakInput { keccak_input }),
+            _ => Err("expected either authMethodType and authMethodId, or keccakInput".to_string()),
+        }
+    }
+}
+
+impl PkpAuthMethod {
+    pub fn key_id(&self) -> [u8; 32] {
+        match self {
+            Self::AuthMethod {
+                auth_method_type,
+                auth_method_id,
+            } => keccak256(format!("{auth_method_type}:{auth_method_id}")),
+            Self::KeccakInput { keccak_input } => keccak256(keccak_input),
+        }
+    }
+}
+
+/// A PKP as `claimKeyId` would derive it
+#[derive(Tsify, Serialize, Debug, Clone)]
+#[tsify(into_wasm_abi)]
+#[serde(rename_all = "camelCase")]
+pub struct PkpKey {
+    #[tsify(type = "Uint8Array")]
+    #[serde(with = "serde_bytes")]
+    pub key_id: Vec<u8>,
+    /// Uncompressed, like `ecdsaDeriveKey`
+    #[tsify(type = "Uint8Array")]
+    #[serde(with = "serde_bytes")]
+    pub public_key: Vec<u8>,
+    /// EIP-55 checksummed
+    pub eth_address: String,
+}
+
+/// Key id of the PKP claimable by an auth method
+#[wasm_bindgen(js_name = "pkpKeyId")]
+pub fn pkp_key_id(auth_method: PkpAuthMethod) -> JsResult<Uint8Array> {
+    into_uint8array(auth_method.key_id())
+}
+
+/// Key id, public key and address of the PKP claimable by an auth method, from the root keys of the network
+#[wasm_bindgen(js_name = "pkpDerive")]
+pub fn pkp_derive(auth_method: PkpAuthMethod, public_keys: Vec<Uint8Array>) -> JsResult<PkpKey> {
+    let key_id = auth_method.key_id();
+    let public_key = Ecdsa::<Secp256k1>::derive_point(&key_id, public_keys)?.to_affine();
+
+    Ok(PkpKey {
+        key_id: key_id.to_vec(),
+        public_key: public_key.to_encoded_point(false).as_bytes().to_vec(),
+        eth_address: eth_address(&public_key),
+    })
+}
@@ -0,0 +1,171 @@
+//! Certificates given in place of the bare VCEK: a DER or PEM certificate, a PEM bundle
+//! with the ASK and ARK, or the cache blob of a chain that was already verified.
+//!
+//! The blob is `MAGIC`, the version byte, then the ARK, the signer (ASK or ASVK) and the
+//! endorsement key (VCEK or VLEK) as DER, each prefixed with its u16 big endian length.
+//! Nothing in a bundle is trusted: its ARK must be the trusted one and the chain is verified on every use.
+
+use sev::certs::snp::Certificate;
+use x509_cert::der::{Decode, Encode};
+
+use crate::abi::JsResult;
+use crate::error::LitCryptoError;
+
+const MAGIC: &[u8] = b"LSNP";
+const VERSION: u8 = 1;
+
+pub struct CertBundle {
+    pub ark: Option<Certificate>,
+    /// ASK or ASVK
+    pub signer: Option<Certificate>,
+    /// VCEK or VLEK
+    pub endorsement_key: Certificate,
+}
+
+impl CertBundle {
+    pub fn parse(bytes: &[u8]) -> JsResult<Self> {
+        if let Some(blob) = bytes.strip_prefix(MAGIC) {
+            return Self::from_blob(blob);
+        }
+
+        let certs = if bytes.starts_with(b"-----BEGIN") {
+            x509_cert::Certificate::load_pem_chain(bytes)
+        } else {
+            x509_cert::Certificate::from_der(bytes).map(|cert| vec![cert])
+        }
+        .map_err(|e| LitCryptoError::Deserialize(e.to_string()))?;
+
+        Self::classify(certs)
+    }
+
+    pub fn new(ark: Certificate, signer: Certificate, endorsement_key: Certificate) -> Self {
+        Self {
+            ark: Some(ark),
+            signer: Some(signer),
+            endorsement_key,
+        }
+    }
+
+    /// Serializes the bundle into the cache blob
+    pub fn to_blob(&self) -> JsResult<Vec<u8>> {
+        let mut blob = MAGIC.to_vec();
+        blob.push(VERSION);
+        for cert in [
+            self.ark.as_ref(),
+            self.signer.as_ref(),
+            Some(&self.endorsement_key),
+        ] {
+            let der = match cert {
+                Some(cert) => cert
+                    .to_der()
+                    .map_err(|e| LitCryptoError::Serialize(e.to_string()))?,
+                None => Vec::new(),
+            };
+            let len = u16::try_from(der.len())
+                .map_err(|_| LitCryptoError::Serialize("certificate too large".to_string()))?;
+            blob.extend_from_slice(&len.to_be_bytes());
+            blob.extend_from_slice(&der);
+        }
+        Ok(blob)
+    }
+
+    fn from_blob(blob: &[u8]) -> JsResult<Self> {
+        let invalid = |reason: &str| {
+            LitCryptoError::Deserialize(format!("invalid certificate cache blob: {reason}"))
+        };
+
+        let (version, mut rest) = blob.split_first().ok_or_else(|| invalid("truncated"))?;
+        if *version != VERSION {
+            return Err(invalid(&format!("unsupported version {version}")));
+        }
+
+        let mut certs = Vec::with_capacity(3);
+        for _ in 0..3 {
+            if rest.len() < 2 {
+                return Err(invalid("truncated"));
+            }
+            let (len, tail) = rest.split_at(2);
+            let len = u16::from_be_bytes([len[0], len[1]]) as usize;
+            if tail.len() < len {
+                return Err(invalid("truncated"));
+            }
+            let (der, tail) = tail.split_at(len);
+            certs.push((!der.is_empty()).then(|| parse_der(der)).transpose()?);
+            rest = tail;
+        }
+        if !rest.is_empty() {
+            return Err(invalid("trailing bytes"));
+        }
+
+        let endorsement_key = certs
+            .pop()
+            .flatten()
+            .ok_or_else(|| invalid("no VCEK or VLEK"))?;
+        let signer = certs.pop().flatten();
+        let ark = certs.pop().flatten();
+        Ok(Self {
+            ark,
+            signer,
+            endorsement_key,
+        })
+    }
+
+    /// Sorts the certificates of a bundle by what signed what, so their order doesn't matter:
+    /// the ARK signs itself, the signer is issued by another certificate of the bundle and the rest is the endorsement key
+    fn classify(certs: Vec<x509_cert::Certificate>) -> JsResult<Self> {
+        let invalid = |reason: &str| {
+            LitCryptoError::Deserialize(format!("invalid certificate bundle: {reason}"))
+        };
+
+        let is_root = |cert: &x509_cert::Certificate| {
+            cert.tbs_certificate.issuer == cert.tbs_certificate.subject
+        };
+        let issues_another = |cert: &x509_cert::Certificate| {
+            certs.iter().any(|other| {
+                !is_root(other) && other.tbs_certificate.issuer == cert.tbs_certificate.subject
+            })
+        };
+
+        let (mut ark, mut signer, mut endorsement_key) = (None, None, None);
+        for cert in &certs {
+            let slot = if is_root(cert) {
+                &mut ark
+            } else if issues_another(cert) {
+                &mut signer
+            } else {
+                &mut endorsement_key
+            };
+            if slot.replace(cert).is_some() {
+                return Err(invalid("more than one certificate for the same role"));
+            }
+        }
+
+        let convert = |cert: &x509_cert::Certificate| {
+            parse_der(
+                &cert
+                    .to_der()
+                    .map_err(|e| LitCryptoError::Serialize(e.to_string()))?,
+            )
+        };
+        let endorsement_key = endorsement_key.ok_or_else(|| invalid("no VCEK or VLEK"))?;
+        Ok(Self {
+            ark: ark.map(convert).transpose()?,
+            signer: signer.map(convert).transpose()?,
+            endorsement_key: convert(endorsement_key)?,
+        })
+    }
+}
+
+/// Accepts DER or PEM
+pub fn parse_certificate(certificate: &[u8]) -> JsResult<Certificate> {
+    let certificate = if certificate.starts_with(b"-----BEGIN") {
+        Certificate::from_pem(certificate)
+    } else {
+        Certificate::from_der(certificate)
+    };
+    certificate.map_err(|e| LitCryptoError::Deserialize(e.to_string()))
+}
+
+fn parse_der(der: &[u8]) -> JsResult<Certificate> {
+    Certificate::from_der(der).map_err(|e| LitCryptoError::Deserialize(e.to_string()))
+}
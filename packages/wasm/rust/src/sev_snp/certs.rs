@@ -0,0 +1,41 @@
+//! Revocation of the ARK, ASK and VCEK
+//!
+//! The `sev` crate only checks signatures, everything depending on the time is done here
+//! and in `crate::x509`.
+
+use rsa::signature::Verifier;
+use sev::certs::snp::Certificate;
+use x509_cert::der::{referenced::OwnedToRef, Decode};
+use x509_cert::spki::ObjectIdentifier;
+
+use crate::abi::JsResult;
+use crate::error::LitCryptoError;
+use crate::x509::Crl;
+
+/// RSASSA-PSS, which AMD signs its certificates and CRLs with
+const RSA_SSA_PSS_OID: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.2.840.113549.1.1.10");
+
+/// `sev::certs::snp::Certificate` hides the parsed certificate, so we parse it again
+pub fn to_x509(cert: &Certificate) -> JsResult<x509_cert::Certificate> {
+    let der = cert
+        .to_der()
+        .map_err(|e| LitCryptoError::Serialize(e.to_string()))?;
+    x509_cert::Certificate::from_der(&der).map_err(|e| LitCryptoError::Deserialize(e.to_string()))
+}
+
+/// Checks that the ARK issued and signed the CRL, and that it is current at `time` (unix seconds)
+pub fn verify_crl(crl: &Crl, ark: &x509_cert::Certificate, time: u64) -> JsResult<()> {
+    crl.verify("ARK", ark, time, |algorithm, message, signature| {
+        if *algorithm != RSA_SSA_PSS_OID {
+            return Err(format!("unsupported signature algorithm {algorithm}"));
+        }
+        let key =
+            rsa::RsaPublicKey::try_from(ark.tbs_certificate.subject_public_key_info.owned_to_ref())
+                .map_err(|e| format!("invalid ARK public key: {e}"))?;
+        let signature = rsa::pss::Signature::try_from(signature)
+            .map_err(|e| format!("invalid signature: {e}"))?;
+        rsa::pss::VerifyingKey::<sha2::Sha384>::new(key)
+            .verify(message, &signature)
+            .map_err(|_| "not signed by the ARK".to_string())
+    })
+}
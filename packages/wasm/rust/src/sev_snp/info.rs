@@ -0,0 +1,137 @@
+//! The fields of an attestation report in a form JS can display
+
+use serde::Serialize;
+use tsify::Tsify;
+
+use super::{Cpuid, SevProduct, SigningKey, SnpReport, Tcb, TcbKind};
+
+/// Byte fields are hex encoded, in the order they appear in the report
+#[derive(Tsify, Serialize, Debug, Clone)]
+#[tsify(into_wasm_abi)]
+#[serde(rename_all = "camelCase")]
+pub struct SevSnpReport {
+    pub product: SevProduct,
+    pub version: u32,
+    pub guest_svn: u32,
+    pub policy: GuestPolicyInfo,
+    pub family_id: String,
+    pub image_id: String,
+    pub vmpl: u32,
+    pub signature_algorithm: u32,
+    pub current_tcb: Tcb,
+    pub platform_info: PlatformInfo,
+    pub key_info: KeyInfo,
+    pub report_data: String,
+    pub measurement: String,
+    pub host_data: String,
+    pub id_key_digest: String,
+    pub author_key_digest: String,
+    pub report_id: String,
+    pub report_id_ma: String,
+    pub reported_tcb: Tcb,
+    /// Only in version 3 and later reports
+    #[tsify(optional)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cpuid: Option<Cpuid>,
+    /// All zero when the guest masks it
+    pub chip_id: String,
+    pub committed_tcb: Tcb,
+    /// `major.minor.build` of the running firmware
+    pub current_version: String,
+    pub committed_version: String,
+    pub launch_tcb: Tcb,
+    pub signature: SignatureInfo,
+}
+
+/// The GUEST_POLICY the guest was launched with
+#[derive(Tsify, Serialize, Debug, Clone, Copy)]
+#[serde(rename_all = "camelCase")]
+pub struct GuestPolicyInfo {
+    pub abi_major: u8,
+    pub abi_minor: u8,
+    pub smt_allowed: bool,
+    pub migrate_ma_allowed: bool,
+    pub debug_allowed: bool,
+    pub single_socket_required: bool,
+}
+
+#[derive(Tsify, Serialize, Debug, Clone, Copy)]
+#[serde(rename_all = "camelCase")]
+pub struct PlatformInfo {
+    pub smt_enabled: bool,
+    pub tsme_enabled: bool,
+}
+
+#[derive(Tsify, Serialize, Debug, Clone, Copy)]
+#[serde(rename_all = "camelCase")]
+pub struct KeyInfo {
+    pub author_key_enabled: bool,
+    pub mask_chip_key: bool,
+    pub signing_key: SigningKey,
+}
+
+/// ECDSA P-384 signature, R and S as the little-endian 72 byte fields of the report
+#[derive(Tsify, Serialize, Debug, Clone)]
+pub struct SignatureInfo {
+    pub r: String,
+    pub s: String,
+}
+
+impl SevSnpReport {
+    pub fn new(report: &SnpReport, product: SevProduct) -> Self {
+        let policy = report.policy;
+        let key_info = report.key_info();
+
+        Self {
+            product,
+            version: report.version,
+            guest_svn: report.guest_svn,
+            policy: GuestPolicyInfo {
+                abi_major: policy.abi_major() as u8,
+                abi_minor: policy.abi_minor() as u8,
+                smt_allowed: policy.smt_allowed() != 0,
+                migrate_ma_allowed: policy.migrate_ma_allowed() != 0,
+                debug_allowed: policy.debug_allowed() != 0,
+                single_socket_required: policy.single_socket_required() != 0,
+            },
+            family_id: hex::encode(report.family_id),
+            image_id: hex::encode(report.image_id),
+            vmpl: report.vmpl,
+            signature_algorithm: report.sig_algo,
+            current_tcb: report.tcb(TcbKind::Current, product),
+            platform_info: PlatformInfo {
+                smt_enabled: report.plat_info.smt_enabled() != 0,
+                tsme_enabled: report.plat_info.tsme_enabled() != 0,
+            },
+            key_info: KeyInfo {
+                author_key_enabled: key_info & 0x1 != 0,
+                mask_chip_key: key_info & 0x2 != 0,
+                signing_key: report.signing_key(),
+            },
+            report_data: hex::encode(report.report_data),
+            measurement: hex::encode(report.measurement),
+            host_data: hex::encode(report.host_data),
+            id_key_digest: hex::encode(report.id_key_digest),
+            author_key_digest: hex::encode(report.author_key_digest),
+            report_id: hex::encode(report.report_id),
+            report_id_ma: hex::encode(report.report_id_ma),
+            reported_tcb: report.tcb(TcbKind::Reported, product),
+            cpuid: report.cpuid(),
+            chip_id: hex::encode(report.chip_id),
+            committed_tcb: report.tcb(TcbKind::Committed, product),
+            current_version: format!(
+                "{}.{}.{}",
+                report.current_major, report.current_minor, report.current_build
+            ),
+            committed_version: format!(
+                "{}.{}.{}",
+                report.committed_major, report.committed_minor, report.committed_build
+            ),
+            launch_tcb: report.tcb(TcbKind::Launch, product),
+            signature: SignatureInfo {
+                r: hex::encode(report.signature.r()),
+                s: hex::encode(report.signature.s()),
+            },
+        }
+    }
+}
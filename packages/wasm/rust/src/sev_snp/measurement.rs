@@ -0,0 +1,573 @@
+//! Expected launch measurement of a guest, computed like sev-snp-measure
+//!
+//! The launch digest chains a SHA-384 over every page the host adds to the guest at launch:
+//! the OVMF image, the sections its SEV metadata declares (the kernel hashes table among them)
+//! and one VMSA per vCPU. Layouts: SEV-SNP ABI, "PAGE_INFO", and AMD APM Vol 2, Table B-4.
+
+use std::collections::BTreeMap;
+
+use serde::Deserialize;
+use sha2::{Digest, Sha256, Sha384};
+use tsify::Tsify;
+
+use crate::abi::JsResult;
+use crate::error::LitCryptoError;
+
+const PAGE_SIZE: usize = 4096;
+const FOUR_GB: u64 = 0x1_0000_0000;
+/// Where the VMSAs are measured, whatever vCPU they belong to
+const VMSA_GPA: u64 = 0xFFFF_FFFF_F000;
+/// Reset vector of the boot processor
+const BSP_EIP: u64 = 0xFFFF_FFF0;
+
+const PAGE_TYPE_NORMAL: u8 = 0x01;
+const PAGE_TYPE_VMSA: u8 = 0x02;
+const PAGE_TYPE_ZERO: u8 = 0x03;
+const PAGE_TYPE_SECRETS: u8 = 0x05;
+const PAGE_TYPE_CPUID: u8 = 0x06;
+
+const OVMF_TABLE_FOOTER_GUID: [u8; 16] = guid(0x96b582de, 0x1fb2, 0x45f7, 0xbaea_a366_c55a_082d);
+const SEV_HASH_TABLE_RV_GUID: [u8; 16] = guid(0x7255371f, 0x3a3b, 0x4b04, 0x927b_1da6_efa8_d454);
+const SEV_ES_RESET_BLOCK_GUID: [u8; 16] = guid(0x00f771de, 0x1a7e, 0x4fcb, 0x890e_68c7_7e2f_b44e);
+const OVMF_SEV_METADATA_GUID: [u8; 16] = guid(0xdc886566, 0x984a, 0x4798, 0xa75e_5585_a7bf_67cc);
+
+const SEV_HASH_TABLE_HEADER_GUID: [u8; 16] =
+    guid(0x9438d606, 0x4f22, 0x4cc9, 0xb479_a793_d411_fd21);
+const SEV_KERNEL_ENTRY_GUID: [u8; 16] = guid(0x4de79437, 0xabd2, 0x427f, 0xb835_d5b1_72d2_045b);
+const SEV_INITRD_ENTRY_GUID: [u8; 16] = guid(0x44baf731, 0x3a2f, 0x4bd7, 0x9af1_41e2_9169_781d);
+const SEV_CMDLINE_ENTRY_GUID: [u8; 16] = guid(0x97d02dd8, 0xbd20, 0x4c94, 0xaa78_e771_4d36_ab2a);
+
+/// The mixed-endian byte order EFI GUIDs are stored in
+const fn guid(a: u32, b: u16, c: u16, d: u64) -> [u8; 16] {
+    let (a, b, c, d) = (
+        a.to_le_bytes(),
+        b.to_le_bytes(),
+        c.to_le_bytes(),
+        d.to_be_bytes(),
+    );
+    [
+        a[0], a[1], a[2], a[3], b[0], b[1], c[0], c[1], d[0], d[1], d[2], d[3], d[4], d[5], d[6],
+        d[7],
+    ]
+}
+
+/// The `-cpu` model QEMU runs the guest with, which sets the CPUID signature in the VMSAs
+#[derive(Tsify, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VcpuType {
+    #[serde(rename = "EPYC")]
+    Epyc,
+    #[serde(rename = "EPYC-v1")]
+    EpycV1,
+    #[serde(rename = "EPYC-v2")]
+    EpycV2,
+    #[serde(rename = "EPYC-IBPB")]
+    EpycIbpb,
+    #[serde(rename = "EPYC-v3")]
+    EpycV3,
+    #[serde(rename = "EPYC-v4")]
+    EpycV4,
+    #[serde(rename = "EPYC-Rome")]
+    EpycRome,
+    #[serde(rename = "EPYC-Rome-v1")]
+    EpycRomeV1,
+    #[serde(rename = "EPYC-Rome-v2")]
+    EpycRomeV2,
+    #[serde(rename = "EPYC-Rome-v3")]
+    EpycRomeV3,
+    #[serde(rename = "EPYC-Milan")]
+    EpycMilan,
+    #[serde(rename = "EPYC-Milan-v1")]
+    EpycMilanV1,
+    #[serde(rename = "EPYC-Milan-v2")]
+    EpycMilanV2,
+    #[serde(rename = "EPYC-Genoa")]
+    EpycGenoa,
+    #[serde(rename = "EPYC-Genoa-v1")]
+    EpycGenoaV1,
+}
+
+impl VcpuType {
+    /// CPUID leaf 1 EAX of the model
+    pub fn signature(&self) -> u32 {
+        match self {
+            Self::Epyc
+            | Self::EpycV1
+            | Self::EpycV2
+            | Self::EpycIbpb
+            | Self::EpycV3
+            | Self::EpycV4 => cpu_signature(23, 1, 2),
+            Self::EpycRome | Self::EpycRomeV1 | Self::EpycRomeV2 | Self::EpycRomeV3 => {
+                cpu_signature(23, 49, 0)
+            }
+            Self::EpycMilan | Self::EpycMilanV1 | Self::EpycMilanV2 => cpu_signature(25, 1, 1),
+            Self::EpycGenoa | Self::EpycGenoaV1 => cpu_signature(25, 17, 0),
+        }
+    }
+}
+
+fn cpu_signature(family: u32, model: u32, stepping: u32) -> u32 {
+    let (family_low, family_high) = if family > 0xF {
+        (0xF, (family - 0xF) & 0xFF)
+    } else {
+        (family, 0)
+    };
+    (family_high << 20)
+        | ((model >> 4) & 0xF) << 16
+        | family_low << 8
+        | (model & 0xF) << 4
+        | (stepping & 0xF)
+}
+
+/// The hypervisor launching the guest, which sets the initial register state differently
+#[derive(Tsify, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum VmmType {
+    #[default]
+    Qemu,
+    /// AWS EC2, which measures the CPUID page last and leaves the FPU state zeroed
+    Ec2,
+}
+
+/// How the guest is launched. Hashes are hex encoded
+#[derive(Tsify, Deserialize, Debug, Clone)]
+#[tsify(from_wasm_abi)]
+#[serde(rename_all = "camelCase")]
+pub struct SevSnpMeasurementOptions {
+    pub vcpus: u32,
+    /// Required for QEMU guests unless `vcpuSig` is given
+    #[tsify(optional)]
+    pub vcpu_type: Option<VcpuType>,
+    /// CPUID signature of the vCPUs, in place of `vcpuType`
+    #[tsify(optional)]
+    pub vcpu_sig: Option<u32>,
+    /// SEV_FEATURES of the VMSAs, `1` (SNPActive) when not given
+    #[tsify(optional)]
+    pub guest_features: Option<u64>,
+    #[tsify(optional)]
+    pub vmm_type: Option<VmmType>,
+    /// Output of `sevSnpOvmfHash` for the firmware, skips hashing it again
+    #[tsify(optional)]
+    pub ovmf_hash: Option<String>,
+    /// SHA-256 of the kernel for direct boot, which measures the kernel, initrd and command line hashes
+    #[tsify(optional)]
+    pub kernel_hash: Option<String>,
+    /// SHA-256 of the initrd, of no initrd when not given
+    #[tsify(optional)]
+    pub initrd_hash: Option<String>,
+    /// Kernel command line as passed to `-append`
+    #[tsify(optional)]
+    pub cmdline: Option<String>,
+    /// SHA-256 of the NUL terminated command line, in place of `cmdline`
+    #[tsify(optional)]
+    pub cmdline_hash: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SectionType {
+    SnpSecMemory,
+    SnpSecrets,
+    Cpuid,
+    SvsmCaa,
+    SnpKernelHashes,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Section {
+    gpa: u64,
+    size: usize,
+    section_type: SectionType,
+}
+
+fn invalid(reason: impl std::fmt::Display) -> LitCryptoError {
+    LitCryptoError::Measurement(reason.to_string())
+}
+
+/// The OVMF image with its footer table of GUIDed entries and its SEV metadata sections
+struct Ovmf<'a> {
+    data: &'a [u8],
+    table: BTreeMap<[u8; 16], &'a [u8]>,
+    sections: Vec<Section>,
+}
+
+impl<'a> Ovmf<'a> {
+    /// Bytes after the footer table entry, up to the reset vector
+    const FOOTER_OFFSET: usize = 32;
+    /// u16 size and GUID of a table entry, which follow its data
+    const ENTRY_HEADER_SIZE: usize = 18;
+    const SECTION_SIZE: usize = 12;
+
+    fn parse(data: &'a [u8]) -> JsResult<Self> {
+        if data.is_empty() || data.len() % PAGE_SIZE != 0 || data.len() as u64 > FOUR_GB {
+            return Err(invalid(format!(
+                "OVMF is {} bytes, not a multiple of pages",
+                data.len()
+            )));
+        }
+        let mut ovmf = Self {
+            data,
+            table: BTreeMap::new(),
+            sections: Vec::new(),
+        };
+        ovmf.parse_footer_table()?;
+        ovmf.parse_sev_metadata()?;
+        Ok(ovmf)
+    }
+
+    fn gpa(&self) -> u64 {
+        FOUR_GB - self.data.len() as u64
+    }
+
+    fn parse_footer_table(&mut self) -> JsResult<()> {
+        let footer_start = self.data.len() - Self::FOOTER_OFFSET - Self::ENTRY_HEADER_SIZE;
+        let (size, guid) = entry_header(&self.data[footer_start..])?;
+        if guid != OVMF_TABLE_FOOTER_GUID {
+            return Err(invalid("OVMF has no footer table"));
+        }
+        let table_size = size
+            .checked_sub(Self::ENTRY_HEADER_SIZE)
+            .filter(|size| *size <= footer_start)
+            .ok_or_else(|| invalid(format!("invalid OVMF footer table size {size}")))?;
+
+        // Entries are read backwards, each one ends with its size and GUID
+        let mut table = &self.data[footer_start - table_size..footer_start];
+        while table.len() >= Self::ENTRY_HEADER_SIZE {
+            let (size, guid) = entry_header(&table[table.len() - Self::ENTRY_HEADER_SIZE..])?;
+            if size < Self::ENTRY_HEADER_SIZE || size > table.len() {
+                return Err(invalid(format!("invalid OVMF table entry size {size}")));
+            }
+            let start = table.len() - size;
+            self.table
+                .insert(guid, &table[start..table.len() - Self::ENTRY_HEADER_SIZE]);
+            table = &table[..start];
+        }
+        Ok(())
+    }
+
+    fn parse_sev_metadata(&mut self) -> JsResult<()> {
+        let offset_from_end = self.table_u32(&OVMF_SEV_METADATA_GUID, "SEV metadata")? as usize;
+        let start = self
+            .data
+            .len()
+            .checked_sub(offset_from_end)
+            .ok_or_else(|| invalid("SEV metadata is out of the OVMF image"))?;
+        let header = self
+            .data
+            .get(start..start + 16)
+            .ok_or_else(|| invalid("SEV metadata is out of the OVMF image"))?;
+        if &header[..4] != b"ASEV" {
+            return Err(invalid("invalid SEV metadata signature"));
+        }
+        let version = read_u32(&header[8..]);
+        if version != 1 {
+            return Err(invalid(format!(
+                "unsupported SEV metadata version {version}"
+            )));
+        }
+        let count = read_u32(&header[12..]) as usize;
+
+        let sections = count
+            .checked_mul(Self::SECTION_SIZE)
+            .and_then(|len| self.data.get(start + 16..start + 16 + len))
+            .ok_or_else(|| invalid("SEV metadata sections are out of the OVMF image"))?;
+        for section in sections.chunks_exact(Self::SECTION_SIZE) {
+            let section_type = match read_u32(&section[8..]) {
+                1 => SectionType::SnpSecMemory,
+                2 => SectionType::SnpSecrets,
+                3 => SectionType::Cpuid,
+                4 => SectionType::SvsmCaa,
+                0x10 => SectionType::SnpKernelHashes,
+                other => {
+                    return Err(invalid(format!(
+                        "unknown SEV metadata section type {other}"
+                    )))
+                }
+            };
+            self.sections.push(Section {
+                gpa: read_u32(section).into(),
+                size: read_u32(&section[4..]) as usize,
+                section_type,
+            });
+        }
+        Ok(())
+    }
+
+    fn table_u32(&self, guid: &[u8; 16], name: &str) -> JsResult<u32> {
+        self.table
+            .get(guid)
+            .filter(|entry| entry.len() >= 4)
+            .map(|entry| read_u32(entry))
+            .ok_or_else(|| invalid(format!("OVMF has no {name} entry")))
+    }
+
+    fn has_section(&self, section_type: SectionType) -> bool {
+        self.sections
+            .iter()
+            .any(|section| section.section_type == section_type)
+    }
+}
+
+fn entry_header(bytes: &[u8]) -> JsResult<(usize, [u8; 16])> {
+    let header = bytes
+        .get(..Ovmf::ENTRY_HEADER_SIZE)
+        .ok_or_else(|| invalid("truncated OVMF table entry"))?;
+    let mut guid = [0u8; 16];
+    guid.copy_from_slice(&header[2..]);
+    Ok((u16::from_le_bytes([header[0], header[1]]) as usize, guid))
+}
+
+fn read_u32(bytes: &[u8]) -> u32 {
+    u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]])
+}
+
+/// The launch digest as the PSP updates it with each page
+struct Gctx {
+    ld: [u8; 48],
+}
+
+impl Gctx {
+    fn update(&mut self, page_type: u8, gpa: u64, contents: &[u8; 48]) {
+        const PAGE_INFO_LEN: u16 = 0x70;
+
+        let mut hasher = Sha384::new();
+        hasher.update(self.ld);
+        hasher.update(contents);
+        hasher.update(PAGE_INFO_LEN.to_le_bytes());
+        hasher.update([page_type]);
+        // IMI_PAGE, VMPL3, VMPL2 and VMPL1 permissions, reserved
+        hasher.update([0u8; 5]);
+        hasher.update(gpa.to_le_bytes());
+        self.ld = hasher.finalize().into();
+    }
+
+    fn update_normal_pages(&mut self, gpa: u64, data: &[u8]) {
+        for (i, page) in data.chunks(PAGE_SIZE).enumerate() {
+            let gpa = gpa + (i * PAGE_SIZE) as u64;
+            self.update(PAGE_TYPE_NORMAL, gpa, &Sha384::digest(page).into());
+        }
+    }
+
+    fn update_zero_pages(&mut self, gpa: u64, size: usize) -> JsResult<()> {
+        if size % PAGE_SIZE != 0 {
+            return Err(invalid(format!(
+                "section at {gpa:#x} is {size} bytes, not a multiple of pages"
+            )));
+        }
+        for offset in (0..size).step_by(PAGE_SIZE) {
+            self.update(PAGE_TYPE_ZERO, gpa + offset as u64, &[0; 48]);
+        }
+        Ok(())
+    }
+
+    fn update_vmsa_page(&mut self, page: &[u8]) {
+        self.update(PAGE_TYPE_VMSA, VMSA_GPA, &Sha384::digest(page).into());
+    }
+}
+
+/// SHA-256 of the kernel, initrd and command line that OVMF checks before booting them
+struct SevHashes {
+    kernel: [u8; 32],
+    initrd: [u8; 32],
+    cmdline: [u8; 32],
+}
+
+impl SevHashes {
+    fn from_options(options: &SevSnpMeasurementOptions) -> JsResult<Option<Self>> {
+        let Some(kernel) = &options.kernel_hash else {
+            return Ok(None);
+        };
+
+        let initrd = match &options.initrd_hash {
+            Some(initrd) => parse_hash(initrd, "initrdHash")?,
+            None => Sha256::digest([]).into(),
+        };
+        let cmdline = match (&options.cmdline_hash, &options.cmdline) {
+            (Some(cmdline), _) => parse_hash(cmdline, "cmdlineHash")?,
+            (None, cmdline) => {
+                let mut hasher = Sha256::new();
+                hasher.update(cmdline.as_deref().unwrap_or_default());
+                hasher.update([0]);
+                hasher.finalize().into()
+            }
+        };
+
+        Ok(Some(Self {
+            kernel: parse_hash(kernel, "kernelHash")?,
+            initrd,
+            cmdline,
+        }))
+    }
+
+    /// The page holding the padded hash table at the offset OVMF reads it from
+    fn page(&self, offset: usize) -> Vec<u8> {
+        const ENTRY_LEN: u16 = 16 + 2 + 32;
+        const TABLE_LEN: u16 = 16 + 2 + 3 * ENTRY_LEN;
+
+        let mut page = vec![0u8; offset];
+        page.extend_from_slice(&SEV_HASH_TABLE_HEADER_GUID);
+        page.extend_from_slice(&TABLE_LEN.to_le_bytes());
+        for (guid, hash) in [
+            (SEV_CMDLINE_ENTRY_GUID, &self.cmdline),
+            (SEV_INITRD_ENTRY_GUID, &self.initrd),
+            (SEV_KERNEL_ENTRY_GUID, &self.kernel),
+        ] {
+            page.extend_from_slice(&guid);
+            page.extend_from_slice(&ENTRY_LEN.to_le_bytes());
+            page.extend_from_slice(hash);
+        }
+        // The rest of the page, the table padding to 16 bytes included, is zero
+        page.resize(PAGE_SIZE, 0);
+        page
+    }
+}
+
+fn parse_hash<const N: usize>(hash: &str, field: &str) -> JsResult<[u8; N]> {
+    hex::decode(hash.trim_start_matches("0x"))
+        .ok()
+        .and_then(|bytes| bytes.try_into().ok())
+        .ok_or_else(|| invalid(format!("{field} is not {N} hex encoded bytes")))
+}
+
+/// Initial register state of a vCPU, written into a zeroed page at the VMSA offsets
+fn vmsa_page(eip: u64, sev_features: u64, vcpu_sig: u32, vmm_type: VmmType) -> Vec<u8> {
+    let mut page = vec![0u8; PAGE_SIZE];
+    let mut put = |offset: usize, bytes: &[u8]| {
+        page[offset..offset + bytes.len()].copy_from_slice(bytes);
+    };
+    let mut segment = |offset: usize, selector: u16, attrib: u16, base: u64| {
+        put(offset, &selector.to_le_bytes());
+        put(offset + 2, &attrib.to_le_bytes());
+        put(offset + 4, &0xFFFFu32.to_le_bytes());
+        put(offset + 8, &base.to_le_bytes());
+    };
+
+    let (cs_flags, ss_flags, tr_flags, rdx, mxcsr, fcw) = match vmm_type {
+        VmmType::Qemu => (0x9B, 0x93, 0x8B, vcpu_sig as u64, 0x1F80u32, 0x37Fu16),
+        VmmType::Ec2 if eip == BSP_EIP => (0x9A, 0x92, 0x83, 0, 0, 0),
+        VmmType::Ec2 => (0x9B, 0x92, 0x83, 0, 0, 0),
+    };
+
+    segment(0x00, 0, 0x93, 0); // es
+    segment(0x10, 0xF000, cs_flags, eip & 0xFFFF_0000); // cs
+    segment(0x20, 0, ss_flags, 0); // ss
+    segment(0x30, 0, 0x93, 0); // ds
+    segment(0x40, 0, 0x93, 0); // fs
+    segment(0x50, 0, 0x93, 0); // gs
+    segment(0x60, 0, 0, 0); // gdtr
+    segment(0x70, 0, 0x82, 0); // ldtr
+    segment(0x80, 0, 0, 0); // idtr
+    segment(0x90, 0, tr_flags, 0); // tr
+
+    for (offset, value) in [
+        (0x0D0, 0x1000),                // efer, SVME
+        (0x148, 0x40),                  // cr4, MCE
+        (0x158, 0x10),                  // cr0
+        (0x160, 0x400),                 // dr7
+        (0x168, 0xFFFF_0FF0),           // dr6
+        (0x170, 0x2),                   // rflags
+        (0x178, eip & 0xFFFF),          // rip
+        (0x268, 0x0007_0406_0007_0406), // g_pat
+        (0x310, rdx),
+        (0x3B0, sev_features),
+        (0x3E8, 0x1), // xcr0
+    ] {
+        put(offset, &u64::to_le_bytes(value));
+    }
+    put(0x408, &mxcsr.to_le_bytes());
+    put(0x410, &fcw.to_le_bytes());
+
+    page
+}
+
+/// SHA-384 launch digest of the OVMF image alone, the seed the metadata pages and VMSAs are added to
+pub fn ovmf_hash(ovmf: &[u8]) -> JsResult<[u8; 48]> {
+    let ovmf = Ovmf::parse(ovmf)?;
+    let mut gctx = Gctx { ld: [0; 48] };
+    gctx.update_normal_pages(ovmf.gpa(), ovmf.data);
+    Ok(gctx.ld)
+}
+
+/// The measurement of the attestation reports of a guest launched with the given firmware and options
+pub fn launch_digest(ovmf: &[u8], options: &SevSnpMeasurementOptions) -> JsResult<[u8; 48]> {
+    let vmm_type = options.vmm_type.unwrap_or_default();
+    let vcpu_sig = match (options.vcpu_sig, options.vcpu_type, vmm_type) {
+        (Some(sig), _, _) => sig,
+        (None, Some(vcpu_type), _) => vcpu_type.signature(),
+        // EC2 doesn't pass the signature in the VMSA
+        (None, None, VmmType::Ec2) => 0,
+        (None, None, VmmType::Qemu) => {
+            return Err(invalid("QEMU guests need a vcpuType or vcpuSig"))
+        }
+    };
+    if options.vcpus == 0 {
+        return Err(invalid("a guest has at least one vCPU"));
+    }
+
+    let ovmf = Ovmf::parse(ovmf)?;
+    let mut gctx = match &options.ovmf_hash {
+        Some(ovmf_hash) => Gctx {
+            ld: parse_hash(ovmf_hash, "ovmfHash")?,
+        },
+        None => {
+            let mut gctx = Gctx { ld: [0; 48] };
+            gctx.update_normal_pages(ovmf.gpa(), ovmf.data);
+            gctx
+        }
+    };
+
+    let sev_hashes = SevHashes::from_options(options)?;
+    if sev_hashes.is_some() && !ovmf.has_section(SectionType::SnpKernelHashes) {
+        return Err(invalid(
+            "the OVMF has no kernel hashes section, it can't boot a kernel directly",
+        ));
+    }
+
+    for section in &ovmf.sections {
+        match section.section_type {
+            SectionType::SnpSecMemory | SectionType::SvsmCaa => {
+                gctx.update_zero_pages(section.gpa, section.size)?
+            }
+            SectionType::SnpSecrets => gctx.update(PAGE_TYPE_SECRETS, section.gpa, &[0; 48]),
+            SectionType::Cpuid if vmm_type != VmmType::Ec2 => {
+                gctx.update(PAGE_TYPE_CPUID, section.gpa, &[0; 48])
+            }
+            SectionType::Cpuid => {}
+            SectionType::SnpKernelHashes => match &sev_hashes {
+                Some(sev_hashes) => {
+                    if section.size != PAGE_SIZE {
+                        return Err(invalid(format!(
+                            "the kernel hashes section is {} bytes, not a page",
+                            section.size
+                        )));
+                    }
+                    let table_gpa = ovmf.table_u32(&SEV_HASH_TABLE_RV_GUID, "SEV hash table")?;
+                    let page = sev_hashes.page(table_gpa as usize & (PAGE_SIZE - 1));
+                    gctx.update_normal_pages(section.gpa, &page);
+                }
+                None => gctx.update_zero_pages(section.gpa, section.size)?,
+            },
+        }
+    }
+    if vmm_type == VmmType::Ec2 {
+        for section in &ovmf.sections {
+            if section.section_type == SectionType::Cpuid {
+                gctx.update(PAGE_TYPE_CPUID, section.gpa, &[0; 48]);
+            }
+        }
+    }
+
+    let sev_features = options.guest_features.unwrap_or(1);
+    let ap_eip = ovmf.table_u32(&SEV_ES_RESET_BLOCK_GUID, "SEV-ES reset block")?;
+    // without a reset vector the APs can't be measured, no guest would launch with that digest
+    if ap_eip == 0 && options.vcpus > 1 {
+        return Err(invalid(
+            "the SEV-ES reset block has no AP reset vector for the other vCPUs",
+        ));
+    }
+    let bsp = vmsa_page(BSP_EIP, sev_features, vcpu_sig, vmm_type);
+    gctx.update_vmsa_page(&bsp);
+    let ap = vmsa_page(ap_eip.into(), sev_features, vcpu_sig, vmm_type);
+    for _ in 1..options.vcpus {
+        gctx.update_vmsa_page(&ap);
+    }
+
+    Ok(gctx.ld)
+}
@@ -0,0 +1,426 @@
+//! Throwaway AMD certificate chains and attestation reports signed by them, for tests only.
+//!
+//! Nothing here is trusted by default: verification only accepts the reports when given
+//! the mock ARK in `SevSnpVerifyOptions::ark`.
+
+use js_sys::Uint8Array;
+use p384::ecdsa::signature::DigestSigner;
+use p384::pkcs8::EncodePublicKey as _;
+use rsa::signature::{Keypair, RandomizedSigner, SignatureEncoding};
+use rsa::RsaPrivateKey;
+use serde::Deserialize;
+use sha2::{Digest, Sha384};
+use tsify::Tsify;
+use wasm_bindgen::prelude::*;
+use x509_cert::crl::{CertificateList, RevokedCert, TbsCertList};
+use x509_cert::der::asn1::BitString;
+use x509_cert::der::Decode;
+use x509_cert::name::Name;
+use x509_cert::serial_number::SerialNumber;
+use x509_cert::spki::{DynSignatureAlgorithmIdentifier, SubjectPublicKeyInfoOwned};
+use x509_cert::time::Validity;
+use x509_cert::{Certificate, TbsCertificate, Version};
+
+use crate::abi::{into_uint8array, JsResult};
+use crate::error::LitCryptoError;
+use crate::x509::mock::{ca_extension, encode, hex_field, name, time, validity};
+
+use super::report::{
+    KEY_INFO_OFFSET, REPORT_SIZE, SIGNATURE_R, SIGNATURE_S, SIGNED_LEN, SIG_ALGO_ECDSA_P384_SHA384,
+};
+use super::{SevProduct, SigningKey, Tcb, TcbKind};
+
+/// Far smaller than the 4096 bit keys of AMD, the chain is thrown away and must be quick to create
+const RSA_BITS: usize = 1024;
+const DAY: u64 = 24 * 60 * 60;
+
+/// GUEST_POLICY bit that must be one
+const POLICY_RESERVED: u64 = 1 << 17;
+const POLICY_MIGRATE_MA: u64 = 1 << 18;
+const POLICY_DEBUG: u64 = 1 << 19;
+const POLICY_SINGLE_SOCKET: u64 = 1 << 20;
+
+#[derive(Tsify, Deserialize, Debug, Clone, Default)]
+#[tsify(from_wasm_abi)]
+#[serde(rename_all = "camelCase", default)]
+pub struct SevSnpMockChainOptions {
+    /// Milan when not given
+    #[tsify(optional)]
+    pub product: Option<SevProduct>,
+    /// Vcek when not given, a Vlek chain has an ASVK in place of the ASK
+    #[tsify(optional)]
+    pub signing_key: Option<SigningKey>,
+    /// Start of the validity period of the VCEK in unix seconds, a day ago when not given.
+    /// The ARK and ASK are valid from a day ago for ten years
+    #[tsify(optional)]
+    pub not_before: Option<u64>,
+    /// End of the validity period of the VCEK in unix seconds, in a year when not given
+    #[tsify(optional)]
+    pub not_after: Option<u64>,
+}
+
+/// Fields of a mock report, the rest are zero. Byte fields are hex encoded
+#[derive(Tsify, Deserialize, Debug, Clone, Default)]
+#[tsify(from_wasm_abi)]
+#[serde(rename_all = "camelCase", default)]
+pub struct SevSnpMockReport {
+    /// 3 when not given, version 2 reports have no CPUID to detect the product from
+    #[tsify(optional)]
+    pub version: Option<u32>,
+    pub guest_svn: u32,
+    pub debug: bool,
+    pub migrate_ma: bool,
+    pub single_socket: bool,
+    pub vmpl: u32,
+    #[tsify(optional, type = "Uint8Array")]
+    #[serde(with = "serde_bytes")]
+    pub report_data: Option<Vec<u8>>,
+    #[tsify(optional)]
+    pub measurement: Option<String>,
+    #[tsify(optional)]
+    pub host_data: Option<String>,
+    #[tsify(optional)]
+    pub id_key_digest: Option<String>,
+    /// Also sets AUTHOR_KEY_EN
+    #[tsify(optional)]
+    pub author_key_digest: Option<String>,
+    /// Used for the current, reported, committed and launch TCB
+    #[tsify(optional)]
+    pub tcb: Option<Tcb>,
+    /// A fixed non-zero id when not given
+    #[tsify(optional)]
+    pub chip_id: Option<String>,
+}
+
+#[derive(Tsify, Deserialize, Debug, Clone, Default)]
+#[tsify(from_wasm_abi)]
+#[serde(rename_all = "camelCase", default)]
+pub struct SevSnpMockCrlOptions {
+    /// Revokes the ASK, or the ASVK of a Vlek chain
+    pub revoke_ask: bool,
+    /// Revokes the VCEK, or the VLEK of a Vlek chain
+    pub revoke_vcek: bool,
+    /// Unix seconds, an hour ago when not given
+    #[tsify(optional)]
+    pub this_update: Option<u64>,
+    /// Unix seconds, in a day when not given
+    #[tsify(optional)]
+    pub next_update: Option<u64>,
+}
+
+/// A throwaway ARK, ASK and VCEK, and the keys to sign reports and CRLs with
+#[wasm_bindgen(js_name = "SevSnpMockChain")]
+pub struct MockChain {
+    product: SevProduct,
+    signing_key: SigningKey,
+    ark_key: rsa::pss::SigningKey<Sha384>,
+    vcek_key: p384::ecdsa::SigningKey,
+    ark: Certificate,
+    ask: Certificate,
+    vcek: Certificate,
+}
+
+#[wasm_bindgen(js_class = "SevSnpMockChain")]
+impl MockChain {
+    #[wasm_bindgen(constructor)]
+    pub fn new(options: Option<SevSnpMockChainOptions>) -> JsResult<MockChain> {
+        let options = options.unwrap_or_default();
+        let product = options.product.unwrap_or(SevProduct::Milan);
+        let signing_key = options.signing_key.unwrap_or(SigningKey::Vcek);
+        if signing_key == SigningKey::None {
+            return Err(LitCryptoError::AttestationChain(
+                "unsigned reports have no certificates".to_string(),
+            ));
+        }
+        let now = (js_sys::Date::now() / 1000.0) as u64;
+
+        let mut rng = rand::thread_rng();
+        let ark_key = rsa_key(&mut rng)?;
+        let ask_key = rsa_key(&mut rng)?;
+        let vcek_key = p384::ecdsa::SigningKey::random(&mut rng);
+
+        let ark_name = name(&format!("CN=ARK-{},O=Lit Protocol Test", product.name()))?;
+        let ask_name = match signing_key {
+            SigningKey::Vlek => name(&format!(
+                "CN=SEV-VLEK-{},O=Lit Protocol Test",
+                product.name()
+            ))?,
+            _ => name(&format!("CN=SEV-{},O=Lit Protocol Test", product.name()))?,
+        };
+        let vcek_name = name(&format!(
+            "CN=SEV-{},O=Lit Protocol Test",
+            signing_key.name()
+        ))?;
+
+        let ca_validity = validity(now - DAY, now + 10 * 365 * DAY)?;
+        let ark = sign_certificate(
+            &ark_key,
+            1,
+            &ark_name,
+            &ark_name,
+            ca_validity,
+            rsa_spki(&ark_key)?,
+            true,
+        )?;
+        let ask = sign_certificate(
+            &ark_key,
+            2,
+            &ark_name,
+            &ask_name,
+            ca_validity,
+            rsa_spki(&ask_key)?,
+            true,
+        )?;
+        let vcek_spki = vcek_key
+            .verifying_key()
+            .to_public_key_der()
+            .map_err(|e| LitCryptoError::Serialize(e.to_string()))?;
+        let vcek = sign_certificate(
+            &ask_key,
+            3,
+            &ask_name,
+            &vcek_name,
+            validity(
+                options.not_before.unwrap_or(now - DAY),
+                options.not_after.unwrap_or(now + 365 * DAY),
+            )?,
+            SubjectPublicKeyInfoOwned::from_der(vcek_spki.as_bytes())
+                .map_err(|e| LitCryptoError::Deserialize(e.to_string()))?,
+            false,
+        )?;
+
+        Ok(Self {
+            product,
+            signing_key,
+            ark_key,
+            vcek_key,
+            ark,
+            ask,
+            vcek,
+        })
+    }
+
+    /// The ARK (DER), pass it as `ark` in the verify options
+    pub fn ark(&self) -> JsResult<Uint8Array> {
+        to_der(&self.ark)
+    }
+
+    /// The ASK (DER), or the ASVK of a Vlek chain
+    pub fn ask(&self) -> JsResult<Uint8Array> {
+        to_der(&self.ask)
+    }
+
+    /// The VCEK (DER), or the VLEK of a Vlek chain
+    pub fn vcek(&self) -> JsResult<Uint8Array> {
+        to_der(&self.vcek)
+    }
+
+    /// Creates an attestation report with the given fields, signed by the VCEK
+    pub fn report(&self, fields: Option<SevSnpMockReport>) -> JsResult<Uint8Array> {
+        into_uint8array(self.sign_report(&fields.unwrap_or_default())?)
+    }
+
+    /// Creates a CRL signed by the ARK
+    pub fn crl(&self, options: Option<SevSnpMockCrlOptions>) -> JsResult<Uint8Array> {
+        let options = options.unwrap_or_default();
+        let now = (js_sys::Date::now() / 1000.0) as u64;
+
+        let revoked = [
+            (options.revoke_ask, &self.ask),
+            (options.revoke_vcek, &self.vcek),
+        ]
+        .into_iter()
+        .filter(|(revoke, _)| *revoke)
+        .map(|(_, cert)| {
+            Ok(RevokedCert {
+                serial_number: cert.tbs_certificate.serial_number.clone(),
+                revocation_date: time(now - DAY)?,
+                crl_entry_extensions: None,
+            })
+        })
+        .collect::<JsResult<Vec<_>>>()?;
+
+        let tbs_cert_list = TbsCertList {
+            version: Version::V2,
+            signature: algorithm(&self.ark_key)?,
+            issuer: self.ark.tbs_certificate.subject.clone(),
+            this_update: time(options.this_update.unwrap_or(now - 60 * 60))?,
+            next_update: Some(time(options.next_update.unwrap_or(now + DAY))?),
+            revoked_certificates: (!revoked.is_empty()).then_some(revoked),
+            crl_extensions: None,
+        };
+        let signature = rsa_sign(&self.ark_key, &encode(&tbs_cert_list)?)?;
+        let crl = CertificateList {
+            signature_algorithm: tbs_cert_list.signature.clone(),
+            tbs_cert_list,
+            signature,
+        };
+        into_uint8array(encode(&crl)?)
+    }
+}
+
+impl MockChain {
+    fn sign_report(&self, fields: &SevSnpMockReport) -> JsResult<Vec<u8>> {
+        let product = self.product;
+        let version = fields.version.unwrap_or(3);
+        let mut raw = vec![0u8; REPORT_SIZE];
+
+        raw[0x00..0x04].copy_from_slice(&version.to_le_bytes());
+        raw[0x04..0x08].copy_from_slice(&fields.guest_svn.to_le_bytes());
+
+        let mut policy = POLICY_RESERVED;
+        for (set, bit) in [
+            (fields.migrate_ma, POLICY_MIGRATE_MA),
+            (fields.debug, POLICY_DEBUG),
+            (fields.single_socket, POLICY_SINGLE_SOCKET),
+        ] {
+            if set {
+                policy |= bit;
+            }
+        }
+        raw[0x08..0x10].copy_from_slice(&policy.to_le_bytes());
+        raw[0x30..0x34].copy_from_slice(&fields.vmpl.to_le_bytes());
+        raw[0x34..0x38].copy_from_slice(&SIG_ALGO_ECDSA_P384_SHA384.to_le_bytes());
+
+        let author_key_en = u32::from(fields.author_key_digest.is_some());
+        let key_info = self.signing_key.to_key_info() | author_key_en;
+        raw[KEY_INFO_OFFSET..KEY_INFO_OFFSET + 4].copy_from_slice(&key_info.to_le_bytes());
+
+        if let Some(report_data) = &fields.report_data {
+            if report_data.len() > 64 {
+                return Err(LitCryptoError::Deserialize(format!(
+                    "reportData has {} bytes, at most 64 fit",
+                    report_data.len()
+                )));
+            }
+            raw[0x50..0x50 + report_data.len()].copy_from_slice(report_data);
+        }
+        for (field, value, offset, len) in [
+            ("measurement", &fields.measurement, 0x90, 48),
+            ("hostData", &fields.host_data, 0xC0, 32),
+            ("idKeyDigest", &fields.id_key_digest, 0xE0, 48),
+            ("authorKeyDigest", &fields.author_key_digest, 0x110, 48),
+            ("chipId", &fields.chip_id, 0x1A0, 64),
+        ] {
+            if let Some(value) = value {
+                raw[offset..offset + len].copy_from_slice(&hex_field(field, value, len)?);
+            }
+        }
+        if fields.chip_id.is_none() {
+            // Turin chip ids only have 8 bytes, which is how version 2 reports tell it apart
+            let len = match product {
+                SevProduct::Turin => 8,
+                SevProduct::Milan | SevProduct::Genoa => 64,
+            };
+            raw[0x1A0..0x1A0 + len].fill(0x5A);
+        }
+
+        let tcb = fields.tcb.unwrap_or(Tcb {
+            fmc: (product == SevProduct::Turin).then_some(1),
+            bootloader: 9,
+            tee: 0,
+            snp: 23,
+            microcode: 72,
+        });
+        for kind in [
+            TcbKind::Current,
+            TcbKind::Reported,
+            TcbKind::Committed,
+            TcbKind::Launch,
+        ] {
+            raw[kind.offset()..kind.offset() + 8].copy_from_slice(&tcb.to_bytes(product));
+        }
+
+        if version >= 3 {
+            let (family, model) = match product {
+                SevProduct::Milan => (0x19, 0x01),
+                SevProduct::Genoa => (0x19, 0x11),
+                SevProduct::Turin => (0x1A, 0x02),
+            };
+            raw[0x188..0x18B].copy_from_slice(&[family, model, 1]);
+        }
+
+        let signature: p384::ecdsa::Signature = self
+            .vcek_key
+            .sign_digest(Sha384::new_with_prefix(&raw[..SIGNED_LEN]));
+        for (offset, scalar) in [(SIGNATURE_R, signature.r()), (SIGNATURE_S, signature.s())] {
+            let mut bytes = scalar.to_bytes();
+            bytes.reverse();
+            raw[offset..offset + bytes.len()].copy_from_slice(&bytes);
+        }
+
+        Ok(raw)
+    }
+}
+
+fn rsa_key(
+    rng: &mut (impl rand::CryptoRng + rand::RngCore),
+) -> JsResult<rsa::pss::SigningKey<Sha384>> {
+    let key =
+        RsaPrivateKey::new(rng, RSA_BITS).map_err(|e| LitCryptoError::Serialize(e.to_string()))?;
+    Ok(rsa::pss::SigningKey::new(key))
+}
+
+fn rsa_spki(key: &rsa::pss::SigningKey<Sha384>) -> JsResult<SubjectPublicKeyInfoOwned> {
+    let der = key
+        .verifying_key()
+        .as_ref()
+        .to_public_key_der()
+        .map_err(|e| LitCryptoError::Serialize(e.to_string()))?;
+    SubjectPublicKeyInfoOwned::from_der(der.as_bytes())
+        .map_err(|e| LitCryptoError::Deserialize(e.to_string()))
+}
+
+fn algorithm(
+    key: &rsa::pss::SigningKey<Sha384>,
+) -> JsResult<x509_cert::spki::AlgorithmIdentifierOwned> {
+    key.signature_algorithm_identifier()
+        .map_err(|e| LitCryptoError::Serialize(e.to_string()))
+}
+
+fn rsa_sign(key: &rsa::pss::SigningKey<Sha384>, message: &[u8]) -> JsResult<BitString> {
+    let signature = key
+        .try_sign_with_rng(&mut rand::thread_rng(), message)
+        .map_err(|e| LitCryptoError::Serialize(e.to_string()))?;
+    BitString::from_bytes(&signature.to_bytes())
+        .map_err(|e| LitCryptoError::Serialize(e.to_string()))
+}
+
+fn sign_certificate(
+    issuer_key: &rsa::pss::SigningKey<Sha384>,
+    serial: u8,
+    issuer: &Name,
+    subject: &Name,
+    validity: Validity,
+    subject_public_key_info: SubjectPublicKeyInfoOwned,
+    ca: bool,
+) -> JsResult<Certificate> {
+    let extensions = ca
+        .then(|| ca_extension().map(|extension| vec![extension]))
+        .transpose()?;
+
+    let tbs_certificate = TbsCertificate {
+        version: Version::V3,
+        serial_number: SerialNumber::new(&[serial])
+            .map_err(|e| LitCryptoError::Serialize(e.to_string()))?,
+        signature: algorithm(issuer_key)?,
+        issuer: issuer.clone(),
+        validity,
+        subject: subject.clone(),
+        subject_public_key_info,
+        issuer_unique_id: None,
+        subject_unique_id: None,
+        extensions,
+    };
+    let signature = rsa_sign(issuer_key, &encode(&tbs_certificate)?)?;
+
+    Ok(Certificate {
+        signature_algorithm: tbs_certificate.signature.clone(),
+        tbs_certificate,
+        signature,
+    })
+}
+
+fn to_der(cert: &Certificate) -> JsResult<Uint8Array> {
+    into_uint8array(encode(cert)?)
+}
@@ -0,0 +1,446 @@
+mod bundle;
+mod certs;
+mod info;
+mod measurement;
+#[cfg(feature = "sev-snp-mock")]
+mod mock;
+mod node;
+mod node_set;
+mod options;
+mod policy;
+mod product;
+mod report;
+
+use std::collections::BTreeMap;
+
+use js_sys::Uint8Array;
+use wasm_bindgen::prelude::*;
+
+use sev::certs::snp::{ca, Chain, Verifiable};
+
+use crate::abi::{from_js, into_js, into_uint8array, JsResult};
+use crate::error::LitCryptoError;
+
+use crate::x509::{check_validity, Crl};
+use bundle::{parse_certificate, CertBundle};
+use certs::{to_x509, verify_crl};
+
+pub use crate::binding::ReportDataVersion;
+pub use info::{GuestPolicyInfo, KeyInfo, PlatformInfo, SevSnpReport, SignatureInfo};
+pub use measurement::{SevSnpMeasurementOptions, VcpuType, VmmType};
+#[cfg(feature = "sev-snp-mock")]
+pub use mock::{MockChain, SevSnpMockChainOptions, SevSnpMockCrlOptions, SevSnpMockReport};
+pub use node::{
+    verify_node_attestation, AttestationCheck, AttestationCheckKind, NodeAttestation,
+    NodeAttestationVerdict,
+};
+pub use node_set::{verify_node_set, NodeAttestationInput, NodeAttestationResult, NodeSetVerdict};
+pub use options::SevSnpVerifyOptions;
+pub use policy::{MinTcb, SevSnpPolicy};
+pub use product::SevProduct;
+pub use report::{Cpuid, SigningKey, SnpReport, Tcb, TcbKind};
+
+/// Gets the vcek url for the given attestation report.  You can fetch this certificate yourself, and pass it in to verify_attestation_report
+/// The product is detected from the report when not given
+#[wasm_bindgen(js_name = "sevSnpGetVcekUrl")]
+pub fn sev_snp_get_vcek_url(
+    attestation_report: &[u8],
+    product: Option<SevProduct>,
+) -> JsResult<String> {
+    let attestation_report = parse_attestation_report(attestation_report)?;
+    let product = resolve_product(&attestation_report, product)?;
+    let signing_key = attestation_report.signing_key();
+    if signing_key != SigningKey::Vcek {
+        return Err(LitCryptoError::AttestationChain(format!(
+            "the report is signed by the {}, only VCEKs are fetched by chip id",
+            signing_key.name()
+        )));
+    }
+    let url = get_vcek_url(&attestation_report, product);
+    Ok(url)
+}
+
+/// Parses the attestation report without verifying it, for display.
+/// The product, needed to decode the TCB versions, is detected from the report when not given
+#[wasm_bindgen(js_name = "sevSnpParseReport")]
+pub fn sev_snp_parse_report(
+    attestation_report: &[u8],
+    product: Option<SevProduct>,
+) -> JsResult<SevSnpReport> {
+    let attestation_report = parse_attestation_report(attestation_report)?;
+    let product = resolve_product(&attestation_report, product)?;
+    Ok(SevSnpReport::new(&attestation_report, product))
+}
+
+/// Detects the EPYC product that produced the given attestation report.
+/// Version 2 reports only tell Turin apart, Milan and Genoa ones fail with `unknown_product`
+#[wasm_bindgen(js_name = "sevSnpDetectProduct")]
+pub fn sev_snp_detect_product(attestation_report: &[u8]) -> JsResult<SevProduct> {
+    let attestation_report = parse_attestation_report(attestation_report)?;
+    SevProduct::detect(&attestation_report)
+}
+
+const KDS_CERT_SITE: &str = "https://kdsintf.amd.com";
+#[allow(dead_code)]
+const KDS_DEV_CERT_SITE: &str = "https://kdsintfdev.amd.com";
+
+#[allow(dead_code)]
+const KDS_CEK: &str = "/cek/id/";
+
+const KDS_VCEK: &str = "/vcek/v1/"; // KDS_VCEK/{product_name}/{hwid}?{tcb parameter list}
+const KDS_VLEK: &str = "/vlek/v1/"; // VLEKs are loaded by the cloud provider, the KDS only serves their chain and CRL
+const KDS_VCEK_CERT_CHAIN: &str = "cert_chain"; // KDS_VCEK/{product_name}/cert_chain
+const KDS_VCEK_CRL: &str = "crl"; // KDS_VCEK/{product_name}/crl"
+
+/// Gets the url of the certificates (PEM) between the endorsement key and the ARK of the product:
+/// the ASK for VCEKs, which is the default, or the ASVK for VLEKs
+#[wasm_bindgen(js_name = "sevSnpGetCertChainUrl")]
+pub fn sev_snp_get_cert_chain_url(
+    product: SevProduct,
+    signing_key: Option<SigningKey>,
+) -> JsResult<String> {
+    get_kds_url(product, signing_key, KDS_VCEK_CERT_CHAIN)
+}
+
+/// Gets the url of the certificate revocation list (DER) of the product, signed by its ARK.
+/// VCEKs, which are the default, and VLEKs each have their own
+#[wasm_bindgen(js_name = "sevSnpGetCrlUrl")]
+pub fn sev_snp_get_crl_url(
+    product: SevProduct,
+    signing_key: Option<SigningKey>,
+) -> JsResult<String> {
+    get_kds_url(product, signing_key, KDS_VCEK_CRL)
+}
+
+fn get_kds_url(
+    product: SevProduct,
+    signing_key: Option<SigningKey>,
+    endpoint: &str,
+) -> JsResult<String> {
+    let prefix = match signing_key.unwrap_or(SigningKey::Vcek) {
+        SigningKey::Vcek => KDS_VCEK,
+        SigningKey::Vlek => KDS_VLEK,
+        SigningKey::None => {
+            return Err(LitCryptoError::AttestationChain(
+                "unsigned reports have no certificates".to_string(),
+            ))
+        }
+    };
+
+    Ok(format!(
+        "{}{}{}/{}",
+        KDS_CERT_SITE,
+        prefix,
+        product.name(),
+        endpoint
+    ))
+}
+
+fn get_vcek_url(attestation_report: &SnpReport, product: SevProduct) -> String {
+    let Tcb {
+        fmc,
+        bootloader,
+        tee,
+        snp,
+        microcode,
+    } = attestation_report.tcb(TcbKind::Reported, product);
+
+    let fmc = fmc
+        .map(|fmc| format!("fmcSPL={fmc:0>2}&"))
+        .unwrap_or_default();
+
+    format!(
+        "{}{}{}/{}?{fmc}blSPL={bootloader:0>2}&teeSPL={tee:0>2}&snpSPL={snp:0>2}&ucodeSPL={microcode:0>2}",
+        KDS_CERT_SITE,
+        KDS_VCEK,
+        product.name(),
+        product.hwid(&attestation_report.chip_id)
+    )
+}
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(typescript_type = "Record<string, Uint8Array>")]
+    pub type AttestationData;
+
+    #[wasm_bindgen(typescript_type = "PolicyViolation[]")]
+    pub type PolicyViolations;
+
+    #[wasm_bindgen(typescript_type = "NodeAttestationInput[]")]
+    pub type NodeAttestationInputs;
+}
+
+/// Verifies the report, its certificate chain and report data.
+/// The certificates are the VCEK (or VLEK) as DER or PEM, a PEM bundle with its ASK (or ASVK) and ARK,
+/// or a blob from `sevSnpCertBundle`. The ARK must be the builtin one of the product unless the options replace it.
+/// With the options, also checks the certificates against a CRL and the report against a policy
+#[wasm_bindgen(js_name = "sevSnpVerify")]
+pub fn sev_snp_verify(
+    attestation_report: &[u8],
+    attestation_data: AttestationData,
+    signatures: Vec<Uint8Array>,
+    challenge: &[u8],
+    vcek_certificate: &[u8],
+    options: Option<SevSnpVerifyOptions>,
+) -> JsResult<()> {
+    let options = options.unwrap_or_default();
+    let time = options.verification_time();
+    let attestation_report = parse_attestation_report(attestation_report)?;
+    let product = resolve_product(&attestation_report, options.product)?;
+    let attestation_data = from_js(attestation_data)?;
+    let signatures = signatures
+        .into_iter()
+        .map(from_js::<Vec<u8>>)
+        .collect::<JsResult<Vec<_>>>()?;
+    let chain = cert_chain(
+        &attestation_report,
+        product,
+        &options,
+        CertBundle::parse(vcek_certificate)?,
+    )?;
+
+    if let Some(crl) = &options.crl {
+        verify_revocation(crl, &chain, &attestation_report, time)?;
+    }
+    verify_certificate(&chain, &attestation_report, time)?;
+    verify_challenge(
+        challenge,
+        attestation_data,
+        signatures,
+        &attestation_report,
+        options.report_data_version.unwrap_or_default(),
+    )?;
+
+    if let Some(policy) = &options.policy {
+        verify_policy(policy, &attestation_report, product)?;
+    }
+
+    Ok(())
+}
+
+/// Runs every check on the attestation a node returned for our challenge: nonce, external address and port,
+/// certificate chain, report data and, when given in the options, revocation and the policy.
+/// Failed checks are reported in the verdict, only malformed input throws
+#[wasm_bindgen(js_name = "sevSnpVerifyNodeAttestation")]
+pub fn sev_snp_verify_node_attestation(
+    attestation: NodeAttestation,
+    challenge: &[u8],
+    url: &str,
+    vcek_certificate: &[u8],
+    options: Option<SevSnpVerifyOptions>,
+) -> JsResult<NodeAttestationVerdict> {
+    verify_node_attestation(
+        &attestation,
+        challenge,
+        url,
+        CertBundle::parse(vcek_certificate)?,
+        &options.unwrap_or_default(),
+    )
+}
+
+/// Verifies the attestations of a whole node set, each like `sevSnpVerifyNodeAttestation` but verifying
+/// the certificates nodes share once. Attested nodes must not advertise the same value under any of the
+/// unique data keys. The verdict says whether at least `threshold` nodes attested, only malformed input
+/// of the whole set, such as the same URL twice, throws
+#[wasm_bindgen(js_name = "sevSnpVerifyNodeAttestations")]
+pub fn sev_snp_verify_node_attestations(
+    nodes: NodeAttestationInputs,
+    threshold: usize,
+    options: Option<SevSnpVerifyOptions>,
+    unique_data_keys: Option<Vec<String>>,
+) -> JsResult<NodeSetVerdict> {
+    verify_node_set(
+        &from_js::<Vec<NodeAttestationInput>>(nodes)?,
+        threshold,
+        &options.unwrap_or_default(),
+        &unique_data_keys.unwrap_or_default(),
+    )
+}
+
+/// Verifies the certificate chain of the report, and its revocation when the options have a CRL,
+/// then packs the ARK, ASK (or ASVK) and VCEK (or VLEK) into a blob to cache and pass instead of the VCEK.
+/// The certificates are in any of the forms `sevSnpVerify` accepts
+#[wasm_bindgen(js_name = "sevSnpCertBundle")]
+pub fn sev_snp_cert_bundle(
+    attestation_report: &[u8],
+    vcek_certificate: &[u8],
+    options: Option<SevSnpVerifyOptions>,
+) -> JsResult<Uint8Array> {
+    let options = options.unwrap_or_default();
+    let time = options.verification_time();
+    let attestation_report = parse_attestation_report(attestation_report)?;
+    let product = resolve_product(&attestation_report, options.product)?;
+    let chain = cert_chain(
+        &attestation_report,
+        product,
+        &options,
+        CertBundle::parse(vcek_certificate)?,
+    )?;
+
+    if let Some(crl) = &options.crl {
+        verify_revocation(crl, &chain, &attestation_report, time)?;
+    }
+    verify_certificate(&chain, &attestation_report, time)?;
+
+    let Chain { ca, vcek } = chain;
+    into_uint8array(CertBundle::new(ca.ark, ca.ask, vcek).to_blob()?)
+}
+
+/// Computes the report data a node commits to with the given binding version
+#[wasm_bindgen(js_name = "sevSnpReportData")]
+pub fn sev_snp_report_data(
+    version: ReportDataVersion,
+    challenge: &[u8],
+    attestation_data: AttestationData,
+    signatures: Vec<Uint8Array>,
+) -> JsResult<Uint8Array> {
+    let attestation_data = from_js(attestation_data)?;
+    let signatures = signatures
+        .into_iter()
+        .map(from_js::<Vec<u8>>)
+        .collect::<JsResult<Vec<_>>>()?;
+    into_uint8array(version.report_data(challenge, &attestation_data, &signatures))
+}
+
+/// Computes the launch measurement, hex encoded, of a guest booted from the given OVMF image,
+/// to allowlist in `SevSnpPolicy.allowedMeasurements`
+#[wasm_bindgen(js_name = "sevSnpLaunchMeasurement")]
+pub fn sev_snp_launch_measurement(
+    ovmf: &[u8],
+    options: SevSnpMeasurementOptions,
+) -> JsResult<String> {
+    Ok(hex::encode(measurement::launch_digest(ovmf, &options)?))
+}
+
+/// Hashes the OVMF image alone, hex encoded, to pass as `ovmfHash` when computing many measurements
+#[wasm_bindgen(js_name = "sevSnpOvmfHash")]
+pub fn sev_snp_ovmf_hash(ovmf: &[u8]) -> JsResult<String> {
+    Ok(hex::encode(measurement::ovmf_hash(ovmf)?))
+}
+
+/// Lists every check of the policy the report fails, without verifying its signature.
+/// An empty list means the report is accepted
+#[wasm_bindgen(js_name = "sevSnpCheckPolicy")]
+pub fn sev_snp_check_policy(
+    attestation_report: &[u8],
+    policy: SevSnpPolicy,
+    product: Option<SevProduct>,
+) -> JsResult<PolicyViolations> {
+    let attestation_report = parse_attestation_report(attestation_report)?;
+    let product = resolve_product(&attestation_report, product)?;
+    let violations: js_sys::Array = into_js(&policy.check(&attestation_report, product))?;
+    Ok(violations.unchecked_into())
+}
+
+fn parse_attestation_report(attestation_report: &[u8]) -> JsResult<SnpReport> {
+    SnpReport::parse(attestation_report).map_err(LitCryptoError::AttestationReport)
+}
+
+fn resolve_product(report: &SnpReport, product: Option<SevProduct>) -> JsResult<SevProduct> {
+    match product {
+        Some(product) => Ok(product),
+        None => SevProduct::detect(report),
+    }
+}
+
+/// Completes the endorsement key of the bundle into the chain to verify.
+/// The ARK is the one of the options, or else the builtin one of the product, and a bundled ARK must match it.
+/// The signer is the ASK (ASVK for VLEKs) of the options, or else of the bundle, or else the builtin ASK
+fn cert_chain(
+    report: &SnpReport,
+    product: SevProduct,
+    options: &SevSnpVerifyOptions,
+    bundle: CertBundle,
+) -> JsResult<Chain> {
+    let builtin = product.ca_chain()?;
+    let ark = match &options.ark {
+        Some(ark) => parse_certificate(ark)?,
+        None => builtin.ark,
+    };
+    if bundle.ark.as_ref().is_some_and(|bundled| *bundled != ark) {
+        return Err(LitCryptoError::AttestationChain(format!(
+            "the bundled ARK is not the trusted ARK of {}",
+            product.name()
+        )));
+    }
+
+    let signer = match report.signing_key() {
+        SigningKey::Vcek => match (&options.ask, bundle.signer) {
+            (Some(ask), _) => parse_certificate(ask)?,
+            (None, Some(ask)) => ask,
+            (None, None) => builtin.ask,
+        },
+        SigningKey::Vlek => match (&options.asvk, bundle.signer) {
+            (Some(asvk), _) => parse_certificate(asvk)?,
+            (None, Some(asvk)) => asvk,
+            (None, None) => {
+                return Err(LitCryptoError::AttestationChain(
+                    "the report is signed by a VLEK, pass the ASVK of the product".to_string(),
+                ))
+            }
+        },
+        SigningKey::None => {
+            return Err(LitCryptoError::AttestationChain(
+                "the report is not signed".to_string(),
+            ))
+        }
+    };
+
+    Ok(Chain {
+        ca: ca::Chain { ark, ask: signer },
+        vcek: bundle.endorsement_key,
+    })
+}
+
+fn verify_certificate(chain: &Chain, report: &SnpReport, time: u64) -> JsResult<()> {
+    verify_chain(chain, report.signing_key(), time)?;
+    report.verify_signature(&chain.vcek)
+}
+
+/// Everything about the chain that does not depend on the report, so nodes sharing certificates only verify it once
+fn verify_chain(chain: &Chain, signing_key: SigningKey, time: u64) -> JsResult<()> {
+    chain
+        .verify()
+        .map_err(|e| LitCryptoError::AttestationChain(e.to_string()))?;
+
+    for (name, cert) in [
+        ("ARK", &chain.ca.ark),
+        (signing_key.signer_name(), &chain.ca.ask),
+        (signing_key.name(), &chain.vcek),
+    ] {
+        check_validity(name, &to_x509(cert)?, time)?;
+    }
+    Ok(())
+}
+
+fn verify_revocation(crl: &[u8], chain: &Chain, report: &SnpReport, time: u64) -> JsResult<()> {
+    let signing_key = report.signing_key();
+    let crl = Crl::parse(crl)?;
+
+    verify_crl(&crl, &to_x509(&chain.ca.ark)?, time)?;
+    crl.check(signing_key.signer_name(), &to_x509(&chain.ca.ask)?)?;
+    crl.check(signing_key.name(), &to_x509(&chain.vcek)?)
+}
+
+fn verify_policy(policy: &SevSnpPolicy, report: &SnpReport, product: SevProduct) -> JsResult<()> {
+    let violations = policy.check(report, product);
+    if !violations.is_empty() {
+        return Err(LitCryptoError::PolicyViolations(violations));
+    }
+    Ok(())
+}
+
+fn verify_challenge(
+    challenge: &[u8],
+    data: BTreeMap<String, Vec<u8>>,
+    signatures: Vec<Vec<u8>>,
+    attestation_report: &SnpReport,
+    version: ReportDataVersion,
+) -> JsResult<()> {
+    version.verify(
+        &attestation_report.report_data,
+        challenge,
+        &data,
+        &signatures,
+    )
+}
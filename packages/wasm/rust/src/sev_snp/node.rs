@@ -0,0 +1,302 @@
+//! The checks a client runs on the attestation a node returns from the handshake
+
+use std::collections::BTreeMap;
+
+use base64_light::base64_decode;
+use serde::{Deserialize, Serialize};
+use sev::certs::snp::Certificate;
+use tsify::Tsify;
+
+use crate::abi::JsResult;
+use crate::error::{LitCryptoError, PolicyViolation};
+
+use super::{
+    bundle::CertBundle, cert_chain, parse_attestation_report, resolve_product, verify_chain,
+    verify_challenge, verify_revocation, SevProduct, SevSnpVerifyOptions, SnpReport,
+};
+
+/// Data key holding the `host:port` the node serves on
+const EXTERNAL_ADDR: &str = "EXTERNAL_ADDR";
+
+/// Attestation as returned by the node, all byte fields base64 encoded
+#[derive(Tsify, Serialize, Deserialize, Debug, Clone)]
+#[tsify(from_wasm_abi)]
+pub struct NodeAttestation {
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub noonce: String,
+    pub data: BTreeMap<String, String>,
+    pub signatures: Vec<String>,
+    pub report: String,
+}
+
+#[derive(Tsify, Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum AttestationCheckKind {
+    /// The nonce the node attested to is the challenge we sent
+    Nonce,
+    /// `EXTERNAL_ADDR` is the host we talked to
+    ExternalAddress,
+    /// `EXTERNAL_ADDR` is the port we talked to
+    ExternalPort,
+    /// The VCEK or VLEK chains to the AMD root and signed the report
+    CertificateChain,
+    /// The CRL revokes none of the certificates below the ARK
+    Revocation,
+    /// The report data binds the challenge, data and signatures
+    ReportData,
+    Policy,
+    /// No other attested node advertises the same values under the unique data keys
+    UniqueData,
+}
+
+impl AttestationCheckKind {
+    /// Same as the serialized name
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Nonce => "nonce",
+            Self::ExternalAddress => "externalAddress",
+            Self::ExternalPort => "externalPort",
+            Self::CertificateChain => "certificateChain",
+            Self::Revocation => "revocation",
+            Self::ReportData => "reportData",
+            Self::Policy => "policy",
+            Self::UniqueData => "uniqueData",
+        }
+    }
+}
+
+#[derive(Tsify, Serialize, Debug, Clone)]
+pub struct AttestationCheck {
+    pub check: AttestationCheckKind,
+    pub passed: bool,
+    #[tsify(optional)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expected: Option<String>,
+    #[tsify(optional)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub actual: Option<String>,
+    #[tsify(optional)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+}
+
+/// Outcome of every check, the node is trusted only if `valid`
+#[derive(Tsify, Serialize, Debug, Clone)]
+#[tsify(into_wasm_abi)]
+pub struct NodeAttestationVerdict {
+    pub valid: bool,
+    pub product: SevProduct,
+    pub checks: Vec<AttestationCheck>,
+    pub violations: Vec<PolicyViolation>,
+}
+
+impl AttestationCheck {
+    fn compare(check: AttestationCheckKind, expected: String, actual: Option<String>) -> Self {
+        Self {
+            check,
+            passed: actual.as_ref() == Some(&expected),
+            expected: Some(expected),
+            actual,
+            message: None,
+        }
+    }
+
+    pub fn from_result(check: AttestationCheckKind, result: JsResult<()>) -> Self {
+        let (expected, actual) = match &result {
+            Err(LitCryptoError::ReportDataMismatch {
+                expected, actual, ..
+            }) => (Some(hex::encode(expected)), Some(hex::encode(actual))),
+            _ => (None, None),
+        };
+
+        Self {
+            check,
+            passed: result.is_ok(),
+            expected,
+            actual,
+            message: result.err().map(|e| e.to_string()),
+        }
+    }
+}
+
+/// Outcome of the checks on the certificates of a node, which do not depend on its report
+/// beyond the product and signing key, so nodes sharing certificates can share it
+#[derive(Clone)]
+pub struct ChainChecks {
+    revocation: Option<AttestationCheck>,
+    /// The VCEK or VLEK once its chain is verified
+    endorsement_key: JsResult<Certificate>,
+}
+
+/// Verifies the chain of the certificates up to the ARK, and its revocation when given a CRL
+pub fn check_chain(
+    report: &SnpReport,
+    product: SevProduct,
+    options: &SevSnpVerifyOptions,
+    certificates: CertBundle,
+    crl: Option<&[u8]>,
+) -> ChainChecks {
+    let time = options.verification_time();
+
+    // A VLEK report without the ASVK, or a bundle with another ARK, fails the chain check rather than erroring, like any bad chain
+    match cert_chain(report, product, options, certificates) {
+        Ok(chain) => ChainChecks {
+            revocation: crl.map(|crl| {
+                AttestationCheck::from_result(
+                    AttestationCheckKind::Revocation,
+                    verify_revocation(crl, &chain, report, time),
+                )
+            }),
+            endorsement_key: verify_chain(&chain, report.signing_key(), time).map(|()| chain.vcek),
+        },
+        Err(e) => ChainChecks {
+            revocation: None,
+            endorsement_key: Err(e),
+        },
+    }
+}
+
+/// Runs every check instead of stopping at the first failure, so the verdict says all that is wrong.
+/// Only malformed input (report, certificate or URL) is an error.
+pub fn verify_node_attestation(
+    attestation: &NodeAttestation,
+    challenge: &[u8],
+    url: &str,
+    certificates: CertBundle,
+    options: &SevSnpVerifyOptions,
+) -> JsResult<NodeAttestationVerdict> {
+    verify_node(attestation, challenge, url, options, |report, product| {
+        Ok(check_chain(
+            report,
+            product,
+            options,
+            certificates,
+            options.crl.as_deref(),
+        ))
+    })
+}
+
+/// Same as `verify_node_attestation`, with the checks of the certificates supplied by the caller
+pub fn verify_node(
+    attestation: &NodeAttestation,
+    challenge: &[u8],
+    url: &str,
+    options: &SevSnpVerifyOptions,
+    chain_checks: impl FnOnce(&SnpReport, SevProduct) -> JsResult<ChainChecks>,
+) -> JsResult<NodeAttestationVerdict> {
+    let report = parse_attestation_report(&base64_decode(&attestation.report))?;
+    let product = resolve_product(&report, options.product)?;
+    let (host, port) = host_and_port(url)?;
+
+    let data = attestation
+        .data
+        .iter()
+        .map(|(key, value)| (key.clone(), base64_decode(value)))
+        .collect::<BTreeMap<_, _>>();
+    let signatures = attestation
+        .signatures
+        .iter()
+        .map(|s| base64_decode(s))
+        .collect::<Vec<_>>();
+
+    // host names are case insensitive, and the url host is lowercased too
+    let (external_host, external_port) = match data.get(EXTERNAL_ADDR) {
+        Some(addr) => match String::from_utf8_lossy(addr).rsplit_once(':') {
+            Some((host, port)) => (Some(host.to_ascii_lowercase()), Some(port.to_string())),
+            None => (
+                Some(String::from_utf8_lossy(addr).to_ascii_lowercase()),
+                None,
+            ),
+        },
+        None => (None, None),
+    };
+
+    let mut checks = vec![
+        AttestationCheck::compare(
+            AttestationCheckKind::Nonce,
+            hex::encode(challenge),
+            Some(hex::encode(base64_decode(&attestation.noonce))),
+        ),
+        AttestationCheck::compare(AttestationCheckKind::ExternalAddress, host, external_host),
+        AttestationCheck::compare(
+            AttestationCheckKind::ExternalPort,
+            port.to_string(),
+            external_port,
+        ),
+    ];
+
+    let ChainChecks {
+        revocation,
+        endorsement_key,
+    } = chain_checks(&report, product)?;
+    checks.extend(revocation);
+    checks.push(AttestationCheck::from_result(
+        AttestationCheckKind::CertificateChain,
+        endorsement_key.and_then(|key| report.verify_signature(&key)),
+    ));
+    checks.push(AttestationCheck::from_result(
+        AttestationCheckKind::ReportData,
+        verify_challenge(
+            challenge,
+            data,
+            signatures,
+            &report,
+            options.report_data_version.unwrap_or_default(),
+        ),
+    ));
+
+    let violations = options
+        .policy
+        .as_ref()
+        .map(|policy| policy.check(&report, product))
+        .unwrap_or_default();
+    if options.policy.is_some() {
+        checks.push(AttestationCheck {
+            check: AttestationCheckKind::Policy,
+            passed: violations.is_empty(),
+            expected: None,
+            actual: None,
+            message: (!violations.is_empty())
+                .then(|| LitCryptoError::PolicyViolations(violations.clone()).to_string()),
+        });
+    }
+
+    Ok(NodeAttestationVerdict {
+        valid: checks.iter().all(|c| c.passed),
+        product,
+        checks,
+        violations,
+    })
+}
+
+/// Host and port we talked to, http and https URLs without a port use the scheme's default
+pub fn host_and_port(url: &str) -> JsResult<(String, u16)> {
+    let invalid = || LitCryptoError::Deserialize(format!("invalid url {url}"));
+
+    let (scheme, rest) = url.split_once("://").ok_or_else(invalid)?;
+    let authority = rest.split(['/', '?', '#']).next().unwrap_or_default();
+    let authority = authority.rsplit_once('@').map_or(authority, |(_, a)| a);
+
+    // The port follows the last colon, unless that colon is inside an IPv6 literal
+    let (host, port) = match authority.rsplit_once(':') {
+        Some((host, port)) if !port.contains(']') => (host, Some(port)),
+        _ => (authority, None),
+    };
+    if host.is_empty() {
+        return Err(invalid());
+    }
+
+    let port = match (port, scheme.to_ascii_lowercase().as_str()) {
+        (Some(port), _) => port.parse().map_err(|_| invalid())?,
+        (None, "https") => 443,
+        (None, "http") => 80,
+        (None, _) => {
+            return Err(LitCryptoError::Deserialize(format!(
+                "unknown port in url {url}"
+            )))
+        }
+    };
+
+    Ok((host.to_ascii_lowercase(), port))
+}
@@ -0,0 +1,246 @@
+//! Attestation of every node of the network at once, as the client does during the handshake
+//!
+//! Nodes on the same chip and TCB share their VCEK, so each distinct chain is verified once.
+//! The report signatures and everything else are still checked per node.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use base64_light::base64_decode;
+use serde::{Deserialize, Serialize};
+use tsify::Tsify;
+
+use crate::abi::JsResult;
+use crate::error::LitCryptoError;
+
+use super::bundle::CertBundle;
+use super::node::{
+    check_chain, host_and_port, verify_node, AttestationCheck, AttestationCheckKind, ChainChecks,
+    NodeAttestation, NodeAttestationVerdict,
+};
+use super::{SevProduct, SevSnpVerifyOptions, SigningKey};
+
+/// The attestation a node returned for our challenge, with the certificates to verify it against
+#[derive(Tsify, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct NodeAttestationInput {
+    /// The URL we talked to
+    pub url: String,
+    pub attestation: NodeAttestation,
+    #[tsify(type = "Uint8Array")]
+    #[serde(with = "serde_bytes")]
+    pub challenge: Vec<u8>,
+    /// The VCEK or VLEK in any of the forms `sevSnpVerify` accepts
+    #[tsify(type = "Uint8Array")]
+    #[serde(with = "serde_bytes")]
+    pub certificates: Vec<u8>,
+    /// CRL of the product of the node, replacing the one of the options when the network mixes products
+    #[tsify(optional, type = "Uint8Array")]
+    #[serde(default, with = "serde_bytes")]
+    pub crl: Option<Vec<u8>>,
+}
+
+#[derive(Tsify, Serialize, Debug, Clone)]
+pub struct NodeAttestationResult {
+    pub url: String,
+    pub valid: bool,
+    /// Missing when the input of the node is malformed, and no check could run
+    #[tsify(optional)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub verdict: Option<NodeAttestationVerdict>,
+    /// Why the node is not trusted, empty when it is
+    pub reasons: Vec<String>,
+}
+
+/// Per node results, and whether enough nodes attested to trust the network
+#[derive(Tsify, Serialize, Debug, Clone)]
+#[tsify(into_wasm_abi)]
+#[serde(rename_all = "camelCase")]
+pub struct NodeSetVerdict {
+    pub nodes: Vec<NodeAttestationResult>,
+    /// How many nodes passed every check
+    pub attested: usize,
+    pub threshold: usize,
+    pub threshold_met: bool,
+    /// URLs of the nodes that did not attest, the reasons are in their result
+    pub failed: Vec<String>,
+}
+
+#[derive(PartialEq, Eq, Hash)]
+struct ChainKey {
+    certificates: Vec<u8>,
+    crl: Option<Vec<u8>>,
+    product: SevProduct,
+    signing_key: SigningKey,
+}
+
+/// Verifies every node like `verify_node_attestation`, sharing the chain checks between nodes
+/// with the same certificates. With unique data keys, attested nodes advertising the same value
+/// under any of them fail, since a single node could otherwise count several times towards the threshold.
+/// Malformed input only fails its node, but the same URL twice is an error.
+pub fn verify_node_set(
+    nodes: &[NodeAttestationInput],
+    threshold: usize,
+    options: &SevSnpVerifyOptions,
+    unique_data_keys: &[String],
+) -> JsResult<NodeSetVerdict> {
+    let mut urls = HashSet::new();
+    for node in nodes {
+        // URLs that do not parse fail their node below
+        let key = host_and_port(&node.url).unwrap_or_else(|_| (node.url.clone(), 0));
+        if !urls.insert(key) {
+            return Err(LitCryptoError::Deserialize(format!(
+                "duplicate node url {}",
+                node.url
+            )));
+        }
+    }
+
+    let mut chains: HashMap<ChainKey, JsResult<ChainChecks>> = HashMap::new();
+    let mut verdicts = nodes
+        .iter()
+        .map(|node| {
+            verify_node(
+                &node.attestation,
+                &node.challenge,
+                &node.url,
+                options,
+                |report, product| {
+                    let crl = node.crl.as_ref().or(options.crl.as_ref());
+                    let key = ChainKey {
+                        certificates: node.certificates.clone(),
+                        crl: crl.cloned(),
+                        product,
+                        signing_key: report.signing_key(),
+                    };
+                    chains
+                        .entry(key)
+                        .or_insert_with(|| {
+                            CertBundle::parse(&node.certificates).map(|certificates| {
+                                check_chain(
+                                    report,
+                                    product,
+                                    options,
+                                    certificates,
+                                    crl.map(Vec::as_slice),
+                                )
+                            })
+                        })
+                        .clone()
+                },
+            )
+        })
+        .collect::<Vec<_>>();
+
+    check_unique_data(nodes, &mut verdicts, unique_data_keys);
+
+    let nodes = nodes
+        .iter()
+        .zip(verdicts)
+        .map(|(node, verdict)| match verdict {
+            Ok(verdict) => NodeAttestationResult {
+                url: node.url.clone(),
+                valid: verdict.valid,
+                reasons: verdict
+                    .checks
+                    .iter()
+                    .filter(|check| !check.passed)
+                    .map(describe)
+                    .collect(),
+                verdict: Some(verdict),
+            },
+            Err(e) => NodeAttestationResult {
+                url: node.url.clone(),
+                valid: false,
+                verdict: None,
+                reasons: vec![e.to_string()],
+            },
+        })
+        .collect::<Vec<_>>();
+
+    let attested = nodes.iter().filter(|node| node.valid).count();
+    let failed = nodes
+        .iter()
+        .filter(|node| !node.valid)
+        .map(|node| node.url.clone())
+        .collect();
+
+    Ok(NodeSetVerdict {
+        nodes,
+        attested,
+        threshold,
+        threshold_met: attested >= threshold,
+        failed,
+    })
+}
+
+/// Only compares nodes that passed every other check, so a node that does not attest
+/// cannot make an honest one fail by copying its data
+fn check_unique_data(
+    nodes: &[NodeAttestationInput],
+    verdicts: &mut [JsResult<NodeAttestationVerdict>],
+    unique_data_keys: &[String],
+) {
+    if unique_data_keys.is_empty() {
+        return;
+    }
+
+    let mut advertised: BTreeMap<(&str, Vec<u8>), Vec<&str>> = BTreeMap::new();
+    for (node, verdict) in nodes.iter().zip(verdicts.iter()) {
+        if !matches!(verdict, Ok(verdict) if verdict.valid) {
+            continue;
+        }
+        for key in unique_data_keys {
+            if let Some(value) = node.attestation.data.get(key) {
+                advertised
+                    .entry((key.as_str(), base64_decode(value)))
+                    .or_default()
+                    .push(&node.url);
+            }
+        }
+    }
+
+    for (node, verdict) in nodes.iter().zip(verdicts.iter_mut()) {
+        let Ok(verdict) = verdict else { continue };
+        if !verdict.valid {
+            continue;
+        }
+
+        let mut problems = Vec::new();
+        for key in unique_data_keys {
+            match node.attestation.data.get(key) {
+                Some(value) => {
+                    let others = advertised[&(key.as_str(), base64_decode(value))]
+                        .iter()
+                        .filter(|url| **url != node.url)
+                        .copied()
+                        .collect::<Vec<_>>();
+                    if !others.is_empty() {
+                        problems.push(format!("{key} is also advertised by {}", others.join(", ")));
+                    }
+                }
+                None => problems.push(format!("{key} is missing")),
+            }
+        }
+
+        verdict.valid = problems.is_empty();
+        verdict.checks.push(AttestationCheck {
+            check: AttestationCheckKind::UniqueData,
+            passed: problems.is_empty(),
+            expected: None,
+            actual: None,
+            message: (!problems.is_empty()).then(|| problems.join(", ")),
+        });
+    }
+}
+
+fn describe(check: &AttestationCheck) -> String {
+    match (&check.message, &check.expected, &check.actual) {
+        (Some(message), _, _) => message.clone(),
+        (None, Some(expected), actual) => format!(
+            "{} expected {expected}, got {}",
+            check.check.name(),
+            actual.as_deref().unwrap_or("nothing")
+        ),
+        _ => format!("{} failed", check.check.name()),
+    }
+}
@@ -0,0 +1,47 @@
+//! Optional inputs shared by the verification entry points
+
+use serde::Deserialize;
+use tsify::Tsify;
+
+use super::{ReportDataVersion, SevProduct, SevSnpPolicy};
+
+#[derive(Tsify, Deserialize, Debug, Clone, Default)]
+#[tsify(from_wasm_abi)]
+#[serde(rename_all = "camelCase", default)]
+pub struct SevSnpVerifyOptions {
+    /// Detected from the report when not given, which version 2 reports of Milan and Genoa need
+    #[tsify(optional)]
+    pub product: Option<SevProduct>,
+    /// Rejects reports that violate it
+    #[tsify(optional)]
+    pub policy: Option<SevSnpPolicy>,
+    /// Binding of the report data, the legacy V0 when not given
+    #[tsify(optional)]
+    pub report_data_version: Option<ReportDataVersion>,
+    /// Root of trust (DER or PEM) replacing the builtin ARK of the product, e.g. a test CA
+    #[tsify(optional, type = "Uint8Array")]
+    #[serde(with = "serde_bytes")]
+    pub ark: Option<Vec<u8>>,
+    /// ASK (DER or PEM) replacing the one of the certificates or the builtin one of the product
+    #[tsify(optional, type = "Uint8Array")]
+    #[serde(with = "serde_bytes")]
+    pub ask: Option<Vec<u8>>,
+    /// ASVK of the product (DER or PEM), required to verify reports signed by a VLEK unless the certificates include it
+    #[tsify(optional, type = "Uint8Array")]
+    #[serde(with = "serde_bytes")]
+    pub asvk: Option<Vec<u8>>,
+    /// CRL of the product from the KDS, the certificates below the ARK are checked against it when given
+    #[tsify(optional, type = "Uint8Array")]
+    #[serde(with = "serde_bytes")]
+    pub crl: Option<Vec<u8>>,
+    /// Unix time in seconds the certificates and CRL must be valid at, now when not given
+    #[tsify(optional)]
+    pub verification_time: Option<u64>,
+}
+
+impl SevSnpVerifyOptions {
+    pub fn verification_time(&self) -> u64 {
+        self.verification_time
+            .unwrap_or_else(|| (js_sys::Date::now() / 1000.0) as u64)
+    }
+}
@@ -0,0 +1,168 @@
+//! Checks of the launch-time properties of a guest, on top of the certificate chain and report data
+
+use serde::{Deserialize, Serialize};
+use tsify::Tsify;
+
+use crate::error::PolicyViolation;
+
+use super::{SevProduct, SnpReport, TcbKind};
+
+/// What an attestation report must show to be trusted.
+/// Byte fields are hex encoded, unset fields are not checked.
+#[derive(Tsify, Serialize, Deserialize, Debug, Clone, Default)]
+#[tsify(from_wasm_abi)]
+#[serde(rename_all = "camelCase", default)]
+pub struct SevSnpPolicy {
+    /// Launch measurements of the node images we trust
+    #[tsify(optional)]
+    pub allowed_measurements: Option<Vec<String>>,
+    /// Lowest reported TCB accepted for each component
+    #[tsify(optional)]
+    pub min_tcb: Option<MinTcb>,
+    #[tsify(optional)]
+    pub min_guest_svn: Option<u32>,
+    /// Accept guests launched with debugging enabled, which lets the host read their memory
+    pub allow_debug: bool,
+    /// Accept guests that can be associated with a migration agent
+    pub allow_migration_agent: bool,
+    pub require_single_socket: bool,
+    /// VMPL the report must have been requested from
+    #[tsify(optional)]
+    pub vmpl: Option<u32>,
+    #[tsify(optional)]
+    pub host_data: Option<String>,
+    #[tsify(optional)]
+    pub allowed_id_key_digests: Option<Vec<String>>,
+    #[tsify(optional)]
+    pub allowed_author_key_digests: Option<Vec<String>>,
+}
+
+#[derive(Tsify, Serialize, Deserialize, Debug, Clone, Copy, Default)]
+#[serde(rename_all = "camelCase", default)]
+pub struct MinTcb {
+    /// Only checked on Turin and later, which report it
+    #[tsify(optional)]
+    pub fmc: Option<u8>,
+    #[tsify(optional)]
+    pub bootloader: Option<u8>,
+    #[tsify(optional)]
+    pub tee: Option<u8>,
+    #[tsify(optional)]
+    pub snp: Option<u8>,
+    #[tsify(optional)]
+    pub microcode: Option<u8>,
+}
+
+impl SevSnpPolicy {
+    /// Returns every check the report fails, an empty list means the report is accepted
+    pub fn check(&self, report: &SnpReport, product: SevProduct) -> Vec<PolicyViolation> {
+        let mut violations = Vec::new();
+        let mut violation = |field: &str, expected: String, actual: String| {
+            violations.push(PolicyViolation {
+                field: field.to_string(),
+                expected,
+                actual,
+            })
+        };
+
+        if let Some(allowed) = &self.allowed_measurements {
+            check_allowed(&mut violation, "measurement", allowed, &report.measurement);
+        }
+
+        if let Some(min_tcb) = &self.min_tcb {
+            let tcb = report.tcb(TcbKind::Reported, product);
+            let components = [
+                ("reportedTcb.fmc", min_tcb.fmc, tcb.fmc),
+                (
+                    "reportedTcb.bootloader",
+                    min_tcb.bootloader,
+                    Some(tcb.bootloader),
+                ),
+                ("reportedTcb.tee", min_tcb.tee, Some(tcb.tee)),
+                ("reportedTcb.snp", min_tcb.snp, Some(tcb.snp)),
+                (
+                    "reportedTcb.microcode",
+                    min_tcb.microcode,
+                    Some(tcb.microcode),
+                ),
+            ];
+            for (field, min, actual) in components {
+                if let (Some(min), Some(actual)) = (min, actual) {
+                    if actual < min {
+                        violation(field, format!(">= {min}"), actual.to_string());
+                    }
+                }
+            }
+        }
+
+        if let Some(min) = self.min_guest_svn {
+            if report.guest_svn < min {
+                violation(
+                    "guestSvn",
+                    format!(">= {min}"),
+                    report.guest_svn.to_string(),
+                );
+            }
+        }
+
+        let policy = report.policy;
+        if !self.allow_debug && policy.debug_allowed() != 0 {
+            violation("policy.debug", "false".into(), "true".into());
+        }
+        if !self.allow_migration_agent && policy.migrate_ma_allowed() != 0 {
+            violation("policy.migrateMa", "false".into(), "true".into());
+        }
+        if self.require_single_socket && policy.single_socket_required() == 0 {
+            violation("policy.singleSocket", "true".into(), "false".into());
+        }
+
+        if let Some(vmpl) = self.vmpl {
+            if report.vmpl != vmpl {
+                violation("vmpl", vmpl.to_string(), report.vmpl.to_string());
+            }
+        }
+
+        if let Some(host_data) = &self.host_data {
+            check_allowed(
+                &mut violation,
+                "hostData",
+                std::slice::from_ref(host_data),
+                &report.host_data,
+            );
+        }
+        if let Some(allowed) = &self.allowed_id_key_digests {
+            check_allowed(
+                &mut violation,
+                "idKeyDigest",
+                allowed,
+                &report.id_key_digest,
+            );
+        }
+        if let Some(allowed) = &self.allowed_author_key_digests {
+            check_allowed(
+                &mut violation,
+                "authorKeyDigest",
+                allowed,
+                &report.author_key_digest,
+            );
+        }
+
+        violations
+    }
+}
+
+fn check_allowed(
+    violation: &mut impl FnMut(&str, String, String),
+    field: &str,
+    allowed: &[String],
+    actual: &[u8],
+) {
+    let actual = hex::encode(actual);
+    let allowed_match = allowed
+        .iter()
+        .any(|a| a.trim_start_matches("0x").eq_ignore_ascii_case(&actual));
+
+    if !allowed_match {
+        violation(field, format!("one of [{}]", allowed.join(", ")), actual);
+    }
+}
@@ -0,0 +1,99 @@
+//! EPYC product lines, each with its own AMD root and signing keys
+
+use serde::{Deserialize, Serialize};
+use sev::certs::snp::{
+    builtin::{genoa, milan},
+    ca, Certificate,
+};
+use tsify::Tsify;
+
+use crate::abi::JsResult;
+use crate::error::LitCryptoError;
+
+use super::SnpReport;
+
+/// Not shipped by the `sev` version we depend on, taken from https://kdsintf.amd.com/vcek/v1/Turin/cert_chain
+const TURIN_ARK: &[u8] = include_bytes!("builtin/turin/ark.pem");
+const TURIN_ASK: &[u8] = include_bytes!("builtin/turin/ask.pem");
+
+#[derive(Tsify, Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[tsify(into_wasm_abi, from_wasm_abi)]
+pub enum SevProduct {
+    Milan,
+    Genoa,
+    Turin,
+}
+
+impl SevProduct {
+    pub fn from_cpuid(family: u8, model: u8) -> Option<Self> {
+        match (family, model) {
+            (0x19, 0x00..=0x0F) => Some(Self::Milan),
+            (0x19, 0x10..=0x1F | 0xA0..=0xAF) => Some(Self::Genoa),
+            (0x1A, 0x00..=0x11) => Some(Self::Turin),
+            _ => None,
+        }
+    }
+
+    /// Uses the CPUID fields of version 3 and later reports.
+    /// Version 2 reports don't carry them, only the shorter chip id of Turin tells its product,
+    /// the others need the product to be given
+    pub fn detect(report: &SnpReport) -> JsResult<Self> {
+        if let Some(cpuid) = report.cpuid() {
+            return Self::from_cpuid(cpuid.family, cpuid.model).ok_or(
+                LitCryptoError::UnknownProduct {
+                    family: Some(cpuid.family),
+                    model: Some(cpuid.model),
+                },
+            );
+        }
+
+        let unknown = LitCryptoError::UnknownProduct {
+            family: None,
+            model: None,
+        };
+        if report.chip_id.iter().all(|b| *b == 0) {
+            // Masked chip id, there is nothing left to go by
+            return Err(unknown);
+        }
+        if report.chip_id[8..].iter().all(|b| *b == 0) {
+            return Ok(Self::Turin);
+        }
+        // A full chip id is either Milan or Genoa, guessing would verify against the wrong root keys
+        Err(unknown)
+    }
+
+    /// Name used by the AMD Key Distribution Service
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Milan => "Milan",
+            Self::Genoa => "Genoa",
+            Self::Turin => "Turin",
+        }
+    }
+
+    /// The chip id as the KDS expects it, Turin only uses the first 8 bytes
+    pub fn hwid(&self, chip_id: &[u8; 64]) -> String {
+        match self {
+            Self::Milan | Self::Genoa => hex::encode(chip_id),
+            Self::Turin => hex::encode(&chip_id[..8]),
+        }
+    }
+
+    /// The built-in ARK and ASK of this product
+    pub fn ca_chain(&self) -> JsResult<ca::Chain> {
+        let (ark, ask) = match self {
+            Self::Milan => (milan::ark(), milan::ask()),
+            Self::Genoa => (genoa::ark(), genoa::ask()),
+            Self::Turin => (
+                Certificate::from_pem(TURIN_ARK),
+                Certificate::from_pem(TURIN_ASK),
+            ),
+        };
+        let into_err = |e: std::io::Error| LitCryptoError::AttestationChain(e.to_string());
+
+        Ok(ca::Chain {
+            ark: ark.map_err(into_err)?,
+            ask: ask.map_err(into_err)?,
+        })
+    }
+}
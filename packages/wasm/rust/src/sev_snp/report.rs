@@ -0,0 +1,318 @@
+//! Bounds-checked parsing of SEV-SNP attestation reports
+//!
+//! Reports come from the network, so nothing is read before the length, version,
+//! reserved fields and signature algorithm have been checked.
+//! Layout: SEV-SNP Firmware ABI Specification, Table 22 "ATTESTATION_REPORT Structure".
+
+use std::ops::{Deref, Range};
+
+use p384::ecdsa::signature::DigestVerifier;
+use serde::{Deserialize, Serialize};
+use sev::certs::snp::Certificate;
+use sev::firmware::guest::AttestationReport;
+use sha2::{Digest, Sha384};
+use tsify::Tsify;
+
+use crate::error::{LitCryptoError, ReportError};
+
+use super::SevProduct;
+
+pub const REPORT_SIZE: usize = 0x4A0;
+
+pub const MIN_VERSION: u32 = 2;
+pub const MAX_VERSION: u32 = 4;
+
+/// ECDSA P-384 with SHA-384, the only algorithm the firmware signs reports with
+pub const SIG_ALGO_ECDSA_P384_SHA384: u32 = 1;
+
+/// Everything the report signature covers
+pub const SIGNED_LEN: usize = 0x2A0;
+/// R and S are 72 byte little-endian fields
+pub const SIGNATURE_R: usize = 0x2A0;
+pub const SIGNATURE_S: usize = 0x2E8;
+const SCALAR_LEN: usize = 48;
+
+pub const KEY_INFO_OFFSET: usize = 0x48;
+/// AUTHOR_KEY_EN, MASK_CHIP_KEY and SIGNING_KEY, the other bits are reserved
+const KEY_INFO_MASK: u32 = 0x1F;
+const SIGNING_KEY_SHIFT: u32 = 2;
+const SIGNING_KEY_MASK: u32 = 0x7;
+
+/// Reserved ranges that must be zero, with the first and last report version they apply to
+const RESERVED: &[(Range<usize>, u32, u32)] = &[
+    (0x4C..0x50, MIN_VERSION, MAX_VERSION),
+    // CPUID_FAM_ID, CPUID_MOD_ID and CPUID_STEP were added in version 3
+    (0x188..0x18B, MIN_VERSION, 2),
+    (0x18B..0x1A0, MIN_VERSION, MAX_VERSION),
+    (0x1EB..0x1EC, MIN_VERSION, MAX_VERSION),
+    (0x1EF..0x1F0, MIN_VERSION, MAX_VERSION),
+    (0x1F8..0x2A0, MIN_VERSION, MAX_VERSION),
+    // R and S are 72 byte little-endian fields holding 48 byte P-384 scalars
+    (0x2D0..0x2E8, MIN_VERSION, MAX_VERSION),
+    (0x318..0x330, MIN_VERSION, MAX_VERSION),
+    (0x330..REPORT_SIZE, MIN_VERSION, MAX_VERSION),
+];
+
+/// CPUID_FAM_ID, CPUID_MOD_ID and CPUID_STEP of version 3 and later reports
+#[derive(Tsify, Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cpuid {
+    pub family: u8,
+    pub model: u8,
+    pub stepping: u8,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TcbKind {
+    Current,
+    Reported,
+    Committed,
+    Launch,
+}
+
+impl TcbKind {
+    pub fn offset(&self) -> usize {
+        match self {
+            Self::Current => 0x38,
+            Self::Reported => 0x180,
+            Self::Committed => 0x1E0,
+            Self::Launch => 0x1F0,
+        }
+    }
+}
+
+/// SIGNING_KEY of the report, the key that endorses it
+#[derive(Tsify, Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[tsify(into_wasm_abi, from_wasm_abi)]
+pub enum SigningKey {
+    /// Versioned Chip Endorsement Key, derived from the chip and fetched from the KDS by chip id
+    Vcek,
+    /// Versioned Loaded Endorsement Key, loaded by the cloud provider and rooted in the ASVK
+    Vlek,
+    /// The report is not signed
+    None,
+}
+
+impl SigningKey {
+    fn from_key_info(key_info: u32) -> Option<Self> {
+        match (key_info >> SIGNING_KEY_SHIFT) & SIGNING_KEY_MASK {
+            0 => Some(Self::Vcek),
+            1 => Some(Self::Vlek),
+            7 => Some(Self::None),
+            _ => None,
+        }
+    }
+
+    /// The SIGNING_KEY bits of KEY_INFO
+    pub fn to_key_info(&self) -> u32 {
+        let bits = match self {
+            Self::Vcek => 0,
+            Self::Vlek => 1,
+            Self::None => 7,
+        };
+        bits << SIGNING_KEY_SHIFT
+    }
+
+    /// Name of the endorsement key certificate
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Vcek => "VCEK",
+            Self::Vlek => "VLEK",
+            Self::None => "none",
+        }
+    }
+
+    /// Name of the certificate that signs the endorsement key, below the ARK
+    pub fn signer_name(&self) -> &'static str {
+        match self {
+            Self::Vlek => "ASVK",
+            Self::Vcek | Self::None => "ASK",
+        }
+    }
+}
+
+/// A TCB_VERSION decoded with the layout of the product that produced it
+#[derive(Tsify, Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Tcb {
+    /// Only reported by Turin and later
+    #[tsify(optional)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fmc: Option<u8>,
+    pub bootloader: u8,
+    pub tee: u8,
+    pub snp: u8,
+    pub microcode: u8,
+}
+
+impl Tcb {
+    pub fn from_bytes(bytes: [u8; 8], product: SevProduct) -> Self {
+        match product {
+            SevProduct::Milan | SevProduct::Genoa => Self {
+                fmc: None,
+                bootloader: bytes[0],
+                tee: bytes[1],
+                snp: bytes[6],
+                microcode: bytes[7],
+            },
+            SevProduct::Turin => Self {
+                fmc: Some(bytes[0]),
+                bootloader: bytes[1],
+                tee: bytes[2],
+                snp: bytes[3],
+                microcode: bytes[7],
+            },
+        }
+    }
+
+    pub fn to_bytes(&self, product: SevProduct) -> [u8; 8] {
+        match product {
+            SevProduct::Milan | SevProduct::Genoa => [
+                self.bootloader,
+                self.tee,
+                0,
+                0,
+                0,
+                0,
+                self.snp,
+                self.microcode,
+            ],
+            SevProduct::Turin => [
+                self.fmc.unwrap_or_default(),
+                self.bootloader,
+                self.tee,
+                self.snp,
+                0,
+                0,
+                0,
+                self.microcode,
+            ],
+        }
+    }
+}
+
+/// A validated attestation report along with the raw bytes it was parsed from
+#[derive(Clone)]
+pub struct SnpReport {
+    report: AttestationReport,
+    raw: Vec<u8>,
+}
+
+impl SnpReport {
+    pub fn parse(bytes: &[u8]) -> Result<Self, ReportError> {
+        if bytes.len() < REPORT_SIZE {
+            return Err(ReportError::Length {
+                expected: REPORT_SIZE,
+                actual: bytes.len(),
+            });
+        }
+        let (raw, padding) = bytes.split_at(REPORT_SIZE);
+        // Nodes send the report in the firmware's response buffer, anything after it must be zero
+        if let Some(offset) = padding.iter().position(|b| *b != 0) {
+            return Err(ReportError::Reserved {
+                offset: REPORT_SIZE + offset,
+            });
+        }
+
+        let version = read_u32(raw, 0x00);
+        if !(MIN_VERSION..=MAX_VERSION).contains(&version) {
+            return Err(ReportError::Version(version));
+        }
+
+        let sig_algo = read_u32(raw, 0x34);
+        if sig_algo != SIG_ALGO_ECDSA_P384_SHA384 {
+            return Err(ReportError::SignatureAlgorithm(sig_algo));
+        }
+
+        let key_info = read_u32(raw, KEY_INFO_OFFSET);
+        if key_info & !KEY_INFO_MASK != 0 {
+            return Err(ReportError::Reserved {
+                offset: KEY_INFO_OFFSET,
+            });
+        }
+        if SigningKey::from_key_info(key_info).is_none() {
+            return Err(ReportError::SigningKey(
+                (key_info >> SIGNING_KEY_SHIFT) & SIGNING_KEY_MASK,
+            ));
+        }
+
+        for (range, from, to) in RESERVED {
+            if !(*from..=*to).contains(&version) {
+                continue;
+            }
+            if let Some(offset) = raw[range.clone()].iter().position(|b| *b != 0) {
+                return Err(ReportError::Reserved {
+                    offset: range.start + offset,
+                });
+            }
+        }
+
+        // All fields are fixed size, so this only fails if the layout above is wrong
+        let report = bincode::deserialize::<AttestationReport>(raw)
+            .map_err(|e| ReportError::Malformed(e.to_string()))?;
+
+        Ok(Self {
+            report,
+            raw: raw.to_vec(),
+        })
+    }
+
+    pub fn raw(&self) -> &[u8] {
+        &self.raw
+    }
+
+    /// AUTHOR_KEY_EN, MASK_CHIP_KEY and SIGNING_KEY, which `AttestationReport` keeps private
+    pub fn key_info(&self) -> u32 {
+        read_u32(&self.raw, KEY_INFO_OFFSET)
+    }
+
+    pub fn signing_key(&self) -> SigningKey {
+        // Checked by parse
+        SigningKey::from_key_info(self.key_info()).unwrap_or(SigningKey::None)
+    }
+
+    pub fn cpuid(&self) -> Option<Cpuid> {
+        (self.version >= 3).then(|| Cpuid {
+            family: self.raw[0x188],
+            model: self.raw[0x189],
+            stepping: self.raw[0x18A],
+        })
+    }
+
+    /// Checks the signature against the endorsement key alone, the chain above it is verified separately
+    pub fn verify_signature(&self, endorsement_key: &Certificate) -> Result<(), LitCryptoError> {
+        let invalid = |reason: &str| LitCryptoError::AttestationChain(reason.to_string());
+
+        let mut signature = [0u8; 2 * SCALAR_LEN];
+        for (i, offset) in [SIGNATURE_R, SIGNATURE_S].into_iter().enumerate() {
+            let scalar = &mut signature[i * SCALAR_LEN..(i + 1) * SCALAR_LEN];
+            scalar.copy_from_slice(&self.raw[offset..offset + SCALAR_LEN]);
+            scalar.reverse();
+        }
+        let signature = p384::ecdsa::Signature::from_slice(&signature)
+            .map_err(|_| invalid("invalid report signature"))?;
+        let key = p384::ecdsa::VerifyingKey::from_sec1_bytes(endorsement_key.public_key_sec1())
+            .map_err(|_| invalid("invalid endorsement key"))?;
+
+        key.verify_digest(Sha384::new_with_prefix(&self.raw[..SIGNED_LEN]), &signature)
+            .map_err(|_| invalid("the report is not signed by the endorsement key"))
+    }
+
+    pub fn tcb(&self, kind: TcbKind, product: SevProduct) -> Tcb {
+        let mut bytes = [0u8; 8];
+        bytes.copy_from_slice(&self.raw[kind.offset()..kind.offset() + 8]);
+        Tcb::from_bytes(bytes, product)
+    }
+}
+
+impl Deref for SnpReport {
+    type Target = AttestationReport;
+
+    fn deref(&self) -> &Self::Target {
+        &self.report
+    }
+}
+
+fn read_u32(raw: &[u8], offset: usize) -> u32 {
+    let mut bytes = [0u8; 4];
+    bytes.copy_from_slice(&raw[offset..offset + 4]);
+    u32::from_le_bytes(bytes)
+}
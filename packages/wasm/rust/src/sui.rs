@@ -0,0 +1,206 @@
+//! Sui intent messages, serialized signatures and addresses of secp256k1 and secp256r1 keys
+
+use base64_light::{base64_decode, base64_encode_bytes};
+use blake2::{digest::consts::U32, Blake2b};
+use elliptic_curve::{scalar::IsHigh as _, sec1::ToEncodedPoint};
+use js_sys::Uint8Array;
+use k256::{ecdsa::signature::hazmat::PrehashVerifier, Secp256k1};
+use p256::NistP256;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tsify::Tsify;
+use wasm_bindgen::prelude::*;
+
+use crate::abi::{from_js, into_uint8array, JsResult};
+use crate::ecdsa::{Ecdsa, EcdsaSignature, EcdsaVariant};
+use crate::error::LitCryptoError;
+
+type Blake2b256 = Blake2b<U32>;
+
+const SECP256K1_FLAG: u8 = 0x01;
+const SECP256R1_FLAG: u8 = 0x02;
+
+/// Flag, `r ‖ s` and compressed public key
+const SERIALIZED_SIGNATURE_LENGTH: usize = 1 + 64 + 33;
+
+/// What an intent message is signed for, the Sui app and intent version 0 are implied
+#[derive(Tsify, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[tsify(from_wasm_abi)]
+pub enum IntentScope {
+    TransactionData,
+    TransactionEffects,
+    CheckpointSummary,
+    PersonalMessage,
+}
+
+impl IntentScope {
+    /// `scope ‖ version ‖ app id` followed by the message, personal messages being BCS `vector<u8>`
+    pub fn intent_message(self, message: &[u8]) -> Vec<u8> {
+        let mut bytes = vec![self as u8, 0, 0];
+        if self == Self::PersonalMessage {
+            encode_uleb128(message.len() as u64, &mut bytes);
+        }
+        bytes.extend_from_slice(message);
+        bytes
+    }
+}
+
+fn encode_uleb128(mut value: u64, bytes: &mut Vec<u8>) {
+    while value >= 0x80 {
+        bytes.push(value as u8 | 0x80);
+        value >>= 7;
+    }
+    bytes.push(value as u8);
+}
+
+/// An intent message, its BLAKE2b-256 digest, and the SHA-256 of the digest to pass as `message_hash`
+#[derive(Tsify, Serialize, Debug, Clone)]
+#[tsify(into_wasm_abi)]
+#[serde(rename_all = "camelCase")]
+pub struct SuiSignBytes {
+    #[tsify(type = "Uint8Array")]
+    #[serde(with = "serde_bytes")]
+    pub intent_message: Vec<u8>,
+    #[tsify(type = "Uint8Array")]
+    #[serde(with = "serde_bytes")]
+    pub digest: Vec<u8>,
+    #[tsify(type = "Uint8Array")]
+    #[serde(with = "serde_bytes")]
+    pub hash: Vec<u8>,
+}
+
+impl SuiSignBytes {
+    fn new(scope: IntentScope, message: &[u8]) -> Self {
+        let intent_message = scope.intent_message(message);
+        let digest = Blake2b256::digest(&intent_message).to_vec();
+        let hash = Sha256::digest(&digest).to_vec();
+        Self {
+            intent_message,
+            digest,
+            hash,
+        }
+    }
+}
+
+fn unsupported(variant: EcdsaVariant) -> LitCryptoError {
+    LitCryptoError::SerializedSignature(format!("{variant:?} has no Sui signature scheme"))
+}
+
+fn flag(variant: EcdsaVariant) -> JsResult<u8> {
+    match variant {
+        EcdsaVariant::K256 => Ok(SECP256K1_FLAG),
+        EcdsaVariant::P256 => Ok(SECP256R1_FLAG),
+        EcdsaVariant::P384 => Err(unsupported(variant)),
+    }
+}
+
+fn compressed(variant: EcdsaVariant, public_key: &[u8]) -> JsResult<Vec<u8>> {
+    let point = match variant {
+        EcdsaVariant::K256 => k256::PublicKey::from_sec1_bytes(public_key)
+            .map(|public_key| public_key.to_encoded_point(true).as_bytes().to_vec()),
+        EcdsaVariant::P256 => p256::PublicKey::from_sec1_bytes(public_key)
+            .map(|public_key| public_key.to_encoded_point(true).as_bytes().to_vec()),
+        EcdsaVariant::P384 => return Err(unsupported(variant)),
+    };
+    point.map_err(|_| LitCryptoError::InvalidPoint)
+}
+
+fn address(flag: u8, compressed: &[u8]) -> String {
+    let hash = Blake2b256::new()
+        .chain_update([flag])
+        .chain_update(compressed)
+        .finalize();
+    format!("0x{}", hex::encode(hash))
+}
+
+/// Intent message of a BCS transaction or a personal message and the hashes to sign
+#[wasm_bindgen(js_name = "suiSignBytes")]
+pub fn sui_sign_bytes(scope: IntentScope, message: Uint8Array) -> JsResult<SuiSignBytes> {
+    let message = from_js::<Vec<u8>>(message)?;
+    Ok(SuiSignBytes::new(scope, &message))
+}
+
+/// Digest identifying BCS transaction data, `blake2b256("TransactionData::" ‖ bytes)`
+#[wasm_bindgen(js_name = "suiTransactionDigest")]
+pub fn sui_transaction_digest(transaction_data: Uint8Array) -> JsResult<Uint8Array> {
+    let transaction_data = from_js::<Vec<u8>>(transaction_data)?;
+    into_uint8array(
+        Blake2b256::new()
+            .chain_update(b"TransactionData::")
+            .chain_update(transaction_data)
+            .finalize(),
+    )
+}
+
+/// Base64 serialized signature `flag ‖ r ‖ s ‖ compressed key` of a combined signature, with a low `s`
+#[wasm_bindgen(js_name = "suiSignature")]
+pub fn sui_signature(
+    variant: EcdsaVariant,
+    signature: EcdsaSignature,
+    public_key: Uint8Array,
+) -> JsResult<String> {
+    let public_key = compressed(variant, &from_js::<Vec<u8>>(public_key)?)?;
+    let (rs, _) = match variant {
+        EcdsaVariant::K256 => Ecdsa::<Secp256k1>::low_s_signature(signature)?,
+        EcdsaVariant::P256 => Ecdsa::<NistP256>::low_s_signature(signature)?,
+        EcdsaVariant::P384 => return Err(unsupported(variant)),
+    };
+
+    Ok(base64_encode_bytes(
+        &[&[flag(variant)?][..], &rs, &public_key].concat(),
+    ))
+}
+
+/// Sui address of a public key, `blake2b256(flag ‖ compressed key)`
+#[wasm_bindgen(js_name = "suiAddress")]
+pub fn sui_address(variant: EcdsaVariant, public_key: Uint8Array) -> JsResult<String> {
+    let public_key = compressed(variant, &from_js::<Vec<u8>>(public_key)?)?;
+    Ok(address(flag(variant)?, &public_key))
+}
+
+/// Verifies a base64 serialized signature of a message as Sui validators do, high `s` values are rejected.
+/// Returns the address of the signer.
+#[wasm_bindgen(js_name = "suiVerify")]
+pub fn sui_verify(scope: IntentScope, message: Uint8Array, signature: String) -> JsResult<String> {
+    let message = from_js::<Vec<u8>>(message)?;
+    let serialized = base64_decode(&signature);
+    if serialized.len() != SERIALIZED_SIGNATURE_LENGTH {
+        return Err(LitCryptoError::SerializedSignature(format!(
+            "expected {SERIALIZED_SIGNATURE_LENGTH} bytes, got {}",
+            serialized.len()
+        )));
+    }
+    let (flag, rs, public_key) = (serialized[0], &serialized[1..65], &serialized[65..]);
+    let hash = Sha256::digest(SuiSignBytes::new(scope, &message).digest);
+
+    let verified = match flag {
+        SECP256K1_FLAG => {
+            let public_key = k256::ecdsa::VerifyingKey::from_sec1_bytes(public_key)
+                .map_err(|_| LitCryptoError::InvalidPoint)?;
+            let signature = k256::ecdsa::Signature::from_slice(rs)
+                .map_err(|_| LitCryptoError::InvalidSignature)?;
+            if signature.s().is_high().into() {
+                return Err(LitCryptoError::InvalidSignature);
+            }
+            public_key.verify_prehash(&hash, &signature)
+        }
+        SECP256R1_FLAG => {
+            let public_key = p256::ecdsa::VerifyingKey::from_sec1_bytes(public_key)
+                .map_err(|_| LitCryptoError::InvalidPoint)?;
+            let signature = p256::ecdsa::Signature::from_slice(rs)
+                .map_err(|_| LitCryptoError::InvalidSignature)?;
+            if signature.s().is_high().into() {
+                return Err(LitCryptoError::InvalidSignature);
+            }
+            public_key.verify_prehash(&hash, &signature)
+        }
+        _ => {
+            return Err(LitCryptoError::SerializedSignature(format!(
+                "unsupported signature scheme flag {flag:#04x}"
+            )))
+        }
+    };
+    verified.map_err(|_| LitCryptoError::SignatureMismatch)?;
+
+    Ok(address(flag, public_key))
+}
@@ -0,0 +1,236 @@
+//! Intel certificates: the PCK chain of the quote, the TCB signing chain of the collateral
+//! and the SGX extensions of the PCK certificate
+
+use p256::ecdsa::signature::Verifier;
+use p256::ecdsa::{Signature, VerifyingKey};
+use x509_cert::der::asn1::{ObjectIdentifier, OctetStringRef};
+use x509_cert::der::{Any, Decode, DecodePem, Encode, Reader, SliceReader, Tagged};
+use x509_cert::Certificate;
+
+use crate::abi::JsResult;
+use crate::error::LitCryptoError;
+use crate::x509::{check_validity, Crl};
+
+/// ecdsa-with-SHA256, which Intel signs its certificates with
+pub const ECDSA_WITH_SHA256_OID: ObjectIdentifier =
+    ObjectIdentifier::new_unwrap("1.2.840.10045.4.3.2");
+
+pub const SGX_EXTENSIONS_OID: ObjectIdentifier =
+    ObjectIdentifier::new_unwrap("1.2.840.113741.1.13.1");
+pub const SGX_TCB_OID: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.2.840.113741.1.13.1.2");
+pub const SGX_PCE_ID_OID: ObjectIdentifier =
+    ObjectIdentifier::new_unwrap("1.2.840.113741.1.13.1.3");
+pub const SGX_FMSPC_OID: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.2.840.113741.1.13.1.4");
+/// PCESVN within the TCB extension, after the 16 CPUSVN components
+pub const SGX_PCESVN_ARC: u32 = 17;
+
+/// What the PCK certificate says about the platform, matched against the TCB info
+#[derive(Debug, Clone)]
+pub struct PckExtensions {
+    pub fmspc: Vec<u8>,
+    pub pce_id: Vec<u8>,
+    pub sgx_tcb_components: [u8; 16],
+    pub pce_svn: u16,
+}
+
+/// Accepts DER or PEM
+pub fn parse_certificate(bytes: &[u8]) -> JsResult<Certificate> {
+    let cert = if bytes.starts_with(b"-----BEGIN") {
+        Certificate::from_pem(bytes)
+    } else {
+        Certificate::from_der(bytes)
+    };
+    cert.map_err(|e| LitCryptoError::Deserialize(e.to_string()))
+}
+
+pub fn parse_pem_chain(bytes: &[u8]) -> JsResult<Vec<Certificate>> {
+    Certificate::load_pem_chain(bytes).map_err(|e| LitCryptoError::Deserialize(e.to_string()))
+}
+
+/// Verifies that each certificate is signed by the next one and the last one by the root,
+/// and that all of them are valid at `time`. Each certificate is checked against the CRL
+/// at its position in `crls` when given, which its issuer must have signed.
+/// A copy of the root at the end of the chain is skipped, it is only trusted as given
+pub fn verify_chain(
+    names: &[&str],
+    chain: &[Certificate],
+    root: &Certificate,
+    crls: &[Option<&Crl>],
+    time: u64,
+) -> JsResult<()> {
+    let chain = match chain.split_last() {
+        Some((last, rest)) if last == root => rest,
+        _ => chain,
+    };
+    if chain.is_empty() {
+        return Err(LitCryptoError::AttestationChain(
+            "no certificate below the root".to_string(),
+        ));
+    }
+
+    let name = |i: usize| {
+        if i == chain.len() {
+            "Intel root"
+        } else {
+            names.get(i).copied().unwrap_or("intermediate")
+        }
+    };
+    let issuers = chain.iter().skip(1).chain(std::iter::once(root));
+    for (i, (cert, issuer)) in chain.iter().zip(issuers).enumerate() {
+        verify_signed_by(name(i), cert, issuer)?;
+        check_validity(name(i), cert, time)?;
+        if let Some(crl) = crls.get(i).copied().flatten() {
+            crl.verify(
+                name(i + 1),
+                issuer,
+                time,
+                |algorithm, message, signature| {
+                    verify_signature(issuer, algorithm, message, signature)
+                },
+            )?;
+            crl.check(name(i), cert)?;
+        }
+    }
+    check_validity("Intel root", root, time)
+}
+
+fn verify_signed_by(name: &str, cert: &Certificate, issuer: &Certificate) -> JsResult<()> {
+    let invalid = |reason: String| LitCryptoError::AttestationChain(format!("{name}: {reason}"));
+
+    if cert.tbs_certificate.issuer != issuer.tbs_certificate.subject {
+        return Err(invalid(format!(
+            "issued by {}, not {}",
+            cert.tbs_certificate.issuer, issuer.tbs_certificate.subject
+        )));
+    }
+
+    let message = cert
+        .tbs_certificate
+        .to_der()
+        .map_err(|e| LitCryptoError::Serialize(e.to_string()))?;
+    verify_signature(
+        issuer,
+        &cert.signature_algorithm.oid,
+        &message,
+        cert.signature.raw_bytes(),
+    )
+    .map_err(invalid)
+}
+
+/// Verifies an ECDSA P-256 signature of the issuer, of a certificate or a CRL
+fn verify_signature(
+    issuer: &Certificate,
+    algorithm: &ObjectIdentifier,
+    message: &[u8],
+    signature: &[u8],
+) -> Result<(), String> {
+    if *algorithm != ECDSA_WITH_SHA256_OID {
+        return Err(format!("unsupported signature algorithm {algorithm}"));
+    }
+
+    let signature =
+        Signature::from_der(signature).map_err(|e| format!("invalid signature: {e}"))?;
+    public_key(issuer)
+        .map_err(|e| e.to_string())?
+        .verify(message, &signature)
+        .map_err(|_| format!("not signed by {}", issuer.tbs_certificate.subject))
+}
+
+pub fn public_key(cert: &Certificate) -> JsResult<VerifyingKey> {
+    VerifyingKey::from_sec1_bytes(
+        cert.tbs_certificate
+            .subject_public_key_info
+            .subject_public_key
+            .raw_bytes(),
+    )
+    .map_err(|e| LitCryptoError::AttestationChain(format!("invalid P-256 public key: {e}")))
+}
+
+impl PckExtensions {
+    pub fn parse(pck: &Certificate) -> JsResult<Self> {
+        let invalid = |reason: &str| {
+            LitCryptoError::AttestationChain(format!("invalid PCK SGX extensions: {reason}"))
+        };
+        let der = |e: x509_cert::der::Error| invalid(&e.to_string());
+
+        let extension = pck
+            .tbs_certificate
+            .extensions
+            .iter()
+            .flatten()
+            .find(|ext| ext.extn_id == SGX_EXTENSIONS_OID)
+            .ok_or_else(|| invalid("missing"))?;
+
+        let (mut fmspc, mut pce_id, mut tcb) = (None, None, None);
+        let extensions = Any::from_der(extension.extn_value.as_bytes()).map_err(der)?;
+        for (oid, value) in oid_values(&extensions).map_err(der)? {
+            match oid {
+                SGX_FMSPC_OID => fmspc = Some(octets(&value).map_err(der)?),
+                SGX_PCE_ID_OID => pce_id = Some(octets(&value).map_err(der)?),
+                SGX_TCB_OID => tcb = Some(value),
+                _ => {}
+            }
+        }
+
+        let mut sgx_tcb_components = [0u8; 16];
+        let mut pce_svn = None;
+        let tcb = tcb.ok_or_else(|| invalid("no TCB"))?;
+        for (oid, value) in oid_values(&tcb).map_err(der)? {
+            let Some(arc) = oid.arcs().last() else {
+                continue;
+            };
+            if !oid.as_bytes().starts_with(SGX_TCB_OID.as_bytes()) {
+                continue;
+            }
+            match arc {
+                1..=16 => {
+                    let svn: u16 = value.decode_as().map_err(der)?;
+                    sgx_tcb_components[arc as usize - 1] =
+                        u8::try_from(svn).map_err(|_| invalid("component SVN above 255"))?;
+                }
+                SGX_PCESVN_ARC => pce_svn = Some(value.decode_as::<u16>().map_err(der)?),
+                _ => {}
+            }
+        }
+
+        Ok(Self {
+            fmspc: fmspc.ok_or_else(|| invalid("no FMSPC"))?,
+            pce_id: pce_id.ok_or_else(|| invalid("no PCE-ID"))?,
+            sgx_tcb_components,
+            pce_svn: pce_svn.ok_or_else(|| invalid("no PCESVN"))?,
+        })
+    }
+}
+
+/// The `SEQUENCE OF SEQUENCE { OBJECT IDENTIFIER, ANY }` the SGX extensions are made of
+fn oid_values(any: &Any) -> x509_cert::der::Result<Vec<(ObjectIdentifier, Any)>> {
+    sequence(any)?
+        .iter()
+        .map(|entry| {
+            let fields = sequence(entry)?;
+            let mut fields = fields.into_iter();
+            let oid = fields
+                .next()
+                .ok_or_else(|| x509_cert::der::Tag::ObjectIdentifier.value_error())?
+                .decode_as::<ObjectIdentifier>()?;
+            let value = fields
+                .next()
+                .ok_or_else(|| x509_cert::der::Tag::Sequence.value_error())?;
+            Ok((oid, value))
+        })
+        .collect()
+}
+
+fn sequence(any: &Any) -> x509_cert::der::Result<Vec<Any>> {
+    any.tag().assert_eq(x509_cert::der::Tag::Sequence)?;
+    let mut reader = SliceReader::new(any.value())?;
+    let mut items = Vec::new();
+    while !reader.is_finished() {
+        items.push(reader.decode::<Any>()?);
+    }
+    Ok(items)
+}
+
+fn octets(any: &Any) -> x509_cert::der::Result<Vec<u8>> {
+    Ok(any.decode_as::<OctetStringRef>()?.as_bytes().to_vec())
+}
@@ -0,0 +1,368 @@
+//! TCB info and QE identity, as served by the Intel Provisioning Certification Service
+//!
+//! Both are JSON documents signed by the Intel TCB signing key over the exact bytes of their body,
+//! so the body is kept raw until its signature is checked.
+
+use std::collections::BTreeMap;
+
+use p256::ecdsa::signature::Verifier;
+use p256::ecdsa::Signature;
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use serde_json::value::RawValue;
+use x509_cert::der::DateTime;
+use x509_cert::Certificate;
+
+use crate::abi::JsResult;
+use crate::error::LitCryptoError;
+
+use super::certs::{public_key, PckExtensions};
+use super::quote::{QeReport, TdReport};
+
+/// Status of a TCB level that needs nothing done
+pub const UP_TO_DATE: &str = "UpToDate";
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct TcbInfo {
+    pub id: String,
+    pub version: u32,
+    pub issue_date: String,
+    pub next_update: String,
+    pub fmspc: String,
+    pub pce_id: String,
+    pub tdx_module: Option<TdxModule>,
+    #[serde(default)]
+    pub tdx_module_identities: Vec<TdxModuleIdentity>,
+    pub tcb_levels: Vec<TcbLevel>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct TdxModule {
+    pub mrsigner: String,
+    pub attributes: String,
+    pub attributes_mask: String,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct TdxModuleIdentity {
+    pub id: String,
+    pub mrsigner: String,
+    pub attributes: String,
+    pub attributes_mask: String,
+    pub tcb_levels: Vec<IsvTcbLevel>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct TcbLevel {
+    pub tcb: Tcb,
+    pub tcb_status: String,
+    #[serde(default, rename = "advisoryIDs")]
+    pub advisory_ids: Vec<String>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct Tcb {
+    pub sgxtcbcomponents: Vec<TcbComponent>,
+    pub pcesvn: u16,
+    #[serde(default)]
+    pub tdxtcbcomponents: Vec<TcbComponent>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct TcbComponent {
+    pub svn: u8,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct QeIdentity {
+    pub id: String,
+    pub issue_date: String,
+    pub next_update: String,
+    pub miscselect: String,
+    pub miscselect_mask: String,
+    pub attributes: String,
+    pub attributes_mask: String,
+    pub mrsigner: String,
+    pub isvprodid: u16,
+    pub tcb_levels: Vec<IsvTcbLevel>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct IsvTcbLevel {
+    pub tcb: IsvTcb,
+    pub tcb_status: String,
+    #[serde(default, rename = "advisoryIDs")]
+    pub advisory_ids: Vec<String>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct IsvTcb {
+    pub isvsvn: u16,
+}
+
+/// The TCB level a component matched, with Intel's advisories for it
+#[derive(Debug, Clone)]
+pub struct TcbMatch {
+    pub status: String,
+    pub advisory_ids: Vec<String>,
+}
+
+fn invalid(reason: impl std::fmt::Display) -> LitCryptoError {
+    LitCryptoError::Collateral(reason.to_string())
+}
+
+/// Parses `{"<key>": {...}, "signature": "<hex>"}` and checks the signature over the raw body
+pub fn parse_signed<T: DeserializeOwned>(
+    json: &str,
+    key: &str,
+    signer: &Certificate,
+) -> JsResult<T> {
+    let document: BTreeMap<String, &RawValue> =
+        serde_json::from_str(json).map_err(|e| invalid(format!("{key}: {e}")))?;
+    let body = document
+        .get(key)
+        .ok_or_else(|| invalid(format!("no {key}")))?;
+    let signature: String = document
+        .get("signature")
+        .map(|s| serde_json::from_str(s.get()))
+        .transpose()
+        .map_err(|e| invalid(format!("{key} signature: {e}")))?
+        .ok_or_else(|| invalid(format!("{key} is not signed")))?;
+
+    let signature = hex::decode(signature)
+        .ok()
+        .and_then(|s| Signature::from_slice(&s).ok())
+        .ok_or_else(|| invalid(format!("invalid {key} signature")))?;
+    public_key(signer)?
+        .verify(body.get().as_bytes(), &signature)
+        .map_err(|_| invalid(format!("{key} is not signed by the TCB signing key")))?;
+
+    serde_json::from_str(body.get()).map_err(|e| invalid(format!("{key}: {e}")))
+}
+
+/// Checks that `time` is between the issue date and the next update of the document
+pub fn check_dates(name: &str, issue_date: &str, next_update: &str, time: u64) -> JsResult<()> {
+    let issued = parse_date(issue_date)?;
+    let next = parse_date(next_update)?;
+    if time < issued {
+        return Err(invalid(format!(
+            "{name} is issued at {issued}, after {time}"
+        )));
+    }
+    if time > next {
+        return Err(invalid(format!(
+            "{name} expired at {next}, fetch a new one"
+        )));
+    }
+    Ok(())
+}
+
+/// Parses the `YYYY-MM-DDTHH:MM:SSZ` dates of the collateral into unix seconds
+fn parse_date(date: &str) -> JsResult<u64> {
+    let parse = || -> Option<u64> {
+        let date = date.strip_suffix('Z')?;
+        let (day, time) = date.split_once('T')?;
+        let mut day = day.splitn(3, '-').map(str::parse::<u16>);
+        // Fractional seconds are dropped
+        let time = time.split('.').next()?;
+        let mut time = time.splitn(3, ':').map(str::parse::<u8>);
+        let date_time = DateTime::new(
+            day.next()?.ok()?,
+            day.next()?.ok()? as u8,
+            day.next()?.ok()? as u8,
+            time.next()?.ok()?,
+            time.next()?.ok()?,
+            time.next()?.ok()?,
+        )
+        .ok()?;
+        Some(date_time.unix_duration().as_secs())
+    };
+    parse().ok_or_else(|| invalid(format!("invalid date {date}")))
+}
+
+impl TcbInfo {
+    /// Checks that the TCB info is for the platform of the PCK certificate
+    pub fn check_platform(&self, pck: &PckExtensions) -> JsResult<()> {
+        if self.id != "TDX" {
+            return Err(invalid(format!("TCB info is for {}, not TDX", self.id)));
+        }
+        if self.version != 3 {
+            return Err(invalid(format!(
+                "unsupported TCB info version {}",
+                self.version
+            )));
+        }
+        if !hex_eq(&self.fmspc, &pck.fmspc) {
+            return Err(invalid(format!(
+                "TCB info is for FMSPC {}, the PCK certificate is for {}",
+                self.fmspc,
+                hex::encode(&pck.fmspc)
+            )));
+        }
+        if !hex_eq(&self.pce_id, &pck.pce_id) {
+            return Err(invalid(format!(
+                "TCB info is for PCE {}, the PCK certificate is for {}",
+                self.pce_id,
+                hex::encode(&pck.pce_id)
+            )));
+        }
+        Ok(())
+    }
+
+    /// Finds the highest TCB level the platform and the TDX module are at.
+    /// The levels are sorted from the highest, the first one all components reach is the match
+    pub fn platform_level(&self, pck: &PckExtensions, td: &TdReport) -> JsResult<TcbMatch> {
+        // From TDX module 1.x on, its version is checked against the module identities instead
+        let skip = if td.tee_tcb_svn[1] > 0 { 2 } else { 0 };
+
+        self.tcb_levels
+            .iter()
+            .find(|level| {
+                let sgx = svns_reach(&pck.sgx_tcb_components, &level.tcb.sgxtcbcomponents, 0);
+                let tdx = svns_reach(&td.tee_tcb_svn, &level.tcb.tdxtcbcomponents, skip);
+                sgx && tdx && pck.pce_svn >= level.tcb.pcesvn
+            })
+            .map(|level| TcbMatch {
+                status: level.tcb_status.clone(),
+                advisory_ids: level.advisory_ids.clone(),
+            })
+            .ok_or_else(|| invalid("the platform is below every TCB level"))
+    }
+
+    /// Checks the TDX module signer and attributes, and for modules 1.x and later,
+    /// finds the TCB level of the module identity
+    pub fn module_level(&self, td: &TdReport) -> JsResult<Option<TcbMatch>> {
+        let seam_attributes = td.seam_attributes.to_le_bytes();
+        let major = td.tee_tcb_svn[1];
+        if major == 0 {
+            if let Some(module) = &self.tdx_module {
+                check_module(
+                    "TDX module",
+                    &module.mrsigner,
+                    &module.attributes,
+                    &module.attributes_mask,
+                    td,
+                    &seam_attributes,
+                )?;
+            }
+            return Ok(None);
+        }
+
+        let id = format!("TDX_{major:02X}");
+        let identity = self
+            .tdx_module_identities
+            .iter()
+            .find(|identity| identity.id.eq_ignore_ascii_case(&id))
+            .ok_or_else(|| invalid(format!("TCB info has no identity for module {id}")))?;
+        check_module(
+            &id,
+            &identity.mrsigner,
+            &identity.attributes,
+            &identity.attributes_mask,
+            td,
+            &seam_attributes,
+        )?;
+
+        let isv_svn = u16::from(td.tee_tcb_svn[0]);
+        isv_level(&identity.tcb_levels, isv_svn)
+            .map(Some)
+            .ok_or_else(|| invalid(format!("module {id} is below every TCB level")))
+    }
+}
+
+impl QeIdentity {
+    /// Checks the QE report against the identity of the TDX quoting enclave and finds its TCB level
+    pub fn qe_level(&self, qe: &QeReport) -> JsResult<TcbMatch> {
+        if self.id != "TD_QE" {
+            return Err(invalid(format!(
+                "QE identity is for {}, not TD_QE",
+                self.id
+            )));
+        }
+        if !hex_eq(&self.mrsigner, &qe.mr_signer) {
+            return Err(invalid("the quoting enclave is not signed by Intel"));
+        }
+        if self.isvprodid != qe.isv_prod_id {
+            return Err(invalid(format!(
+                "the quoting enclave is product {}, not {}",
+                qe.isv_prod_id, self.isvprodid
+            )));
+        }
+        if !masked_eq(
+            &self.miscselect,
+            &self.miscselect_mask,
+            &qe.misc_select.to_le_bytes(),
+        )? {
+            return Err(invalid("unexpected MISCSELECT of the quoting enclave"));
+        }
+        if !masked_eq(&self.attributes, &self.attributes_mask, &qe.attributes)? {
+            return Err(invalid("unexpected attributes of the quoting enclave"));
+        }
+
+        isv_level(&self.tcb_levels, qe.isv_svn)
+            .ok_or_else(|| invalid("the quoting enclave is below every TCB level"))
+    }
+}
+
+fn check_module(
+    id: &str,
+    mrsigner: &str,
+    attributes: &str,
+    attributes_mask: &str,
+    td: &TdReport,
+    seam_attributes: &[u8],
+) -> JsResult<()> {
+    if !hex_eq(mrsigner, &td.mr_signer_seam) {
+        return Err(invalid(format!("{id} is not signed by Intel")));
+    }
+    if !masked_eq(attributes, attributes_mask, seam_attributes)? {
+        return Err(invalid(format!("unexpected SEAM attributes of {id}")));
+    }
+    Ok(())
+}
+
+fn isv_level(levels: &[IsvTcbLevel], isv_svn: u16) -> Option<TcbMatch> {
+    levels
+        .iter()
+        .find(|level| isv_svn >= level.tcb.isvsvn)
+        .map(|level| TcbMatch {
+            status: level.tcb_status.clone(),
+            advisory_ids: level.advisory_ids.clone(),
+        })
+}
+
+fn svns_reach(actual: &[u8; 16], level: &[TcbComponent], skip: usize) -> bool {
+    level
+        .iter()
+        .zip(actual)
+        .skip(skip)
+        .all(|(component, svn)| *svn >= component.svn)
+}
+
+fn hex_eq(expected: &str, actual: &[u8]) -> bool {
+    expected.eq_ignore_ascii_case(&hex::encode(actual))
+}
+
+/// Compares the bits of `actual` the hex encoded mask selects
+fn masked_eq(expected: &str, mask: &str, actual: &[u8]) -> JsResult<bool> {
+    let expected = hex::decode(expected).map_err(invalid)?;
+    let mask = hex::decode(mask).map_err(invalid)?;
+    if expected.len() != actual.len() || mask.len() != actual.len() {
+        return Err(invalid(format!(
+            "expected {} bytes masks, got {}",
+            actual.len(),
+            mask.len()
+        )));
+    }
+    Ok(expected
+        .iter()
+        .zip(&mask)
+        .zip(actual)
+        .all(|((expected, mask), actual)| expected & mask == actual & mask))
+}
@@ -0,0 +1,64 @@
+//! The fields of a TDX quote in a form JS can display
+
+use serde::Serialize;
+use tsify::Tsify;
+
+use super::quote::{TdxQuote, TD_ATTRIBUTES_DEBUG};
+
+/// Byte fields are hex encoded, in the order they appear in the quote
+#[derive(Tsify, Serialize, Debug, Clone)]
+#[tsify(into_wasm_abi)]
+#[serde(rename_all = "camelCase")]
+pub struct TdxQuoteInfo {
+    pub version: u16,
+    pub qe_vendor_id: String,
+    pub tee_tcb_svn: String,
+    pub mr_seam: String,
+    pub mr_signer_seam: String,
+    pub seam_attributes: String,
+    pub td_attributes: String,
+    pub debug: bool,
+    pub xfam: String,
+    pub mr_td: String,
+    pub mr_config_id: String,
+    pub mr_owner: String,
+    pub mr_owner_config: String,
+    pub rtmrs: Vec<String>,
+    pub report_data: String,
+    /// Only in TDX 1.5 quotes
+    #[tsify(optional)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tee_tcb_svn_2: Option<String>,
+    #[tsify(optional)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mr_service_td: Option<String>,
+    /// x || y of the P-256 key that signed the quote
+    pub attestation_key: String,
+}
+
+impl TdxQuoteInfo {
+    pub fn new(quote: &TdxQuote) -> Self {
+        let td = &quote.body;
+
+        Self {
+            version: quote.version,
+            qe_vendor_id: hex::encode(quote.qe_vendor_id),
+            tee_tcb_svn: hex::encode(td.tee_tcb_svn),
+            mr_seam: hex::encode(&td.mr_seam),
+            mr_signer_seam: hex::encode(&td.mr_signer_seam),
+            seam_attributes: hex::encode(td.seam_attributes.to_le_bytes()),
+            td_attributes: hex::encode(td.td_attributes.to_le_bytes()),
+            debug: td.td_attributes & TD_ATTRIBUTES_DEBUG != 0,
+            xfam: hex::encode(td.xfam.to_le_bytes()),
+            mr_td: hex::encode(&td.mr_td),
+            mr_config_id: hex::encode(&td.mr_config_id),
+            mr_owner: hex::encode(&td.mr_owner),
+            mr_owner_config: hex::encode(&td.mr_owner_config),
+            rtmrs: td.rtmrs.iter().map(hex::encode).collect(),
+            report_data: hex::encode(td.report_data),
+            tee_tcb_svn_2: td.tee_tcb_svn_2.map(hex::encode),
+            mr_service_td: td.mr_service_td.as_ref().map(hex::encode),
+            attestation_key: hex::encode(quote.attestation_key),
+        }
+    }
+}
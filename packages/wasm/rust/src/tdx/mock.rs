@@ -0,0 +1,662 @@
+//! Throwaway Intel certificate chains, and TDX quotes and PCS collateral signed by them, for tests only.
+//!
+//! Nothing here is trusted by default: verification only accepts the quotes when given
+//! the mock root as `intel_root`.
+
+use js_sys::Uint8Array;
+use p256::ecdsa::signature::Signer;
+use p256::ecdsa::{Signature, SigningKey};
+use p256::pkcs8::EncodePublicKey as _;
+use serde::Deserialize;
+use serde_json::json;
+use sha2::{Digest, Sha256};
+use tsify::Tsify;
+use wasm_bindgen::prelude::*;
+use x509_cert::crl::{CertificateList, RevokedCert, TbsCertList};
+use x509_cert::der::asn1::{BitString, ObjectIdentifier, OctetString, OctetStringRef};
+use x509_cert::der::pem::LineEnding;
+use x509_cert::der::{Any, DateTime, Decode, EncodePem, Tag};
+use x509_cert::ext::Extension;
+use x509_cert::name::Name;
+use x509_cert::serial_number::SerialNumber;
+use x509_cert::spki::{AlgorithmIdentifierOwned, SubjectPublicKeyInfoOwned};
+use x509_cert::time::Validity;
+use x509_cert::{Certificate, TbsCertificate, Version};
+
+use crate::abi::{into_uint8array, JsResult};
+use crate::error::LitCryptoError;
+use crate::x509::mock::{ca_extension, encode, hex_field, name, time, validity};
+
+use super::certs::{
+    ECDSA_WITH_SHA256_OID, SGX_EXTENSIONS_OID, SGX_FMSPC_OID, SGX_PCESVN_ARC, SGX_PCE_ID_OID,
+    SGX_TCB_OID,
+};
+use super::collateral::UP_TO_DATE;
+use super::quote::{
+    ATT_KEY_TYPE_ECDSA_P256, BODY_TYPE_TDX_15, BODY_V5_SIZE, CERT_DATA_PCK_CHAIN,
+    CERT_DATA_QE_REPORT, QE_REPORT_SIZE, TD_ATTRIBUTES_DEBUG, TEE_TYPE_TDX,
+};
+use super::TdxCollateral;
+
+const DAY: u64 = 24 * 60 * 60;
+
+const FMSPC: [u8; 6] = [0x00, 0x80, 0x6f, 0x05, 0x00, 0x00];
+const PCE_ID: [u8; 2] = [0x00, 0x00];
+/// The SGX TCB of the PCK certificate, at the up to date level of the TCB info
+const SGX_TCB_COMPONENTS: [u8; 16] = [2; 16];
+const PCE_SVN: u16 = 13;
+/// TDX module SVN 3 of major version 1, then the TDX TCB components
+const TEE_TCB_SVN: &str = "03010200000000000000000000000000";
+/// Of module 1.x, whose signer is all zeros
+const TDX_MODULE_ID: &str = "TDX_01";
+
+const INTEL_QE_VENDOR_ID: &str = "939a7233f79c4ca9940a0db3957f0607";
+const QE_MR_SIGNER: &str = "dc9e2a7c6f948f17474e34a7fc43ed030f7c1563f1babddf6340c82e0e54a8c5";
+const QE_ISV_PROD_ID: u16 = 2;
+const QE_ISV_SVN: u16 = 4;
+/// INIT and MODE64BIT
+const QE_ATTRIBUTES: &str = "11000000000000000000000000000000";
+const QE_AUTH_DATA_LEN: u8 = 32;
+
+/// The advisory of the out of date levels of the collateral
+const OUT_OF_DATE_ADVISORY: &str = "INTEL-SA-00837";
+
+#[derive(Tsify, Deserialize, Debug, Clone, Default)]
+#[tsify(from_wasm_abi)]
+#[serde(rename_all = "camelCase", default)]
+pub struct TdxMockChainOptions {
+    /// Start of the validity period of the PCK certificate in unix seconds, a day ago when not given.
+    /// The root, PCK CA and TCB signing certificates are valid from a day ago for ten years
+    #[tsify(optional)]
+    pub not_before: Option<u64>,
+    /// End of the validity period of the PCK certificate in unix seconds, in a year when not given
+    #[tsify(optional)]
+    pub not_after: Option<u64>,
+}
+
+/// Fields of a mock quote, the rest are zero. Byte fields are hex encoded
+#[derive(Tsify, Deserialize, Debug, Clone, Default)]
+#[tsify(from_wasm_abi)]
+#[serde(rename_all = "camelCase", default)]
+pub struct TdxMockQuote {
+    /// 4 when not given, version 5 quotes have a TDX 1.5 body
+    #[tsify(optional)]
+    pub version: Option<u16>,
+    #[tsify(optional, type = "Uint8Array")]
+    #[serde(with = "serde_bytes")]
+    pub report_data: Option<Vec<u8>>,
+    #[tsify(optional)]
+    pub mr_td: Option<String>,
+    /// RTMR0 to RTMR3, missing ones are zero
+    #[tsify(optional)]
+    pub rtmrs: Option<Vec<String>>,
+    #[tsify(optional)]
+    pub mr_config_id: Option<String>,
+    #[tsify(optional)]
+    pub mr_owner: Option<String>,
+    #[tsify(optional)]
+    pub mr_owner_config: Option<String>,
+    pub debug: bool,
+    /// TDX module SVN, its major version and the TDX TCB components, at the up to date level when not given
+    #[tsify(optional)]
+    pub tee_tcb_svn: Option<String>,
+    /// ISVSVN of the quoting enclave, at the up to date level when not given
+    #[tsify(optional)]
+    pub qe_isv_svn: Option<u16>,
+    /// Replaces the report data of the QE report, which then no longer commits to the attestation key
+    #[tsify(optional, type = "Uint8Array")]
+    #[serde(with = "serde_bytes")]
+    pub qe_report_data: Option<Vec<u8>>,
+}
+
+/// The TCB info has an up to date level and an `OutOfDate` one below it for the platform,
+/// the TDX module and the quoting enclave
+#[derive(Tsify, Deserialize, Debug, Clone, Default)]
+#[tsify(from_wasm_abi)]
+#[serde(rename_all = "camelCase", default)]
+pub struct TdxMockCollateralOptions {
+    /// Status of the highest platform level, `UpToDate` when not given
+    #[tsify(optional)]
+    pub tcb_status: Option<String>,
+    /// Advisories of the highest platform level
+    pub advisory_ids: Vec<String>,
+    /// Status of the highest TDX module level, `UpToDate` when not given
+    #[tsify(optional)]
+    pub tdx_module_tcb_status: Option<String>,
+    /// Status of the highest quoting enclave level, `UpToDate` when not given
+    #[tsify(optional)]
+    pub qe_tcb_status: Option<String>,
+    /// FMSPC of the TCB info, the one of the PCK certificate when not given
+    #[tsify(optional)]
+    pub fmspc: Option<String>,
+    /// MRSIGNER of the QE identity, the one of the quotes when not given
+    #[tsify(optional)]
+    pub qe_mr_signer: Option<String>,
+    /// Unix seconds, an hour ago when not given
+    #[tsify(optional)]
+    pub issue_date: Option<u64>,
+    /// Unix seconds, in a month when not given
+    #[tsify(optional)]
+    pub next_update: Option<u64>,
+}
+
+#[derive(Tsify, Deserialize, Debug, Clone, Default)]
+#[tsify(from_wasm_abi)]
+#[serde(rename_all = "camelCase", default)]
+pub struct TdxMockCrlOptions {
+    /// Revokes the PCK certificate, in the CRL of the PCK CA
+    pub revoke_pck: bool,
+    /// Revokes the PCK CA, in the CRL of the root
+    pub revoke_pck_ca: bool,
+    /// Revokes the TCB signing certificate, in the CRL of the root
+    pub revoke_tcb_signing: bool,
+    /// Unix seconds, an hour ago when not given
+    #[tsify(optional)]
+    pub this_update: Option<u64>,
+    /// Unix seconds, in a day when not given
+    #[tsify(optional)]
+    pub next_update: Option<u64>,
+}
+
+/// A throwaway Intel root, PCK CA, PCK and TCB signing certificate, the keys that sign
+/// quotes, collateral and CRLs with them, and the attestation key of the quoting enclave
+#[wasm_bindgen(js_name = "TdxMockChain")]
+pub struct MockChain {
+    root_key: SigningKey,
+    pck_ca_key: SigningKey,
+    pck_key: SigningKey,
+    tcb_signing_key: SigningKey,
+    attestation_key: SigningKey,
+    root: Certificate,
+    pck_ca: Certificate,
+    pck: Certificate,
+    tcb_signing: Certificate,
+}
+
+#[wasm_bindgen(js_class = "TdxMockChain")]
+impl MockChain {
+    #[wasm_bindgen(constructor)]
+    pub fn new(options: Option<TdxMockChainOptions>) -> JsResult<MockChain> {
+        let options = options.unwrap_or_default();
+        let now = (js_sys::Date::now() / 1000.0) as u64;
+
+        let mut rng = rand::thread_rng();
+        let root_key = SigningKey::random(&mut rng);
+        let pck_ca_key = SigningKey::random(&mut rng);
+        let pck_key = SigningKey::random(&mut rng);
+        let tcb_signing_key = SigningKey::random(&mut rng);
+        let attestation_key = SigningKey::random(&mut rng);
+
+        let root_name = name("CN=Intel SGX Root CA,O=Lit Protocol Test")?;
+        let pck_ca_name = name("CN=Intel SGX PCK Platform CA,O=Lit Protocol Test")?;
+        let ca_validity = validity(now - DAY, now + 10 * 365 * DAY)?;
+
+        let root = sign_certificate(
+            &root_key,
+            1,
+            &root_name,
+            &root_name,
+            ca_validity,
+            &root_key,
+            Some(ca_extension()?),
+        )?;
+        let pck_ca = sign_certificate(
+            &root_key,
+            2,
+            &root_name,
+            &pck_ca_name,
+            ca_validity,
+            &pck_ca_key,
+            Some(ca_extension()?),
+        )?;
+        let tcb_signing = sign_certificate(
+            &root_key,
+            3,
+            &root_name,
+            &name("CN=Intel SGX TCB Signing,O=Lit Protocol Test")?,
+            ca_validity,
+            &tcb_signing_key,
+            None,
+        )?;
+        let pck = sign_certificate(
+            &pck_ca_key,
+            4,
+            &pck_ca_name,
+            &name("CN=Intel SGX PCK Certificate,O=Lit Protocol Test")?,
+            validity(
+                options.not_before.unwrap_or(now - DAY),
+                options.not_after.unwrap_or(now + 365 * DAY),
+            )?,
+            &pck_key,
+            Some(sgx_extension()?),
+        )?;
+
+        Ok(Self {
+            root_key,
+            pck_ca_key,
+            pck_key,
+            tcb_signing_key,
+            attestation_key,
+            root,
+            pck_ca,
+            pck,
+            tcb_signing,
+        })
+    }
+
+    /// The root (DER), pass it as `intel_root` to `tdxVerify`
+    pub fn root(&self) -> JsResult<Uint8Array> {
+        into_uint8array(encode(&self.root)?)
+    }
+
+    /// Creates a quote with the given fields, signed by the attestation key the PCK endorses
+    pub fn quote(&self, fields: Option<TdxMockQuote>) -> JsResult<Uint8Array> {
+        into_uint8array(self.sign_quote(&fields.unwrap_or_default())?)
+    }
+
+    /// Creates the TCB info and QE identity of the quotes, signed by the TCB signing key
+    pub fn collateral(&self, options: Option<TdxMockCollateralOptions>) -> JsResult<TdxCollateral> {
+        let options = options.unwrap_or_default();
+        let now = (js_sys::Date::now() / 1000.0) as u64;
+        let issue_date = pcs_date(options.issue_date.unwrap_or(now - 60 * 60))?;
+        let next_update = pcs_date(options.next_update.unwrap_or(now + 30 * DAY))?;
+        let status = |status: &Option<String>| status.as_deref().unwrap_or(UP_TO_DATE).to_string();
+        let svns = |svns: &[u8]| {
+            svns.iter()
+                .map(|svn| json!({ "svn": svn }))
+                .collect::<Vec<_>>()
+        };
+        let up_to_date_tdx = hex_field("teeTcbSvn", TEE_TCB_SVN, 16)?;
+        let mut out_of_date_tdx = up_to_date_tdx.clone();
+        out_of_date_tdx[2] -= 1;
+
+        let tcb_info = json!({
+            "id": "TDX",
+            "version": 3,
+            "issueDate": issue_date,
+            "nextUpdate": next_update,
+            "fmspc": options.fmspc.unwrap_or_else(|| hex::encode(FMSPC)),
+            "pceId": hex::encode(PCE_ID),
+            "tcbType": 0,
+            "tcbEvaluationDataNumber": 17,
+            "tdxModule": {
+                "mrsigner": hex::encode([0u8; 48]),
+                "attributes": "0000000000000000",
+                "attributesMask": "FFFFFFFFFFFFFFFF",
+            },
+            "tdxModuleIdentities": [{
+                "id": TDX_MODULE_ID,
+                "mrsigner": hex::encode([0u8; 48]),
+                "attributes": "0000000000000000",
+                "attributesMask": "FFFFFFFFFFFFFFFF",
+                "tcbLevels": [
+                    { "tcb": { "isvsvn": up_to_date_tdx[0] }, "tcbDate": issue_date, "tcbStatus": status(&options.tdx_module_tcb_status) },
+                    { "tcb": { "isvsvn": up_to_date_tdx[0] - 2 }, "tcbDate": issue_date, "tcbStatus": "OutOfDate", "advisoryIDs": [OUT_OF_DATE_ADVISORY] },
+                ],
+            }],
+            "tcbLevels": [
+                {
+                    "tcb": {
+                        "sgxtcbcomponents": svns(&SGX_TCB_COMPONENTS),
+                        "pcesvn": PCE_SVN,
+                        "tdxtcbcomponents": svns(&up_to_date_tdx),
+                    },
+                    "tcbDate": issue_date,
+                    "tcbStatus": status(&options.tcb_status),
+                    "advisoryIDs": options.advisory_ids,
+                },
+                {
+                    "tcb": {
+                        "sgxtcbcomponents": svns(&SGX_TCB_COMPONENTS.map(|svn| svn - 1)),
+                        "pcesvn": PCE_SVN - 2,
+                        "tdxtcbcomponents": svns(&out_of_date_tdx),
+                    },
+                    "tcbDate": issue_date,
+                    "tcbStatus": "OutOfDate",
+                    "advisoryIDs": [OUT_OF_DATE_ADVISORY],
+                },
+            ],
+        });
+        let qe_identity = json!({
+            "id": "TD_QE",
+            "version": 2,
+            "issueDate": issue_date,
+            "nextUpdate": next_update,
+            "tcbEvaluationDataNumber": 17,
+            "miscselect": "00000000",
+            "miscselectMask": "FFFFFFFF",
+            "attributes": QE_ATTRIBUTES,
+            "attributesMask": "FBFFFFFFFFFFFFFF0000000000000000",
+            "mrsigner": options.qe_mr_signer.unwrap_or_else(|| QE_MR_SIGNER.to_string()),
+            "isvprodid": QE_ISV_PROD_ID,
+            "tcbLevels": [
+                { "tcb": { "isvsvn": QE_ISV_SVN }, "tcbDate": issue_date, "tcbStatus": status(&options.qe_tcb_status) },
+                { "tcb": { "isvsvn": QE_ISV_SVN - 2 }, "tcbDate": issue_date, "tcbStatus": "OutOfDate", "advisoryIDs": [OUT_OF_DATE_ADVISORY] },
+            ],
+        });
+
+        Ok(TdxCollateral {
+            tcb_info: self.sign_document("tcbInfo", &tcb_info),
+            qe_identity: self.sign_document("enclaveIdentity", &qe_identity),
+            issuer_chain: [pem(&self.tcb_signing)?, pem(&self.root)?].concat(),
+            pck_crl: None,
+            root_ca_crl: None,
+        })
+    }
+
+    /// Creates the CRL of the PCK CA, pass it as `pckCrl` in the collateral
+    #[wasm_bindgen(js_name = "pckCrl")]
+    pub fn pck_crl(&self, options: Option<TdxMockCrlOptions>) -> JsResult<Uint8Array> {
+        let options = options.unwrap_or_default();
+        let revoked = [(options.revoke_pck, &self.pck)];
+        into_uint8array(sign_crl(
+            &self.pck_ca_key,
+            &self.pck_ca,
+            &revoked,
+            &options,
+        )?)
+    }
+
+    /// Creates the CRL of the root, pass it as `rootCaCrl` in the collateral
+    #[wasm_bindgen(js_name = "rootCaCrl")]
+    pub fn root_ca_crl(&self, options: Option<TdxMockCrlOptions>) -> JsResult<Uint8Array> {
+        let options = options.unwrap_or_default();
+        let revoked = [
+            (options.revoke_pck_ca, &self.pck_ca),
+            (options.revoke_tcb_signing, &self.tcb_signing),
+        ];
+        into_uint8array(sign_crl(&self.root_key, &self.root, &revoked, &options)?)
+    }
+}
+
+impl MockChain {
+    fn sign_quote(&self, fields: &TdxMockQuote) -> JsResult<Vec<u8>> {
+        let version = fields.version.unwrap_or(4);
+        let mut quote = Vec::new();
+
+        quote.extend_from_slice(&version.to_le_bytes());
+        quote.extend_from_slice(&ATT_KEY_TYPE_ECDSA_P256.to_le_bytes());
+        quote.extend_from_slice(&TEE_TYPE_TDX.to_le_bytes());
+        quote.extend_from_slice(&[0; 4]);
+        quote.extend_from_slice(&hex_field("qeVendorId", INTEL_QE_VENDOR_ID, 16)?);
+        quote.extend_from_slice(&[0; 20]);
+        match version {
+            4 => {}
+            5 => {
+                quote.extend_from_slice(&BODY_TYPE_TDX_15.to_le_bytes());
+                quote.extend_from_slice(&(BODY_V5_SIZE as u32).to_le_bytes());
+            }
+            _ => {
+                return Err(LitCryptoError::Deserialize(format!(
+                    "version {version} quotes are not supported, only 4 and 5"
+                )))
+            }
+        }
+
+        let tee_tcb_svn = hex_field(
+            "teeTcbSvn",
+            fields.tee_tcb_svn.as_deref().unwrap_or(TEE_TCB_SVN),
+            16,
+        )?;
+        let measurement = |field: &str, value: &Option<String>| match value {
+            Some(value) => hex_field(field, value, 48),
+            None => Ok(vec![0; 48]),
+        };
+        quote.extend_from_slice(&tee_tcb_svn);
+        quote.extend_from_slice(&[0; 48]); // MRSEAM
+        quote.extend_from_slice(&[0; 48]); // MRSIGNERSEAM, zero for Intel's modules
+        quote.extend_from_slice(&[0; 8]); // SEAMATTRIBUTES
+        let td_attributes = if fields.debug { TD_ATTRIBUTES_DEBUG } else { 0 };
+        quote.extend_from_slice(&td_attributes.to_le_bytes());
+        quote.extend_from_slice(&[0; 8]); // XFAM
+        quote.extend_from_slice(&measurement("mrTd", &fields.mr_td)?);
+        quote.extend_from_slice(&measurement("mrConfigId", &fields.mr_config_id)?);
+        quote.extend_from_slice(&measurement("mrOwner", &fields.mr_owner)?);
+        quote.extend_from_slice(&measurement("mrOwnerConfig", &fields.mr_owner_config)?);
+        let rtmrs = fields.rtmrs.clone().unwrap_or_default();
+        if rtmrs.len() > 4 {
+            return Err(LitCryptoError::Deserialize(format!(
+                "{} rtmrs, a TD has 4",
+                rtmrs.len()
+            )));
+        }
+        for i in 0..4 {
+            quote.extend_from_slice(&measurement(
+                &format!("rtmrs[{i}]"),
+                &rtmrs.get(i).cloned(),
+            )?);
+        }
+        quote.extend_from_slice(&report_data("reportData", &fields.report_data)?);
+        if version == 5 {
+            quote.extend_from_slice(&tee_tcb_svn); // TEE_TCB_SVN_2
+            quote.extend_from_slice(&[0; 48]); // MRSERVICETD
+        }
+
+        let signature: Signature = self.attestation_key.sign(&quote);
+        let attestation_key = self.attestation_key.verifying_key().to_encoded_point(false);
+        let attestation_key = &attestation_key.as_bytes()[1..];
+
+        let qe_auth_data: Vec<u8> = (0..QE_AUTH_DATA_LEN).collect();
+        let qe_report = self.qe_report(fields, attestation_key, &qe_auth_data)?;
+        let qe_report_signature: Signature = self.pck_key.sign(&qe_report);
+        let pck_chain = [pem(&self.pck)?, pem(&self.pck_ca)?, pem(&self.root)?].concat();
+
+        let mut qe_data = qe_report;
+        qe_data.extend_from_slice(&qe_report_signature.to_bytes());
+        qe_data.extend_from_slice(&(qe_auth_data.len() as u16).to_le_bytes());
+        qe_data.extend_from_slice(&qe_auth_data);
+        qe_data.extend_from_slice(&CERT_DATA_PCK_CHAIN.to_le_bytes());
+        qe_data.extend_from_slice(&(pck_chain.len() as u32).to_le_bytes());
+        qe_data.extend_from_slice(&pck_chain);
+
+        let mut signature_data = signature.to_bytes().to_vec();
+        signature_data.extend_from_slice(attestation_key);
+        signature_data.extend_from_slice(&CERT_DATA_QE_REPORT.to_le_bytes());
+        signature_data.extend_from_slice(&(qe_data.len() as u32).to_le_bytes());
+        signature_data.extend_from_slice(&qe_data);
+
+        quote.extend_from_slice(&(signature_data.len() as u32).to_le_bytes());
+        quote.extend_from_slice(&signature_data);
+        Ok(quote)
+    }
+
+    /// The SGX report of the quoting enclave, committing to the attestation key
+    fn qe_report(
+        &self,
+        fields: &TdxMockQuote,
+        attestation_key: &[u8],
+        qe_auth_data: &[u8],
+    ) -> JsResult<Vec<u8>> {
+        let mut report = vec![0u8; QE_REPORT_SIZE];
+        report[48..64].copy_from_slice(&hex_field("attributes", QE_ATTRIBUTES, 16)?);
+        report[128..160].copy_from_slice(&hex_field("mrSigner", QE_MR_SIGNER, 32)?);
+        report[256..258].copy_from_slice(&QE_ISV_PROD_ID.to_le_bytes());
+        report[258..260].copy_from_slice(&fields.qe_isv_svn.unwrap_or(QE_ISV_SVN).to_le_bytes());
+
+        let report_data = match &fields.qe_report_data {
+            Some(_) => report_data("qeReportData", &fields.qe_report_data)?,
+            None => {
+                let mut report_data = vec![0u8; 64];
+                report_data[..32].copy_from_slice(
+                    &Sha256::new()
+                        .chain_update(attestation_key)
+                        .chain_update(qe_auth_data)
+                        .finalize(),
+                );
+                report_data
+            }
+        };
+        report[320..384].copy_from_slice(&report_data);
+        Ok(report)
+    }
+
+    /// `{"<key>": <body>, "signature": "<hex r ‖ s>"}`, signed over the exact bytes of the body
+    fn sign_document(&self, key: &str, body: &serde_json::Value) -> String {
+        let body = body.to_string();
+        let signature: Signature = self.tcb_signing_key.sign(body.as_bytes());
+        format!(
+            r#"{{"{key}":{body},"signature":"{}"}}"#,
+            hex::encode(signature.to_bytes())
+        )
+    }
+}
+
+/// Zero padded to the 64 bytes of a report data
+fn report_data(field: &str, value: &Option<Vec<u8>>) -> JsResult<Vec<u8>> {
+    let mut report_data = vec![0u8; 64];
+    if let Some(value) = value {
+        if value.len() > 64 {
+            return Err(LitCryptoError::Deserialize(format!(
+                "{field} has {} bytes, at most 64 fit",
+                value.len()
+            )));
+        }
+        report_data[..value.len()].copy_from_slice(value);
+    }
+    Ok(report_data)
+}
+
+/// The SGX extensions of a PCK certificate: its TCB, PCE-ID and FMSPC
+fn sgx_extension() -> JsResult<Extension> {
+    let entry = |oid: ObjectIdentifier, value: Vec<u8>| sequence(&[encode(&oid)?, value]);
+    let arc = |arc: u32| {
+        SGX_TCB_OID
+            .push_arc(arc)
+            .map_err(|e| LitCryptoError::Serialize(e.to_string()))
+    };
+    let octets = |bytes: &[u8]| {
+        encode(&OctetStringRef::new(bytes).map_err(|e| LitCryptoError::Serialize(e.to_string()))?)
+    };
+
+    let mut tcb = (1..=16)
+        .map(|i| {
+            let svn = u16::from(SGX_TCB_COMPONENTS[i as usize - 1]);
+            entry(arc(i)?, encode(&svn)?)
+        })
+        .collect::<JsResult<Vec<_>>>()?;
+    tcb.push(entry(arc(SGX_PCESVN_ARC)?, encode(&PCE_SVN)?)?);
+
+    let extensions = sequence(&[
+        entry(SGX_TCB_OID, sequence(&tcb)?)?,
+        entry(SGX_PCE_ID_OID, octets(&PCE_ID)?)?,
+        entry(SGX_FMSPC_OID, octets(&FMSPC)?)?,
+    ])?;
+    Ok(Extension {
+        extn_id: SGX_EXTENSIONS_OID,
+        critical: false,
+        extn_value: OctetString::new(extensions)
+            .map_err(|e| LitCryptoError::Serialize(e.to_string()))?,
+    })
+}
+
+fn sequence(items: &[Vec<u8>]) -> JsResult<Vec<u8>> {
+    let sequence = Any::new(Tag::Sequence, items.concat())
+        .map_err(|e| LitCryptoError::Serialize(e.to_string()))?;
+    encode(&sequence)
+}
+
+fn algorithm() -> AlgorithmIdentifierOwned {
+    AlgorithmIdentifierOwned {
+        oid: ECDSA_WITH_SHA256_OID,
+        parameters: None,
+    }
+}
+
+fn ecdsa_sign(key: &SigningKey, message: &[u8]) -> JsResult<BitString> {
+    let signature: Signature = key.sign(message);
+    BitString::from_bytes(signature.to_der().as_bytes())
+        .map_err(|e| LitCryptoError::Serialize(e.to_string()))
+}
+
+fn sign_certificate(
+    issuer_key: &SigningKey,
+    serial: u8,
+    issuer: &Name,
+    subject: &Name,
+    validity: Validity,
+    subject_key: &SigningKey,
+    extension: Option<Extension>,
+) -> JsResult<Certificate> {
+    let spki = subject_key
+        .verifying_key()
+        .to_public_key_der()
+        .map_err(|e| LitCryptoError::Serialize(e.to_string()))?;
+    let tbs_certificate = TbsCertificate {
+        version: Version::V3,
+        serial_number: SerialNumber::new(&[serial])
+            .map_err(|e| LitCryptoError::Serialize(e.to_string()))?,
+        signature: algorithm(),
+        issuer: issuer.clone(),
+        validity,
+        subject: subject.clone(),
+        subject_public_key_info: SubjectPublicKeyInfoOwned::from_der(spki.as_bytes())
+            .map_err(|e| LitCryptoError::Deserialize(e.to_string()))?,
+        issuer_unique_id: None,
+        subject_unique_id: None,
+        extensions: extension.map(|extension| vec![extension]),
+    };
+    let signature = ecdsa_sign(issuer_key, &encode(&tbs_certificate)?)?;
+
+    Ok(Certificate {
+        signature_algorithm: algorithm(),
+        tbs_certificate,
+        signature,
+    })
+}
+
+fn sign_crl(
+    issuer_key: &SigningKey,
+    issuer: &Certificate,
+    revoked: &[(bool, &Certificate)],
+    options: &TdxMockCrlOptions,
+) -> JsResult<Vec<u8>> {
+    let now = (js_sys::Date::now() / 1000.0) as u64;
+    let revoked = revoked
+        .iter()
+        .filter(|(revoke, _)| *revoke)
+        .map(|(_, cert)| {
+            Ok(RevokedCert {
+                serial_number: cert.tbs_certificate.serial_number.clone(),
+                revocation_date: time(now - DAY)?,
+                crl_entry_extensions: None,
+            })
+        })
+        .collect::<JsResult<Vec<_>>>()?;
+
+    let tbs_cert_list = TbsCertList {
+        version: Version::V2,
+        signature: algorithm(),
+        issuer: issuer.tbs_certificate.subject.clone(),
+        this_update: time(options.this_update.unwrap_or(now - 60 * 60))?,
+        next_update: Some(time(options.next_update.unwrap_or(now + DAY))?),
+        revoked_certificates: (!revoked.is_empty()).then_some(revoked),
+        crl_extensions: None,
+    };
+    let signature = ecdsa_sign(issuer_key, &encode(&tbs_cert_list)?)?;
+    encode(&CertificateList {
+        signature_algorithm: algorithm(),
+        tbs_cert_list,
+        signature,
+    })
+}
+
+fn pem(cert: &Certificate) -> JsResult<Vec<u8>> {
+    cert.to_pem(LineEnding::LF)
+        .map(String::into_bytes)
+        .map_err(|e| LitCryptoError::Serialize(e.to_string()))
+}
+
+/// The `YYYY-MM-DDTHH:MM:SSZ` dates of the PCS
+fn pcs_date(secs: u64) -> JsResult<String> {
+    let date = DateTime::from_unix_duration(std::time::Duration::from_secs(secs))
+        .map_err(|e| LitCryptoError::Serialize(e.to_string()))?;
+    Ok(format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        date.year(),
+        date.month(),
+        date.day(),
+        date.hour(),
+        date.minutes(),
+        date.seconds()
+    ))
+}
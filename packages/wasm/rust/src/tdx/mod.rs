@@ -0,0 +1,317 @@
+mod certs;
+mod collateral;
+mod info;
+#[cfg(feature = "tdx-mock")]
+mod mock;
+mod options;
+mod policy;
+mod quote;
+
+use js_sys::Uint8Array;
+use p256::ecdsa::signature::Verifier;
+use p256::ecdsa::{Signature, VerifyingKey};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tsify::Tsify;
+use wasm_bindgen::prelude::*;
+
+use crate::abi::{from_js, JsResult};
+use crate::error::LitCryptoError;
+use crate::x509::Crl;
+
+use certs::{parse_certificate, parse_pem_chain, public_key, verify_chain, PckExtensions};
+use collateral::{check_dates, parse_signed, QeIdentity, TcbInfo, TcbMatch, UP_TO_DATE};
+use quote::TdxQuote;
+
+pub use crate::binding::ReportDataVersion;
+pub use info::TdxQuoteInfo;
+#[cfg(feature = "tdx-mock")]
+pub use mock::{
+    MockChain, TdxMockChainOptions, TdxMockCollateralOptions, TdxMockCrlOptions, TdxMockQuote,
+};
+pub use options::TdxVerifyOptions;
+pub use policy::TdxPolicy;
+
+const PCS_SITE: &str = "https://api.trustedservices.intel.com";
+const PCS_TDX_TCB: &str = "/tdx/certification/v4/tcb"; // PCS_TDX_TCB?fmspc={fmspc}
+const PCS_TDX_QE_IDENTITY: &str = "/tdx/certification/v4/qe/identity";
+const PCS_PCK_CRL: &str = "/sgx/certification/v4/pckcrl"; // PCS_PCK_CRL?ca={platform|processor}&encoding=der
+const ROOT_CA_CRL_URL: &str = "https://certificates.trustedservices.intel.com/IntelSGXRootCA.der";
+
+/// The signed documents the Intel PCS serves for the platform of a quote
+#[derive(Tsify, Serialize, Deserialize, Debug, Clone)]
+#[tsify(into_wasm_abi, from_wasm_abi)]
+#[serde(rename_all = "camelCase")]
+pub struct TdxCollateral {
+    /// Body of the TCB info response, `{"tcbInfo": ..., "signature": ...}`
+    pub tcb_info: String,
+    /// Body of the QE identity response, `{"enclaveIdentity": ..., "signature": ...}`
+    pub qe_identity: String,
+    /// TCB signing certificate and its issuers (PEM), from the `TCB-Info-Issuer-Chain` header once URL-decoded
+    #[tsify(type = "Uint8Array")]
+    #[serde(with = "serde_bytes")]
+    pub issuer_chain: Vec<u8>,
+    /// CRL of the PCK CA (DER or PEM), the PCK certificate of the quote is checked against it when given
+    #[tsify(optional, type = "Uint8Array")]
+    #[serde(default, with = "serde_bytes", skip_serializing_if = "Option::is_none")]
+    pub pck_crl: Option<Vec<u8>>,
+    /// CRL of the Intel root (DER or PEM), the PCK CA and TCB signing certificates are checked against it when given
+    #[tsify(optional, type = "Uint8Array")]
+    #[serde(default, with = "serde_bytes", skip_serializing_if = "Option::is_none")]
+    pub root_ca_crl: Option<Vec<u8>>,
+}
+
+/// Urls of the collateral of a quote on the Intel PCS
+#[derive(Tsify, Serialize, Debug, Clone)]
+#[tsify(into_wasm_abi)]
+#[serde(rename_all = "camelCase")]
+pub struct TdxCollateralUrls {
+    pub tcb_info: String,
+    pub qe_identity: String,
+    /// CRL of the PCK CA that issued the PCK certificate of the quote
+    pub pck_crl: String,
+    pub root_ca_crl: String,
+}
+
+/// What the collateral says about the platform of a verified quote
+#[derive(Tsify, Serialize, Debug, Clone)]
+#[tsify(into_wasm_abi)]
+#[serde(rename_all = "camelCase")]
+pub struct TdxVerification {
+    /// FMSPC of the platform, hex encoded
+    pub fmspc: String,
+    pub tcb_status: String,
+    /// Only for TDX module 1.x and later, which TCB info lists separately
+    #[tsify(optional)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tdx_module_tcb_status: Option<String>,
+    pub qe_tcb_status: String,
+    /// Intel security advisories of the platform, TDX module and quoting enclave
+    pub advisory_ids: Vec<String>,
+}
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(typescript_type = "Record<string, Uint8Array>")]
+    pub type AttestationData;
+}
+
+/// Parses the quote without verifying it, for display
+#[wasm_bindgen(js_name = "tdxParseQuote")]
+pub fn tdx_parse_quote(quote: &[u8]) -> JsResult<TdxQuoteInfo> {
+    Ok(TdxQuoteInfo::new(&parse_quote(quote)?))
+}
+
+/// Gets the urls of the TCB info, QE identity and CRLs of the platform that produced the quote.
+/// The issuer chain of the first two is in the `TCB-Info-Issuer-Chain` header of the TCB info response
+#[wasm_bindgen(js_name = "tdxGetCollateralUrls")]
+pub fn tdx_get_collateral_urls(quote: &[u8]) -> JsResult<TdxCollateralUrls> {
+    let quote = parse_quote(quote)?;
+    let pck_certificate = pck_certificate(&quote)?;
+    let pck = PckExtensions::parse(pck_certificate)?;
+    let pck_ca = if pck_certificate
+        .tbs_certificate
+        .issuer
+        .to_string()
+        .contains("Processor")
+    {
+        "processor"
+    } else {
+        "platform"
+    };
+
+    Ok(TdxCollateralUrls {
+        tcb_info: format!(
+            "{PCS_SITE}{PCS_TDX_TCB}?fmspc={}",
+            hex::encode_upper(&pck.fmspc)
+        ),
+        qe_identity: format!("{PCS_SITE}{PCS_TDX_QE_IDENTITY}"),
+        pck_crl: format!("{PCS_SITE}{PCS_PCK_CRL}?ca={pck_ca}&encoding=der"),
+        root_ca_crl: ROOT_CA_CRL_URL.to_string(),
+    })
+}
+
+/// Verifies the quote, from its PCK certificate chain up to the given Intel root (DER or PEM),
+/// the quoting enclave and the TCB levels against the collateral, then the report data.
+/// The certificates are checked against the CRLs of the collateral when given.
+/// With the options, also checks the TD against a policy and accepts other TCB statuses than `UpToDate`
+#[wasm_bindgen(js_name = "tdxVerify")]
+pub fn tdx_verify(
+    quote: &[u8],
+    attestation_data: AttestationData,
+    signatures: Vec<Uint8Array>,
+    challenge: &[u8],
+    intel_root: &[u8],
+    collateral: TdxCollateral,
+    options: Option<TdxVerifyOptions>,
+) -> JsResult<TdxVerification> {
+    let options = options.unwrap_or_default();
+    let time = options.verification_time();
+    let quote = parse_quote(quote)?;
+    let attestation_data = from_js(attestation_data)?;
+    let signatures = signatures
+        .into_iter()
+        .map(from_js::<Vec<u8>>)
+        .collect::<JsResult<Vec<_>>>()?;
+    let root = parse_certificate(intel_root)?;
+    let pck_crl = collateral.pck_crl.as_deref().map(Crl::parse).transpose()?;
+    let root_ca_crl = collateral
+        .root_ca_crl
+        .as_deref()
+        .map(Crl::parse)
+        .transpose()?;
+
+    verify_chain(
+        &["PCK", "PCK CA"],
+        &quote.pck_chain,
+        &root,
+        &[pck_crl.as_ref(), root_ca_crl.as_ref()],
+        time,
+    )?;
+    let pck = PckExtensions::parse(pck_certificate(&quote)?)?;
+    verify_quote_signature(&quote)?;
+    let verification = verify_collateral(
+        &quote,
+        &pck,
+        &root,
+        root_ca_crl.as_ref(),
+        &collateral,
+        &options,
+        time,
+    )?;
+
+    options.report_data_version.unwrap_or_default().verify(
+        &quote.body.report_data,
+        challenge,
+        &attestation_data,
+        &signatures,
+    )?;
+
+    if let Some(policy) = &options.policy {
+        let violations = policy.check(&quote.body);
+        if !violations.is_empty() {
+            return Err(LitCryptoError::PolicyViolations(violations));
+        }
+    }
+
+    Ok(verification)
+}
+
+fn parse_quote(quote: &[u8]) -> JsResult<TdxQuote> {
+    TdxQuote::parse(quote).map_err(LitCryptoError::AttestationReport)
+}
+
+fn pck_certificate(quote: &TdxQuote) -> JsResult<&x509_cert::Certificate> {
+    quote
+        .pck_chain
+        .first()
+        .ok_or_else(|| LitCryptoError::AttestationChain("no PCK certificate".to_string()))
+}
+
+/// The PCK signs the report of the quoting enclave, which commits to the attestation key,
+/// and the attestation key signs the quote
+fn verify_quote_signature(quote: &TdxQuote) -> JsResult<()> {
+    let invalid = |reason: &str| LitCryptoError::AttestationChain(reason.to_string());
+
+    let qe_report_signature = Signature::from_slice(&quote.qe_report_signature)
+        .map_err(|_| invalid("invalid QE report signature"))?;
+    public_key(pck_certificate(quote)?)?
+        .verify(&quote.qe_report.raw, &qe_report_signature)
+        .map_err(|_| invalid("the QE report is not signed by the PCK"))?;
+
+    let expected: [u8; 32] = Sha256::new()
+        .chain_update(quote.attestation_key)
+        .chain_update(&quote.qe_auth_data)
+        .finalize()
+        .into();
+    let (hash, padding) = quote.qe_report.report_data.split_at(32);
+    if hash != expected || padding.iter().any(|b| *b != 0) {
+        return Err(invalid(
+            "the QE report does not commit to the attestation key",
+        ));
+    }
+
+    let mut sec1 = [0x04; 65];
+    sec1[1..].copy_from_slice(&quote.attestation_key);
+    let attestation_key =
+        VerifyingKey::from_sec1_bytes(&sec1).map_err(|_| invalid("invalid attestation key"))?;
+    let signature =
+        Signature::from_slice(&quote.signature).map_err(|_| invalid("invalid quote signature"))?;
+    attestation_key
+        .verify(&quote.signed, &signature)
+        .map_err(|_| invalid("the quote is not signed by the attestation key"))
+}
+
+fn verify_collateral(
+    quote: &TdxQuote,
+    pck: &PckExtensions,
+    root: &x509_cert::Certificate,
+    root_ca_crl: Option<&Crl>,
+    collateral: &TdxCollateral,
+    options: &TdxVerifyOptions,
+    time: u64,
+) -> JsResult<TdxVerification> {
+    let issuer_chain = parse_pem_chain(&collateral.issuer_chain)?;
+    verify_chain(&["TCB signing"], &issuer_chain, root, &[root_ca_crl], time)?;
+    let signer = &issuer_chain[0];
+
+    let tcb_info: TcbInfo = parse_signed(&collateral.tcb_info, "tcbInfo", signer)?;
+    check_dates(
+        "TCB info",
+        &tcb_info.issue_date,
+        &tcb_info.next_update,
+        time,
+    )?;
+    tcb_info.check_platform(pck)?;
+    let platform = tcb_info.platform_level(pck, &quote.body)?;
+    let module = tcb_info.module_level(&quote.body)?;
+
+    let qe_identity: QeIdentity = parse_signed(&collateral.qe_identity, "enclaveIdentity", signer)?;
+    check_dates(
+        "QE identity",
+        &qe_identity.issue_date,
+        &qe_identity.next_update,
+        time,
+    )?;
+    let qe = qe_identity.qe_level(&quote.qe_report)?;
+
+    let allowed = options
+        .allowed_tcb_statuses
+        .clone()
+        .unwrap_or_else(|| vec![UP_TO_DATE.to_string()]);
+    let levels = [
+        ("platform", Some(&platform)),
+        ("TDX module", module.as_ref()),
+        ("quoting enclave", Some(&qe)),
+    ];
+    for (component, level) in levels {
+        if let Some(TcbMatch {
+            status,
+            advisory_ids,
+        }) = level
+        {
+            if !allowed.contains(status) {
+                return Err(LitCryptoError::TcbStatus {
+                    component: component.to_string(),
+                    status: status.clone(),
+                    advisory_ids: advisory_ids.clone(),
+                });
+            }
+        }
+    }
+
+    let mut advisory_ids: Vec<String> = levels
+        .iter()
+        .flat_map(|(_, level)| level.iter().flat_map(|l| l.advisory_ids.iter().cloned()))
+        .collect();
+    advisory_ids.sort();
+    advisory_ids.dedup();
+
+    Ok(TdxVerification {
+        fmspc: hex::encode(&pck.fmspc),
+        tcb_status: platform.status,
+        tdx_module_tcb_status: module.map(|m| m.status),
+        qe_tcb_status: qe.status,
+        advisory_ids,
+    })
+}
@@ -0,0 +1,32 @@
+//! Optional inputs of the TDX verification
+
+use serde::Deserialize;
+use tsify::Tsify;
+
+use super::{ReportDataVersion, TdxPolicy};
+
+#[derive(Tsify, Deserialize, Debug, Clone, Default)]
+#[tsify(from_wasm_abi)]
+#[serde(rename_all = "camelCase", default)]
+pub struct TdxVerifyOptions {
+    /// Rejects quotes that violate it
+    #[tsify(optional)]
+    pub policy: Option<TdxPolicy>,
+    /// Binding of the report data, the legacy V0 when not given
+    #[tsify(optional)]
+    pub report_data_version: Option<ReportDataVersion>,
+    /// TCB statuses of the platform, TDX module and quoting enclave to accept, only `UpToDate` when not given.
+    /// E.g. add `SWHardeningNeeded` to accept platforms whose remaining advisories are mitigated in software
+    #[tsify(optional)]
+    pub allowed_tcb_statuses: Option<Vec<String>>,
+    /// Unix time in seconds the certificates and collateral must be valid at, now when not given
+    #[tsify(optional)]
+    pub verification_time: Option<u64>,
+}
+
+impl TdxVerifyOptions {
+    pub fn verification_time(&self) -> u64 {
+        self.verification_time
+            .unwrap_or_else(|| (js_sys::Date::now() / 1000.0) as u64)
+    }
+}
@@ -0,0 +1,88 @@
+//! Checks of the measurements of a TD, on top of the quote signature and report data
+
+use serde::{Deserialize, Serialize};
+use tsify::Tsify;
+
+use crate::error::PolicyViolation;
+
+use super::quote::{TdReport, TD_ATTRIBUTES_DEBUG};
+
+/// What a TD quote must show to be trusted.
+/// Measurements are hex encoded, unset fields are not checked.
+#[derive(Tsify, Serialize, Deserialize, Debug, Clone, Default)]
+#[tsify(from_wasm_abi)]
+#[serde(rename_all = "camelCase", default)]
+pub struct TdxPolicy {
+    /// Build-time measurements (MRTD) of the node images we trust
+    #[tsify(optional)]
+    pub allowed_mrtds: Option<Vec<String>>,
+    /// Expected RTMR0 to RTMR3 by index, `null` entries are not checked and further ones are violations
+    #[tsify(optional)]
+    pub rtmrs: Option<Vec<Option<String>>>,
+    #[tsify(optional)]
+    pub mr_config_id: Option<String>,
+    #[tsify(optional)]
+    pub mr_owner: Option<String>,
+    #[tsify(optional)]
+    pub mr_owner_config: Option<String>,
+    /// Accept TDs that can be debugged, which lets the host read their memory
+    pub allow_debug: bool,
+}
+
+impl TdxPolicy {
+    /// Returns every check the TD fails, an empty list means the quote is accepted
+    pub fn check(&self, td: &TdReport) -> Vec<PolicyViolation> {
+        let mut violations = Vec::new();
+        let mut check = |field: &str, allowed: &[String], actual: &[u8]| {
+            let actual = hex::encode(actual);
+            let allowed_match = allowed
+                .iter()
+                .any(|a| a.trim_start_matches("0x").eq_ignore_ascii_case(&actual));
+            if !allowed_match {
+                violations.push(PolicyViolation {
+                    field: field.to_string(),
+                    expected: format!("one of [{}]", allowed.join(", ")),
+                    actual,
+                });
+            }
+        };
+
+        if let Some(allowed) = &self.allowed_mrtds {
+            check("mrTd", allowed, &td.mr_td);
+        }
+        for (i, expected) in self.rtmrs.iter().flatten().enumerate() {
+            if let (Some(expected), Some(rtmr)) = (expected, td.rtmrs.get(i)) {
+                check(&format!("rtmrs[{i}]"), std::slice::from_ref(expected), rtmr);
+            }
+        }
+        for (field, expected, actual) in [
+            ("mrConfigId", &self.mr_config_id, &td.mr_config_id),
+            ("mrOwner", &self.mr_owner, &td.mr_owner),
+            ("mrOwnerConfig", &self.mr_owner_config, &td.mr_owner_config),
+        ] {
+            if let Some(expected) = expected {
+                check(field, std::slice::from_ref(expected), actual);
+            }
+        }
+
+        // A TD only has RTMR0 to RTMR3, entries past them would look enforced without being checked
+        let rtmrs = self.rtmrs.as_deref().unwrap_or_default();
+        for (i, expected) in rtmrs.iter().enumerate().skip(td.rtmrs.len()) {
+            violations.push(PolicyViolation {
+                field: format!("rtmrs[{i}]"),
+                expected: format!("at most {} rtmrs", td.rtmrs.len()),
+                actual: expected.clone().unwrap_or_else(|| "null".to_string()),
+            });
+        }
+
+        if !self.allow_debug && td.td_attributes & TD_ATTRIBUTES_DEBUG != 0 {
+            violations.push(PolicyViolation {
+                field: "tdAttributes.debug".to_string(),
+                expected: "false".to_string(),
+                actual: "true".to_string(),
+            });
+        }
+
+        violations
+    }
+}
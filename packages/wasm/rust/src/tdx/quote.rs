@@ -0,0 +1,288 @@
+//! Bounds-checked parsing of TDX quotes
+//!
+//! Layout: Intel TDX DCAP Quoting Library API, Appendix A "Quote Format" (version 4)
+//! and the version 5 body descriptor added for TDX 1.5.
+
+use std::ops::Range;
+
+use crate::error::ReportError;
+
+pub const HEADER_SIZE: usize = 48;
+/// TD Quote Body of TDX 1.0
+pub const BODY_V4_SIZE: usize = 584;
+/// TD Quote Body of TDX 1.5, with TEE_TCB_SVN_2 and MRSERVICETD
+pub const BODY_V5_SIZE: usize = 648;
+pub const QE_REPORT_SIZE: usize = 384;
+
+/// ECDSA-256-with-P-256, the only attestation key type of TDX
+pub const ATT_KEY_TYPE_ECDSA_P256: u16 = 2;
+pub const TEE_TYPE_TDX: u32 = 0x81;
+
+/// Body types of the version 5 descriptor
+const BODY_TYPE_TDX_10: u16 = 2;
+pub const BODY_TYPE_TDX_15: u16 = 3;
+
+/// Certification data types
+pub const CERT_DATA_PCK_CHAIN: u16 = 5;
+pub const CERT_DATA_QE_REPORT: u16 = 6;
+
+/// TD attributes bit set when the TD can be debugged, which lets the host read its memory
+pub const TD_ATTRIBUTES_DEBUG: u64 = 1;
+
+/// The fields of the TD Quote Body
+#[derive(Debug, Clone)]
+pub struct TdReport {
+    pub tee_tcb_svn: [u8; 16],
+    pub mr_seam: Vec<u8>,
+    pub mr_signer_seam: Vec<u8>,
+    pub seam_attributes: u64,
+    pub td_attributes: u64,
+    pub xfam: u64,
+    pub mr_td: Vec<u8>,
+    pub mr_config_id: Vec<u8>,
+    pub mr_owner: Vec<u8>,
+    pub mr_owner_config: Vec<u8>,
+    pub rtmrs: [Vec<u8>; 4],
+    pub report_data: [u8; 64],
+    /// Only in TDX 1.5 bodies
+    pub tee_tcb_svn_2: Option<[u8; 16]>,
+    pub mr_service_td: Option<Vec<u8>>,
+}
+
+/// The SGX report of the Quoting Enclave, signed by the PCK
+#[derive(Debug, Clone)]
+pub struct QeReport {
+    pub raw: Vec<u8>,
+    pub misc_select: u32,
+    pub attributes: Vec<u8>,
+    pub mr_signer: Vec<u8>,
+    pub isv_prod_id: u16,
+    pub isv_svn: u16,
+    pub report_data: [u8; 64],
+}
+
+/// A parsed quote along with the raw bytes its signature covers
+#[derive(Debug, Clone)]
+pub struct TdxQuote {
+    pub version: u16,
+    pub qe_vendor_id: [u8; 16],
+    pub body: TdReport,
+    /// Header and body, what the attestation key signs
+    pub signed: Vec<u8>,
+    /// r || s, big endian
+    pub signature: [u8; 64],
+    /// x || y of the P-256 attestation key
+    pub attestation_key: [u8; 64],
+    pub qe_report: QeReport,
+    pub qe_report_signature: [u8; 64],
+    pub qe_auth_data: Vec<u8>,
+    /// PCK certificate first, then its issuers
+    pub pck_chain: Vec<x509_cert::Certificate>,
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], ReportError> {
+        let end = self.offset.saturating_add(len);
+        let bytes = self
+            .bytes
+            .get(self.offset..end)
+            .ok_or(ReportError::Length {
+                expected: end,
+                actual: self.bytes.len(),
+            })?;
+        self.offset += len;
+        Ok(bytes)
+    }
+
+    fn array<const N: usize>(&mut self) -> Result<[u8; N], ReportError> {
+        let mut array = [0u8; N];
+        array.copy_from_slice(self.take(N)?);
+        Ok(array)
+    }
+
+    /// A SHA-384 measurement register
+    fn measurement(&mut self) -> Result<Vec<u8>, ReportError> {
+        self.take(48).map(<[u8]>::to_vec)
+    }
+
+    fn u16(&mut self) -> Result<u16, ReportError> {
+        Ok(u16::from_le_bytes(self.array()?))
+    }
+
+    fn u32(&mut self) -> Result<u32, ReportError> {
+        Ok(u32::from_le_bytes(self.array()?))
+    }
+
+    fn u64(&mut self) -> Result<u64, ReportError> {
+        Ok(u64::from_le_bytes(self.array()?))
+    }
+
+    /// The next `len` bytes, read by their own reader so they can't overrun
+    fn section(&mut self, len: usize) -> Result<Reader<'a>, ReportError> {
+        Ok(Reader {
+            bytes: self.take(len)?,
+            offset: 0,
+        })
+    }
+}
+
+impl TdxQuote {
+    pub fn parse(bytes: &[u8]) -> Result<Self, ReportError> {
+        let mut reader = Reader { bytes, offset: 0 };
+
+        let version = reader.u16()?;
+        let att_key_type = reader.u16()?;
+        let tee_type = reader.u32()?;
+        reader.take(4)?;
+        let qe_vendor_id = reader.array()?;
+        reader.take(20)?;
+
+        if tee_type != TEE_TYPE_TDX {
+            return Err(ReportError::TeeType(tee_type));
+        }
+        if att_key_type != ATT_KEY_TYPE_ECDSA_P256 {
+            return Err(ReportError::SignatureAlgorithm(att_key_type.into()));
+        }
+
+        let body_range = match version {
+            4 => HEADER_SIZE..HEADER_SIZE + BODY_V4_SIZE,
+            5 => {
+                let body_type = reader.u16()?;
+                let body_size = reader.u32()? as usize;
+                let expected = match body_type {
+                    BODY_TYPE_TDX_10 => BODY_V4_SIZE,
+                    BODY_TYPE_TDX_15 => BODY_V5_SIZE,
+                    _ => {
+                        return Err(ReportError::Malformed(format!(
+                            "unsupported body type {body_type}"
+                        )))
+                    }
+                };
+                if body_size != expected {
+                    return Err(ReportError::Malformed(format!(
+                        "body type {body_type} has {expected} bytes, not {body_size}"
+                    )));
+                }
+                reader.offset..reader.offset + body_size
+            }
+            _ => return Err(ReportError::Version(version.into())),
+        };
+        let body = parse_body(&mut reader, body_range.len())?;
+        let signed = bytes[..body_range.end].to_vec();
+
+        let signature_len = reader.u32()? as usize;
+        let mut signature_data = reader.section(signature_len)?;
+        let signature = signature_data.array()?;
+        let attestation_key = signature_data.array()?;
+
+        let (cert_type, cert_len) = (signature_data.u16()?, signature_data.u32()? as usize);
+        if cert_type != CERT_DATA_QE_REPORT {
+            return Err(ReportError::Malformed(format!(
+                "expected QE report certification data, got type {cert_type}"
+            )));
+        }
+        let mut qe_data = signature_data.section(cert_len)?;
+        let qe_report = parse_qe_report(qe_data.take(QE_REPORT_SIZE)?);
+        let qe_report_signature = qe_data.array()?;
+        let qe_auth_len = qe_data.u16()? as usize;
+        let qe_auth_data = qe_data.take(qe_auth_len)?.to_vec();
+
+        let (cert_type, cert_len) = (qe_data.u16()?, qe_data.u32()? as usize);
+        if cert_type != CERT_DATA_PCK_CHAIN {
+            return Err(ReportError::Malformed(format!(
+                "expected the PCK certificate chain, got certification data type {cert_type}"
+            )));
+        }
+        let pck_chain = parse_pem_chain(qe_data.take(cert_len)?)?;
+
+        Ok(Self {
+            version,
+            qe_vendor_id,
+            body,
+            signed,
+            signature,
+            attestation_key,
+            qe_report,
+            qe_report_signature,
+            qe_auth_data,
+            pck_chain,
+        })
+    }
+}
+
+fn parse_body(reader: &mut Reader, size: usize) -> Result<TdReport, ReportError> {
+    let tee_tcb_svn = reader.array()?;
+    let mr_seam = reader.measurement()?;
+    let mr_signer_seam = reader.measurement()?;
+    let seam_attributes = reader.u64()?;
+    let td_attributes = reader.u64()?;
+    let xfam = reader.u64()?;
+    let mr_td = reader.measurement()?;
+    let mr_config_id = reader.measurement()?;
+    let mr_owner = reader.measurement()?;
+    let mr_owner_config = reader.measurement()?;
+    let rtmrs = [
+        reader.measurement()?,
+        reader.measurement()?,
+        reader.measurement()?,
+        reader.measurement()?,
+    ];
+    let report_data = reader.array()?;
+
+    let (tee_tcb_svn_2, mr_service_td) = if size == BODY_V5_SIZE {
+        (Some(reader.array()?), Some(reader.measurement()?))
+    } else {
+        (None, None)
+    };
+
+    Ok(TdReport {
+        tee_tcb_svn,
+        mr_seam,
+        mr_signer_seam,
+        seam_attributes,
+        td_attributes,
+        xfam,
+        mr_td,
+        mr_config_id,
+        mr_owner,
+        mr_owner_config,
+        rtmrs,
+        report_data,
+        tee_tcb_svn_2,
+        mr_service_td,
+    })
+}
+
+fn parse_qe_report(raw: &[u8]) -> QeReport {
+    let field = |range: Range<usize>| raw[range].to_vec();
+    let mut report_data = [0u8; 64];
+    report_data.copy_from_slice(&raw[320..384]);
+
+    QeReport {
+        raw: raw.to_vec(),
+        misc_select: u32::from_le_bytes([raw[16], raw[17], raw[18], raw[19]]),
+        attributes: field(48..64),
+        mr_signer: field(128..160),
+        isv_prod_id: u16::from_le_bytes([raw[256], raw[257]]),
+        isv_svn: u16::from_le_bytes([raw[258], raw[259]]),
+        report_data,
+    }
+}
+
+fn parse_pem_chain(pem: &[u8]) -> Result<Vec<x509_cert::Certificate>, ReportError> {
+    // The chain is NUL terminated by some quoting libraries
+    let end = pem.iter().rposition(|b| *b != 0).map_or(0, |i| i + 1);
+    let chain = x509_cert::Certificate::load_pem_chain(&pem[..end])
+        .map_err(|e| ReportError::Malformed(format!("invalid PCK certificate chain: {e}")))?;
+    if chain.is_empty() {
+        return Err(ReportError::Malformed(
+            "empty PCK certificate chain".to_string(),
+        ));
+    }
+    Ok(chain)
+}
@@ -0,0 +1,183 @@
+//! Certificate checks shared by the TEEs, on top of their own signature schemes
+
+use x509_cert::crl::CertificateList;
+use x509_cert::der::{pem, Decode, Encode};
+use x509_cert::spki::ObjectIdentifier;
+use x509_cert::Certificate;
+
+use crate::abi::JsResult;
+use crate::error::LitCryptoError;
+
+/// Checks that `time` (unix seconds) is within the validity period of the certificate
+pub fn check_validity(name: &str, cert: &Certificate, time: u64) -> JsResult<()> {
+    let validity = &cert.tbs_certificate.validity;
+    let not_before = validity.not_before.to_unix_duration().as_secs();
+    let not_after = validity.not_after.to_unix_duration().as_secs();
+
+    if time < not_before || time > not_after {
+        return Err(LitCryptoError::CertificateValidity {
+            certificate: name.to_string(),
+            not_before,
+            not_after,
+            time,
+        });
+    }
+    Ok(())
+}
+
+/// A certificate revocation list, as published by the AMD KDS or the Intel PCS
+pub struct Crl(CertificateList);
+
+impl Crl {
+    /// Accepts DER or PEM
+    pub fn parse(bytes: &[u8]) -> JsResult<Self> {
+        let crl = if bytes.starts_with(b"-----BEGIN") {
+            let (_, der) =
+                pem::decode_vec(bytes).map_err(|e| LitCryptoError::Crl(e.to_string()))?;
+            CertificateList::from_der(&der)
+        } else {
+            CertificateList::from_der(bytes)
+        };
+        crl.map(Self)
+            .map_err(|e| LitCryptoError::Crl(e.to_string()))
+    }
+
+    /// Checks that `issuer` issued the list and that it is current at `time` (unix seconds).
+    /// `verify_signature` checks the signature of the list with the key of the issuer, it gets
+    /// the signature algorithm, the signed DER and the signature
+    pub fn verify(
+        &self,
+        issuer_name: &str,
+        issuer: &Certificate,
+        time: u64,
+        verify_signature: impl FnOnce(&ObjectIdentifier, &[u8], &[u8]) -> Result<(), String>,
+    ) -> JsResult<()> {
+        let tbs = &self.0.tbs_cert_list;
+
+        if tbs.issuer != issuer.tbs_certificate.subject {
+            return Err(LitCryptoError::Crl(format!(
+                "issued by {}, not the {issuer_name}",
+                tbs.issuer
+            )));
+        }
+
+        let message = tbs
+            .to_der()
+            .map_err(|e| LitCryptoError::Serialize(e.to_string()))?;
+        verify_signature(
+            &self.0.signature_algorithm.oid,
+            &message,
+            self.0.signature.raw_bytes(),
+        )
+        .map_err(LitCryptoError::Crl)?;
+
+        let this_update = tbs.this_update.to_unix_duration().as_secs();
+        if time < this_update {
+            return Err(LitCryptoError::Crl(format!(
+                "issued at {this_update}, after {time}"
+            )));
+        }
+        if let Some(next_update) = tbs.next_update {
+            let next_update = next_update.to_unix_duration().as_secs();
+            if time > next_update {
+                return Err(LitCryptoError::Crl(format!(
+                    "expired at {next_update}, fetch a new one"
+                )));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Fails if the list revokes the certificate
+    pub fn check(&self, name: &str, cert: &Certificate) -> JsResult<()> {
+        let serial = &cert.tbs_certificate.serial_number;
+        let revoked = self
+            .0
+            .tbs_cert_list
+            .revoked_certificates
+            .iter()
+            .flatten()
+            .any(|revoked| revoked.serial_number == *serial);
+
+        if revoked {
+            return Err(LitCryptoError::CertificateRevoked {
+                certificate: name.to_string(),
+                serial: hex::encode(serial.as_bytes()),
+            });
+        }
+        Ok(())
+    }
+}
+
+/// Building blocks of the mocks: throwaway certificates and the hex encoded fields of their inputs
+#[cfg(any(feature = "sev-snp-mock", feature = "tdx-mock"))]
+pub mod mock {
+    use std::str::FromStr;
+    use std::time::Duration;
+
+    use x509_cert::der::asn1::{GeneralizedTime, OctetString, UtcTime};
+    use x509_cert::der::oid::AssociatedOid;
+    use x509_cert::der::Encode;
+    use x509_cert::ext::pkix::BasicConstraints;
+    use x509_cert::ext::Extension;
+    use x509_cert::name::Name;
+    use x509_cert::time::{Time, Validity};
+
+    use crate::abi::JsResult;
+    use crate::error::LitCryptoError;
+
+    pub fn name(name: &str) -> JsResult<Name> {
+        Name::from_str(name).map_err(|e| LitCryptoError::Serialize(e.to_string()))
+    }
+
+    pub fn validity(not_before: u64, not_after: u64) -> JsResult<Validity> {
+        Ok(Validity {
+            not_before: time(not_before)?,
+            not_after: time(not_after)?,
+        })
+    }
+
+    /// UTCTime up to 2049 and GeneralizedTime after, as RFC 5280 requires
+    pub fn time(secs: u64) -> JsResult<Time> {
+        let duration = Duration::from_secs(secs);
+        let time = match UtcTime::from_unix_duration(duration) {
+            Ok(time) => Ok(Time::UtcTime(time)),
+            Err(_) => GeneralizedTime::from_unix_duration(duration).map(Time::GeneralTime),
+        };
+        time.map_err(|e| LitCryptoError::Serialize(e.to_string()))
+    }
+
+    pub fn encode(value: &impl Encode) -> JsResult<Vec<u8>> {
+        value
+            .to_der()
+            .map_err(|e| LitCryptoError::Serialize(e.to_string()))
+    }
+
+    /// The basic constraints of a certificate authority
+    pub fn ca_extension() -> JsResult<Extension> {
+        let constraints = BasicConstraints {
+            ca: true,
+            path_len_constraint: None,
+        };
+        Ok(Extension {
+            extn_id: BasicConstraints::OID,
+            critical: true,
+            extn_value: OctetString::new(encode(&constraints)?)
+                .map_err(|e| LitCryptoError::Serialize(e.to_string()))?,
+        })
+    }
+
+    /// Decodes a hex encoded field of a mock input, which must have `len` bytes
+    pub fn hex_field(field: &str, value: &str, len: usize) -> JsResult<Vec<u8>> {
+        let bytes = hex::decode(value.trim_start_matches("0x"))
+            .map_err(|e| LitCryptoError::Deserialize(format!("{field}: {e}")))?;
+        if bytes.len() != len {
+            return Err(LitCryptoError::Deserialize(format!(
+                "{field} has {} bytes, expected {len}",
+                bytes.len()
+            )));
+        }
+        Ok(bytes)
+    }
+}
@@ -0,0 +1,55 @@
+import { execSync } from 'child_process';
+import fs from 'fs';
+
+// Each bundle is built from the rust crate with only the listed cargo features enabled,
+// so apps only download and instantiate the capabilities they use.
+// Keep in sync with `BUNDLES` in src/index.ts
+const BUNDLES = {
+  bitcoin: ['bitcoin'],
+  bls: ['bls'],
+  cosmos: ['cosmos'],
+  ecdsa: ['ecdsa'],
+  ethereum: ['ethereum'],
+  frost: ['frost'],
+  'sev-snp': ['sev-snp'],
+  sui: ['sui'],
+  tdx: ['tdx'],
+};
+
+// Only built with `--testing` for the specs, loaded by src/testing.ts and never shipped
+const TEST_BUNDLES = {
+  'sev-snp-mock': ['sev-snp-mock'],
+  'tdx-mock': ['tdx-mock'],
+};
+
+const PROFILE = process.argv.includes('--dev') ? '--dev' : '--release';
+const TESTING = process.argv.includes('--testing');
+const OUT_DIR = 'src/pkg';
+
+function run(command) {
+  console.log(`> ${command}`);
+  execSync(command, { stdio: 'inherit' });
+}
+
+function main() {
+  fs.rmSync(OUT_DIR, { recursive: true, force: true });
+  fs.mkdirSync(OUT_DIR);
+
+  const bundles = TESTING ? { ...BUNDLES, ...TEST_BUNDLES } : BUNDLES;
+  for (const [bundle, features] of Object.entries(bundles)) {
+    const name = `wasm-${bundle}`;
+    const pkgDir = `rust/pkg/${bundle}`;
+
+    run(
+      `wasm-pack build ./rust --target web ${PROFILE} --out-name ${name} --out-dir pkg/${bundle} -- --no-default-features --features ${features.join(
+        ','
+      )}`
+    );
+    run(`node scripts/copyWasmBinary.mjs ${name} ${pkgDir}`);
+
+    fs.renameSync(`${pkgDir}/${name}.js`, `${OUT_DIR}/${name}.js`);
+    fs.renameSync(`${pkgDir}/${name}.d.ts`, `${OUT_DIR}/${name}.d.ts`);
+  }
+}
+
+main();
@@ -1,12 +1,20 @@
 import fs from 'fs';
 import * as pako from 'pako';
 
-const WASM_MODULE_PATH = 'rust/pkg/wasm-internal_bg.wasm';
-const WASM_BINDING_PATH = 'rust/pkg/wasm-internal.js';
+// Name of the bundle built by wasm-pack, see scripts/buildWasm.mjs
+const BUNDLE_NAME = process.argv[2] ?? 'wasm-internal';
+const WASM_PKG_DIR = process.argv[3] ?? 'rust/pkg';
+const WASM_MODULE_PATH = `${WASM_PKG_DIR}/${BUNDLE_NAME}_bg.wasm`;
+const WASM_BINDING_PATH = `${WASM_PKG_DIR}/${BUNDLE_NAME}.js`;
 const CHUNK_SIZE = 100;
 const COMMENT_OUT_LINES = [
   // This regex matches the block that checks if `module_or_path` is undefined and assigns a URL to it.
-  /if\s*\(\s*typeof\s+module_or_path\s*===\s*['"`]undefined['"`]\s*\)\s*{\s*module_or_path\s*=\s*new\s+URL\s*\(\s*['"`]wasm-internal_bg\.wasm['"`],\s*import\.meta\.url\s*\);\s*}/g,
+  new RegExp(
+    String.raw`if\s*\(\s*typeof\s+module_or_path\s*===\s*['"\x60]undefined['"\x60]\s*\)\s*{\s*module_or_path\s*=\s*new\s+URL\s*\(\s*['"\x60]` +
+      BUNDLE_NAME +
+      String.raw`_bg\.wasm['"\x60],\s*import\.meta\.url\s*\);\s*}`,
+    'g'
+  ),
 
   // This regex matches the block that checks if `module_or_path` is a string, `Request`, or `URL` and assigns it to the result of a `fetch`.
   /if\s*\(\s*typeof\s+module_or_path\s*===\s*['"`]string['"`]\s*\|\|\s*\(typeof\s+Request\s*===\s*['"`]function['"`]\s*&&\s*module_or_path\s*instanceof\s+Request\)\s*\|\|\s*\(typeof\s+URL\s*===\s*['"`]function['"`]\s*&&\s*module_or_path\s*instanceof\s+URL\)\s*\)\s*{\s*module_or_path\s*=\s*fetch\s*\(module_or_path\);\s*}/g,
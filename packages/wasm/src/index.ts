@@ -1,53 +1,224 @@
-//@ts-ignore source map not found
-import {
-  BlsVariant,
+export type {
+  BtcAddressType,
+  BtcNetwork,
+  BtcSighash,
+  BtcSignedTransaction,
+  SighashKind,
+} from './pkg/wasm-bitcoin';
+export type { BlsVariant } from './pkg/wasm-bls';
+export type {
+  AminoSignDoc,
+  CosmosSignBytes,
+  DirectSignDoc,
+  StdPubKey,
+  StdSignature,
+} from './pkg/wasm-cosmos';
+export type {
   EcdsaVariant,
-  InitOutput,
-  //@ts-ignore source map not found
-  getModule,
-  initSync,
-} from './pkg/wasm-internal';
-export type { BlsVariant, EcdsaVariant } from './pkg/wasm-internal';
+  HdKeyDeriver,
+  MessageHasher,
+  PkpAuthMethod,
+  PkpKey,
+  TypedData,
+  TypedDataField,
+} from './pkg/wasm-ecdsa';
+export type {
+  AccessListItem,
+  EthSignedTransaction,
+  EthTransaction,
+  PackedUserOperation,
+  UserOperationV06,
+  UserOperationV07,
+} from './pkg/wasm-ethereum';
+export type { FrostVariant } from './pkg/wasm-frost';
+export type {
+  AttestationCheck,
+  AttestationCheckKind,
+  Cpuid,
+  GuestPolicyInfo,
+  KeyInfo,
+  MinTcb,
+  NodeAttestation,
+  NodeAttestationInput,
+  NodeAttestationResult,
+  NodeAttestationVerdict,
+  NodeSetVerdict,
+  PlatformInfo,
+  PolicyViolation,
+  ReportDataVersion,
+  SevProduct,
+  SevSnpMeasurementOptions,
+  SevSnpPolicy,
+  SevSnpReport,
+  SevSnpVerifyOptions,
+  SignatureInfo,
+  SigningKey,
+  Tcb,
+  VcpuType,
+  VmmType,
+} from './pkg/wasm-sev-snp';
+export type { IntentScope, SuiSignBytes } from './pkg/wasm-sui';
+export type {
+  TdxCollateral,
+  TdxCollateralUrls,
+  TdxPolicy,
+  TdxQuoteInfo,
+  TdxVerification,
+  TdxVerifyOptions,
+} from './pkg/wasm-tdx';
 
-import * as wasmInternal from './pkg/wasm-internal';
+import type {
+  BtcAddressType,
+  BtcNetwork,
+  BtcSighash,
+  BtcSignedTransaction,
+} from './pkg/wasm-bitcoin';
+import type { BlsVariant } from './pkg/wasm-bls';
+import type {
+  AminoSignDoc,
+  CosmosSignBytes,
+  DirectSignDoc,
+  StdSignature,
+} from './pkg/wasm-cosmos';
+import type {
+  EcdsaVariant,
+  HdKeyDeriver,
+  MessageHasher,
+  PkpAuthMethod,
+  PkpKey,
+  TypedData,
+} from './pkg/wasm-ecdsa';
+import type {
+  EthSignedTransaction,
+  EthTransaction,
+  PackedUserOperation,
+  UserOperationV06,
+  UserOperationV07,
+} from './pkg/wasm-ethereum';
+import type { FrostVariant } from './pkg/wasm-frost';
+import type {
+  NodeAttestation,
+  NodeAttestationInput,
+  NodeAttestationVerdict,
+  NodeSetVerdict,
+  PolicyViolation,
+  ReportDataVersion,
+  SevProduct,
+  SevSnpMeasurementOptions,
+  SevSnpPolicy,
+  SevSnpReport,
+  SevSnpVerifyOptions,
+  SigningKey,
+} from './pkg/wasm-sev-snp';
+import type { IntentScope, SuiSignBytes } from './pkg/wasm-sui';
+import type {
+  TdxCollateral,
+  TdxCollateralUrls,
+  TdxQuoteInfo,
+  TdxVerification,
+  TdxVerifyOptions,
+} from './pkg/wasm-tdx';
+
+/**
+ * Error thrown by every function of this package.
+ * `code` is one of `LIT_CRYPTO_ERROR_CODE` and `kind` one of `LIT_ERROR_KIND` from `@lit-protocol/constants`
+ */
+export interface LitCryptoError extends Error {
+  code: string;
+  kind: string;
+  details: Record<string, unknown> | null;
+}
+
+interface WasmBundles {
+  bitcoin: typeof import('./pkg/wasm-bitcoin');
+  bls: typeof import('./pkg/wasm-bls');
+  cosmos: typeof import('./pkg/wasm-cosmos');
+  ecdsa: typeof import('./pkg/wasm-ecdsa');
+  ethereum: typeof import('./pkg/wasm-ethereum');
+  frost: typeof import('./pkg/wasm-frost');
+  'sev-snp': typeof import('./pkg/wasm-sev-snp');
+  sui: typeof import('./pkg/wasm-sui');
+  tdx: typeof import('./pkg/wasm-tdx');
+}
+
+/**
+ * A capability of this package, each one is built into its own wasm bundle
+ * (see `scripts/buildWasm.mjs`) and only loaded the first time one of its functions is called
+ */
+export type WasmCapability = keyof WasmBundles;
+
+const BUNDLES: { [K in WasmCapability]: () => Promise<WasmBundles[K]> } = {
+  bitcoin: () => import('./pkg/wasm-bitcoin'),
+  bls: () => import('./pkg/wasm-bls'),
+  cosmos: () => import('./pkg/wasm-cosmos'),
+  ecdsa: () => import('./pkg/wasm-ecdsa'),
+  ethereum: () => import('./pkg/wasm-ethereum'),
+  frost: () => import('./pkg/wasm-frost'),
+  'sev-snp': () => import('./pkg/wasm-sev-snp'),
+  sui: () => import('./pkg/wasm-sui'),
+  tdx: () => import('./pkg/wasm-tdx'),
+};
 
-let loadingPromise: Promise<any> | null = null;
-let wasmSdkInstance: InitOutput | undefined;
+const loadingPromises: { [K in WasmCapability]?: Promise<WasmBundles[K]> } =
+  {};
+const wasmSdkInstances: { [K in WasmCapability]?: WasmBundles[K] } = {};
 
 // Give us a promise that _just_ encapsulates initializing the modules so we can wrap it in other logic
-async function initWasm() {
-  return initSync(getModule());
+async function initWasm<K extends WasmCapability>(
+  capability: K
+): Promise<WasmBundles[K]> {
+  const bundle = await BUNDLES[capability]();
+  //@ts-ignore getModule is prepended to the bindings by copyWasmBinary.mjs
+  bundle.initSync(bundle.getModule());
+  return bundle;
 }
 
 /**
- * Initializes the wasm module and keeps the module in scope within
+ * Initializes the wasm bundle of the given capability and keeps the module in scope within
  * the module context. Does not expose the module context as it is
  * not intended to be used directly.
- * @returns {Promise<void>}
+ * @param {WasmCapability} capability
+ * @returns {Promise<WasmBundles[K]>} the initialized bindings of the bundle
  */
-async function loadModules() {
+async function loadModules<K extends WasmCapability>(
+  capability: K
+): Promise<WasmBundles[K]> {
+  const wasmSdkInstance = wasmSdkInstances[capability];
   if (wasmSdkInstance) {
     // Runtime is 'hot'; already have loaded WASM modules
-    return wasmSdkInstance;
+    return wasmSdkInstance as WasmBundles[K];
   }
 
+  const loadingPromise = loadingPromises[capability];
   if (loadingPromise) {
     // Runtime is currently loading the WASM modules; chain on the result of that load attempt
-    return loadingPromise;
+    return loadingPromise as Promise<WasmBundles[K]>;
   }
 
   // If we got here, we need to load the wasmSdkInstance -- but we want to avoid any other concurrent
   // requests loading it in parallel, so we stash this promise for those to check for
-  loadingPromise = initWasm();
+  const promise = initWasm(capability);
+  (loadingPromises as Record<K, Promise<WasmBundles[K]>>)[capability] = promise;
 
   try {
-    wasmSdkInstance = await loadingPromise;
+    const instance = await promise;
+    (wasmSdkInstances as Record<K, WasmBundles[K]>)[capability] = instance;
+    // If we got here, the SDK loaded successfully
+    return instance;
   } finally {
-    loadingPromise = null;
+    delete loadingPromises[capability];
   }
+}
 
-  // If we got here, the SDK loaded successfully
-  return;
+/**
+ * Loads the wasm bundles of the given capabilities ahead of their first use
+ * @param {WasmCapability[]} capabilities
+ * @returns {Promise<void>}
+ */
+export async function preloadWasm(
+  capabilities: WasmCapability[]
+): Promise<void> {
+  await Promise.all(capabilities.map((capability) => loadModules(capability)));
 }
 
 /**
@@ -64,8 +235,8 @@ export async function blsCombine(
   variant: BlsVariant,
   signature_shares: Uint8Array[]
 ): Promise<Uint8Array> {
-  await loadModules();
-  return wasmInternal.blsCombine(variant, signature_shares);
+  const wasm = await loadModules('bls');
+  return wasm.blsCombine(variant, signature_shares);
 }
 
 /**
@@ -85,8 +256,8 @@ export async function blsDecrypt(
   ciphertext: Uint8Array,
   decryption_key: Uint8Array
 ): Promise<Uint8Array> {
-  await loadModules();
-  return wasmInternal.blsDecrypt(variant, ciphertext, decryption_key);
+  const wasm = await loadModules('bls');
+  return wasm.blsDecrypt(variant, ciphertext, decryption_key);
 }
 
 /**
@@ -107,8 +278,8 @@ export async function blsEncrypt(
   message: Uint8Array,
   identity: Uint8Array
 ): Promise<Uint8Array> {
-  await loadModules();
-  return wasmInternal.blsEncrypt(variant, encryption_key, message, identity);
+  const wasm = await loadModules('bls');
+  return wasm.blsEncrypt(variant, encryption_key, message, identity);
 }
 
 /**
@@ -128,8 +299,8 @@ export async function blsVerify(
   message: Uint8Array,
   signature: Uint8Array
 ): Promise<void> {
-  await loadModules();
-  return wasmInternal.blsVerify(variant, public_key, message, signature);
+  const wasm = await loadModules('bls');
+  return wasm.blsVerify(variant, public_key, message, signature);
 }
 
 /**
@@ -138,6 +309,7 @@ export async function blsVerify(
  * Supports:
  *  - K256
  *  - P256
+ *  - P384
  * @param {EcdsaVariant} variant
  * @param {Uint8Array} presignature
  * @param {(Uint8Array)[]} signature_shares
@@ -148,8 +320,8 @@ export async function ecdsaCombine(
   presignature: Uint8Array,
   signature_shares: Uint8Array[]
 ): Promise<[Uint8Array, Uint8Array, number]> {
-  await loadModules();
-  return wasmInternal.ecdsaCombine(variant, presignature, signature_shares);
+  const wasm = await loadModules('ecdsa');
+  return wasm.ecdsaCombine(variant, presignature, signature_shares);
 }
 
 /**
@@ -158,6 +330,7 @@ export async function ecdsaCombine(
  * Supports:
  * - k256
  * - p256
+ * - p384
  * @param {EcdsaVariant} variant ecdsa scheme
  * @param {Uint8Array} id keyid which will be used for the key derivation
  * @param {(Uint8Array)[]} public_keys ecdsa root keys
@@ -168,8 +341,78 @@ export async function ecdsaDeriveKey(
   id: Uint8Array,
   public_keys: Uint8Array[]
 ): Promise<Uint8Array> {
-  await loadModules();
-  return wasmInternal.ecdsaDeriveKey(variant, id, public_keys);
+  const wasm = await loadModules('ecdsa');
+  return wasm.ecdsaDeriveKey(variant, id, public_keys);
+}
+
+/**
+ * HD key derivation for FROST keys, same as `ecdsaDeriveKey` with the derivation context of each curve
+ *
+ * Supports:
+ * - ed25519
+ * - ristretto255
+ * - bls12-381 g1 and g2, the nodes only derive g1 keys
+ * - jubjub, with the context of the node's RedJubjub keys
+ * @param {FrostVariant} variant curve of the keys
+ * @param {Uint8Array} id keyid which will be used for the key derivation
+ * @param {(Uint8Array)[]} public_keys root keys, compressed
+ * @returns {Uint8Array} the derived key, compressed
+ */
+export async function frostDeriveKey(
+  variant: FrostVariant,
+  id: Uint8Array,
+  public_keys: Uint8Array[]
+): Promise<Uint8Array> {
+  const wasm = await loadModules('frost');
+  return wasm.frostDeriveKey(variant, id, public_keys);
+}
+
+/**
+ * Creates an HD key deriver over the root keys, which decodes them and precomputes their multiples once.
+ * Deriving many keys with it is much faster than calling `ecdsaDeriveKey` for each
+ *
+ * Supports:
+ * - k256
+ * - p256
+ * - p384
+ * @param {EcdsaVariant} variant ecdsa scheme
+ * @param {(Uint8Array)[]} public_keys ecdsa root keys
+ * @returns {Promise<HdKeyDeriver>} with `derive(id)` and `deriveMany(ids)`, which return the same keys as `ecdsaDeriveKey`
+ */
+export async function hdKeyDeriver(
+  variant: EcdsaVariant,
+  public_keys: Uint8Array[]
+): Promise<HdKeyDeriver> {
+  const wasm = await loadModules('ecdsa');
+  return new wasm.HdKeyDeriver(variant, public_keys);
+}
+
+/**
+ * Key id of the PKP an auth method can claim, `keccak256("<authMethodType>:<authMethodId>")`
+ *
+ * @param {PkpAuthMethod} auth_method an `AUTH_METHOD_TYPE` with the id of the user, or bytes to hash as the key id
+ * @returns {Promise<Uint8Array>} the 32 bytes key id
+ */
+export async function pkpKeyId(
+  auth_method: PkpAuthMethod
+): Promise<Uint8Array> {
+  const wasm = await loadModules('ecdsa');
+  return wasm.pkpKeyId(auth_method);
+}
+
+/**
+ * Predicts the PKP an auth method can claim, without asking the nodes
+ *
+ * @param {PkpAuthMethod} auth_method an `AUTH_METHOD_TYPE` with the id of the user, or bytes to hash as the key id
+ * @param {(Uint8Array)[]} public_keys k256 root keys of the network
+ * @returns {Promise<PkpKey>} the key id, uncompressed public key and checksummed ETH address
+ */
+export async function pkpDerive(
+  auth_method: PkpAuthMethod,
+  public_keys: Uint8Array[]
+): Promise<PkpKey> {
+  const wasm = await loadModules('ecdsa');
+  return wasm.pkpDerive(auth_method, public_keys);
 }
 
 /**
@@ -178,6 +421,7 @@ export async function ecdsaDeriveKey(
  * Supports:
  * - k256
  * - p256
+ * - p384
  ** Note ** Not currently supported through the lit network. Please use other ECSDSA signature verification
  * @param {EcdsaVariant} variant
  * @param {Uint8Array} message_hash
@@ -190,8 +434,8 @@ export async function ecdsaVerify(
   public_key: Uint8Array,
   signature: [Uint8Array, Uint8Array, number]
 ): Promise<void> {
-  await loadModules();
-  return wasmInternal.ecdsaVerify(variant, message_hash, public_key, signature);
+  const wasm = await loadModules('ecdsa');
+  return wasm.ecdsaVerify(variant, message_hash, public_key, signature);
 }
 
 /**
@@ -200,6 +444,7 @@ export async function ecdsaVerify(
  * Supports:
  * - k256
  * - p256
+ * - p384
  *  ** Note ** Not currently supported through the lit network. Please use other ECSDSA signature verification
  * @param {EcdsaVariant} variant
  * @param {Uint8Array} pre_signature
@@ -215,8 +460,8 @@ export async function ecdsaCombnieAndVerify(
   message_hash: Uint8Array,
   public_key: Uint8Array
 ): Promise<[Uint8Array, Uint8Array, number]> {
-  await loadModules();
-  return wasmInternal.ecdsaCombineAndVerify(
+  const wasm = await loadModules('ecdsa');
+  return wasm.ecdsaCombineAndVerify(
     variant,
     pre_signature,
     signature_shares,
@@ -225,16 +470,520 @@ export async function ecdsaCombnieAndVerify(
   );
 }
 
+/**
+ * Same as `ecdsaCombnieAndVerify`, hashing the message with the given hasher first
+ *
+ * @param {EcdsaVariant} variant
+ * @param {Uint8Array} pre_signature
+ * @param {Uint8Array[]} signature_shares
+ * @param {Uint8Array} message the message as signed, the JSON encoded typed data for EIP-712
+ * @param {MessageHasher} hasher its digest must be as long as the scalars of the curve
+ * @param {Uint8Array} public_key
+ * @returns {[Uint8Array, Uint8Array, number]}
+ */
+export async function ecdsaCombineAndVerifyMessage(
+  variant: EcdsaVariant,
+  pre_signature: Uint8Array,
+  signature_shares: Uint8Array[],
+  message: Uint8Array,
+  hasher: MessageHasher,
+  public_key: Uint8Array
+): Promise<[Uint8Array, Uint8Array, number]> {
+  const wasm = await loadModules('ecdsa');
+  return wasm.ecdsaCombineAndVerifyMessage(
+    variant,
+    pre_signature,
+    signature_shares,
+    message,
+    hasher,
+    public_key
+  );
+}
+
+/**
+ * Same as `ecdsaCombineAndVerifyMessage`, verifying against the key derived from the root keys
+ *
+ * @param {EcdsaVariant} variant
+ * @param {Uint8Array} pre_signature
+ * @param {Uint8Array[]} signature_shares
+ * @param {Uint8Array} message the message as signed, the JSON encoded typed data for EIP-712
+ * @param {MessageHasher} hasher its digest must be as long as the scalars of the curve
+ * @param {Uint8Array} id keyid which will be used for the key derivation
+ * @param {Uint8Array[]} public_keys ecdsa root keys
+ * @returns {[Uint8Array, Uint8Array, number]}
+ */
+export async function ecdsaCombineAndVerifyMessageWithDerivedKey(
+  variant: EcdsaVariant,
+  pre_signature: Uint8Array,
+  signature_shares: Uint8Array[],
+  message: Uint8Array,
+  hasher: MessageHasher,
+  id: Uint8Array,
+  public_keys: Uint8Array[]
+): Promise<[Uint8Array, Uint8Array, number]> {
+  const wasm = await loadModules('ecdsa');
+  return wasm.ecdsaCombineAndVerifyMessageWithDerivedKey(
+    variant,
+    pre_signature,
+    signature_shares,
+    message,
+    hasher,
+    id,
+    public_keys
+  );
+}
+
+/**
+ * Hashes a message the way it is signed
+ *
+ * Supports:
+ * - keccak256
+ * - sha256
+ * - sha384
+ * - eip191 (`personal_sign`)
+ * - eip712, the message being the JSON encoded typed data
+ * @param {MessageHasher} hasher
+ * @param {Uint8Array} message
+ * @returns {Promise<Uint8Array>} the digest to sign
+ */
+export async function hashMessage(
+  hasher: MessageHasher,
+  message: Uint8Array
+): Promise<Uint8Array> {
+  const wasm = await loadModules('ecdsa');
+  return wasm.hashMessage(hasher, message);
+}
+
+/**
+ * EIP-712 digest of typed data, what `eth_signTypedData_v4` signs
+ * @param {TypedData} typed_data `types.EIP712Domain` is inferred from the domain when missing
+ * @returns {Promise<Uint8Array>} the digest to sign
+ */
+export async function eip712Hash(typed_data: TypedData): Promise<Uint8Array> {
+  const wasm = await loadModules('ecdsa');
+  return wasm.eip712Hash(typed_data);
+}
+
+/**
+ * Hash to sign for a transaction, pass it as `message_hash` to combine its signature
+ * @param {EthTransaction} transaction legacy, EIP-2930, EIP-1559 or EIP-4844 (without its blobs)
+ * @returns {Promise<Uint8Array>} the digest to sign
+ */
+export async function ethTxSigningHash(
+  transaction: EthTransaction
+): Promise<Uint8Array> {
+  const wasm = await loadModules('ethereum');
+  return wasm.ethTxSigningHash(transaction);
+}
+
+/**
+ * Serializes a transaction, signed with the combined signature of its signing hash
+ * @param {EthTransaction} transaction its `from`, when given, must be the signer
+ * @param {[Uint8Array, Uint8Array, number]} [signature] as returned by `ecdsaCombine`, the unsigned transaction is serialized without it
+ * @returns {Promise<Uint8Array>} the raw transaction
+ */
+export async function ethTxSerialize(
+  transaction: EthTransaction,
+  signature?: [Uint8Array, Uint8Array, number]
+): Promise<Uint8Array> {
+  const wasm = await loadModules('ethereum');
+  return wasm.ethTxSerialize(transaction, signature);
+}
+
+/**
+ * Decodes a signed raw transaction, recovering its sender
+ * @param {Uint8Array} raw
+ * @returns {Promise<EthSignedTransaction>}
+ */
+export async function ethTxDecode(
+  raw: Uint8Array
+): Promise<EthSignedTransaction> {
+  const wasm = await loadModules('ethereum');
+  return wasm.ethTxDecode(raw);
+}
+
+/**
+ * Hash the account signs for a user operation of the EntryPoint v0.6
+ *
+ * Most ECDSA validators check a `personal_sign` of it, hash it with `hashMessage('Eip191', ...)` for those
+ * @param {UserOperationV06} user_operation
+ * @param {string} entry_point
+ * @param {number | string} chain_id
+ * @returns {Promise<Uint8Array>} the `userOpHash`
+ */
+export async function userOpHashV06(
+  user_operation: UserOperationV06,
+  entry_point: string,
+  chain_id: number | string
+): Promise<Uint8Array> {
+  const wasm = await loadModules('ethereum');
+  return wasm.userOpHashV06(user_operation, entry_point, chain_id);
+}
+
+/**
+ * Hash the account signs for a user operation of the EntryPoint v0.7
+ * @param {UserOperationV07} user_operation
+ * @param {string} entry_point
+ * @param {number | string} chain_id
+ * @returns {Promise<Uint8Array>} the `userOpHash`
+ */
+export async function userOpHashV07(
+  user_operation: UserOperationV07,
+  entry_point: string,
+  chain_id: number | string
+): Promise<Uint8Array> {
+  const wasm = await loadModules('ethereum');
+  return wasm.userOpHashV07(user_operation, entry_point, chain_id);
+}
+
+/**
+ * Hash the account signs for a packed user operation of the EntryPoint v0.7
+ * @param {PackedUserOperation} user_operation
+ * @param {string} entry_point
+ * @param {number | string} chain_id
+ * @returns {Promise<Uint8Array>} the `userOpHash`
+ */
+export async function userOpHashPacked(
+  user_operation: PackedUserOperation,
+  entry_point: string,
+  chain_id: number | string
+): Promise<Uint8Array> {
+  const wasm = await loadModules('ethereum');
+  return wasm.userOpHashPacked(user_operation, entry_point, chain_id);
+}
+
+/**
+ * Packs a user operation of the EntryPoint v0.7 as `handleOps` takes it
+ * @param {UserOperationV07} user_operation
+ * @returns {Promise<PackedUserOperation>}
+ */
+export async function userOpPack(
+  user_operation: UserOperationV07
+): Promise<PackedUserOperation> {
+  const wasm = await loadModules('ethereum');
+  return wasm.userOpPack(user_operation);
+}
+
+/**
+ * Encodes a combined signature for the `signature` of a user operation, with a low `s`
+ *
+ * - K256: 65 bytes `r || s || v`, `v` being 27 or 28
+ * - P256: 64 bytes `r || s`, that is `abi.encode(r, s)`
+ * @param {EcdsaVariant} variant
+ * @param {[Uint8Array, Uint8Array, number]} signature as returned by `ecdsaCombine`
+ * @returns {Promise<Uint8Array>}
+ */
+export async function userOpSignature(
+  variant: EcdsaVariant,
+  signature: [Uint8Array, Uint8Array, number]
+): Promise<Uint8Array> {
+  const wasm = await loadModules('ethereum');
+  return wasm.userOpSignature(variant, signature);
+}
+
+/**
+ * Input of the RIP-7212 P256 precompile, `hash || r || s || x || y` with a low `s`.
+ * The signature is verified first so the precompile accepts it.
+ * @param {Uint8Array} message_hash
+ * @param {[Uint8Array, Uint8Array, number]} signature a combined P256 signature
+ * @param {Uint8Array} public_key compressed or uncompressed
+ * @returns {Promise<Uint8Array>} 160 bytes
+ */
+export async function userOpRip7212Input(
+  message_hash: Uint8Array,
+  signature: [Uint8Array, Uint8Array, number],
+  public_key: Uint8Array
+): Promise<Uint8Array> {
+  const wasm = await loadModules('ethereum');
+  return wasm.userOpRip7212Input(message_hash, signature, public_key);
+}
+
+/**
+ * Sign bytes of a `SIGN_MODE_DIRECT` sign doc, and their SHA-256 digest to sign
+ * @param {DirectSignDoc} sign_doc
+ * @returns {Promise<CosmosSignBytes>}
+ */
+export async function cosmosDirectSignBytes(
+  sign_doc: DirectSignDoc
+): Promise<CosmosSignBytes> {
+  const wasm = await loadModules('cosmos');
+  return wasm.cosmosDirectSignBytes(sign_doc);
+}
+
+/**
+ * Sign bytes of an amino JSON sign doc, its keys sorted, and their SHA-256 digest to sign
+ * @param {AminoSignDoc} sign_doc
+ * @returns {Promise<CosmosSignBytes>}
+ */
+export async function cosmosAminoSignBytes(
+  sign_doc: AminoSignDoc
+): Promise<CosmosSignBytes> {
+  const wasm = await loadModules('cosmos');
+  return wasm.cosmosAminoSignBytes(sign_doc);
+}
+
+/**
+ * Encodes a combined K256 signature as the 64 bytes `r || s` of Cosmos SDK chains, with a low `s`
+ * @param {[Uint8Array, Uint8Array, number]} signature as returned by `ecdsaCombine`
+ * @returns {Promise<Uint8Array>}
+ */
+export async function cosmosSignature(
+  signature: [Uint8Array, Uint8Array, number]
+): Promise<Uint8Array> {
+  const wasm = await loadModules('cosmos');
+  return wasm.cosmosSignature(signature);
+}
+
+/**
+ * Encodes a combined K256 signature with its public key as amino JSON carries them
+ * @param {Uint8Array} public_key compressed or uncompressed
+ * @param {[Uint8Array, Uint8Array, number]} signature as returned by `ecdsaCombine`
+ * @returns {Promise<StdSignature>}
+ */
+export async function cosmosStdSignature(
+  public_key: Uint8Array,
+  signature: [Uint8Array, Uint8Array, number]
+): Promise<StdSignature> {
+  const wasm = await loadModules('cosmos');
+  return wasm.cosmosStdSignature(public_key, signature);
+}
+
+/**
+ * Bech32 account address of a K256 public key
+ * @param {Uint8Array} public_key compressed or uncompressed
+ * @param {string} prefix the chain's address prefix, e.g. `cosmos` or `osmo`
+ * @returns {Promise<string>}
+ */
+export async function cosmosAddress(
+  public_key: Uint8Array,
+  prefix: string
+): Promise<string> {
+  const wasm = await loadModules('cosmos');
+  return wasm.cosmosAddress(public_key, prefix);
+}
+
+/**
+ * Verifies a Cosmos signature of sign bytes, rejecting signatures with a high `s`
+ * @param {Uint8Array} sign_bytes
+ * @param {Uint8Array} signature 64 bytes `r || s`
+ * @param {Uint8Array} public_key compressed or uncompressed
+ */
+export async function cosmosVerify(
+  sign_bytes: Uint8Array,
+  signature: Uint8Array,
+  public_key: Uint8Array
+): Promise<void> {
+  const wasm = await loadModules('cosmos');
+  return wasm.cosmosVerify(sign_bytes, signature, public_key);
+}
+
+/**
+ * Intent message of BCS transaction data or a personal message, its BLAKE2b-256 digest and the SHA-256 of the digest to sign
+ * @param {IntentScope} scope
+ * @param {Uint8Array} message
+ * @returns {Promise<SuiSignBytes>}
+ */
+export async function suiSignBytes(
+  scope: IntentScope,
+  message: Uint8Array
+): Promise<SuiSignBytes> {
+  const wasm = await loadModules('sui');
+  return wasm.suiSignBytes(scope, message);
+}
+
+/**
+ * Digest identifying BCS transaction data
+ * @param {Uint8Array} transaction_data
+ * @returns {Promise<Uint8Array>}
+ */
+export async function suiTransactionDigest(
+  transaction_data: Uint8Array
+): Promise<Uint8Array> {
+  const wasm = await loadModules('sui');
+  return wasm.suiTransactionDigest(transaction_data);
+}
+
+/**
+ * Encodes a combined signature as a base64 Sui serialized signature `flag || r || s || public key`, with a low `s`
+ * @param {EcdsaVariant} variant K256 or P256
+ * @param {[Uint8Array, Uint8Array, number]} signature as returned by `ecdsaCombine`
+ * @param {Uint8Array} public_key compressed or uncompressed
+ * @returns {Promise<string>}
+ */
+export async function suiSignature(
+  variant: EcdsaVariant,
+  signature: [Uint8Array, Uint8Array, number],
+  public_key: Uint8Array
+): Promise<string> {
+  const wasm = await loadModules('sui');
+  return wasm.suiSignature(variant, signature, public_key);
+}
+
+/**
+ * Sui address of a public key
+ * @param {EcdsaVariant} variant K256 or P256
+ * @param {Uint8Array} public_key compressed or uncompressed
+ * @returns {Promise<string>}
+ */
+export async function suiAddress(
+  variant: EcdsaVariant,
+  public_key: Uint8Array
+): Promise<string> {
+  const wasm = await loadModules('sui');
+  return wasm.suiAddress(variant, public_key);
+}
+
+/**
+ * Verifies a Sui serialized signature of a message, rejecting signatures with a high `s`
+ * @param {IntentScope} scope
+ * @param {Uint8Array} message
+ * @param {string} signature base64 serialized signature
+ * @returns {Promise<string>} the address of the signer
+ */
+export async function suiVerify(
+  scope: IntentScope,
+  message: Uint8Array,
+  signature: string
+): Promise<string> {
+  const wasm = await loadModules('sui');
+  return wasm.suiVerify(scope, message, signature);
+}
+
+/**
+ * Signature hash of a PSBT input, legacy, BIP-143 or BIP-341 depending on the output it spends.
+ * The sighash type is that of the input if it has one, else `sighash_type`, else `SIGHASH_ALL`, or `SIGHASH_DEFAULT` for taproot inputs.
+ * @param {Uint8Array} psbt
+ * @param {number} input_index
+ * @param {number} [sighash_type]
+ * @returns {Promise<BtcSighash>}
+ */
+export async function btcSighash(
+  psbt: Uint8Array,
+  input_index: number,
+  sighash_type?: number
+): Promise<BtcSighash> {
+  const wasm = await loadModules('bitcoin');
+  return wasm.btcSighash(psbt, input_index, sighash_type);
+}
+
+/**
+ * DER encoding of a combined K256 signature with a low `s`, followed by its sighash type
+ * @param {[Uint8Array, Uint8Array, number]} signature as returned by `ecdsaCombine`
+ * @param {number} [sighash_type] `SIGHASH_ALL` by default
+ * @returns {Promise<Uint8Array>}
+ */
+export async function btcDerSignature(
+  signature: [Uint8Array, Uint8Array, number],
+  sighash_type?: number
+): Promise<Uint8Array> {
+  const wasm = await loadModules('bitcoin');
+  return wasm.btcDerSignature(signature, sighash_type);
+}
+
+/**
+ * Adds the partial signature of an input to a PSBT, once verified against the input's sighash
+ * @param {Uint8Array} psbt
+ * @param {number} input_index
+ * @param {Uint8Array} public_key compressed or uncompressed, the signature is added for its compressed encoding
+ * @param {[Uint8Array, Uint8Array, number]} signature as returned by `ecdsaCombine`
+ * @param {number} [sighash_type]
+ * @returns {Promise<Uint8Array>} the PSBT with the signature
+ */
+export async function btcPsbtAddSignature(
+  psbt: Uint8Array,
+  input_index: number,
+  public_key: Uint8Array,
+  signature: [Uint8Array, Uint8Array, number],
+  sighash_type?: number
+): Promise<Uint8Array> {
+  const wasm = await loadModules('bitcoin');
+  return wasm.btcPsbtAddSignature(
+    psbt,
+    input_index,
+    public_key,
+    signature,
+    sighash_type
+  );
+}
+
+/**
+ * Finalizes the P2PKH, P2WPKH and P2SH-P2WPKH inputs of a PSBT from their partial signatures.
+ * The other inputs, and those not signed yet, are left for other signers
+ * @param {Uint8Array} psbt
+ * @returns {Promise<Uint8Array>} the finalized PSBT
+ */
+export async function btcPsbtFinalize(psbt: Uint8Array): Promise<Uint8Array> {
+  const wasm = await loadModules('bitcoin');
+  return wasm.btcPsbtFinalize(psbt);
+}
+
+/**
+ * Extracts the signed transaction of a finalized PSBT
+ * @param {Uint8Array} psbt
+ * @returns {Promise<BtcSignedTransaction>}
+ */
+export async function btcPsbtExtract(
+  psbt: Uint8Array
+): Promise<BtcSignedTransaction> {
+  const wasm = await loadModules('bitcoin');
+  return wasm.btcPsbtExtract(psbt);
+}
+
+/**
+ * Address of a K256 public key
+ * @param {Uint8Array} public_key compressed or uncompressed, the address is that of the compressed key
+ * @param {BtcAddressType} address_type
+ * @param {BtcNetwork} network
+ * @returns {Promise<string>}
+ */
+export async function btcAddress(
+  public_key: Uint8Array,
+  address_type: BtcAddressType,
+  network: BtcNetwork
+): Promise<string> {
+  const wasm = await loadModules('bitcoin');
+  return wasm.btcAddress(public_key, address_type, network);
+}
+
 /**
  * Gets the vcek url for the given attestation report.  You can fetch this certificate yourself, and pass it in to verify_attestation_report
  * @param {Uint8Array} attestation_report
+ * @param {SevProduct} [product] detected from the report when omitted
  * @returns {string}
  */
 export async function sevSnpGetVcekUrl(
-  attestation_report: Uint8Array
+  attestation_report: Uint8Array,
+  product?: SevProduct
 ): Promise<string> {
-  await loadModules();
-  return wasmInternal.sevSnpGetVcekUrl(attestation_report);
+  const wasm = await loadModules('sev-snp');
+  return wasm.sevSnpGetVcekUrl(attestation_report, product);
+}
+
+/**
+ * Parses the attestation report without verifying it
+ * @param {Uint8Array} attestation_report
+ * @param {SevProduct} [product] detected from the report when omitted
+ * @returns {Promise<SevSnpReport>} the report fields, byte fields hex encoded
+ */
+export async function sevSnpParseReport(
+  attestation_report: Uint8Array,
+  product?: SevProduct
+): Promise<SevSnpReport> {
+  const wasm = await loadModules('sev-snp');
+  return wasm.sevSnpParseReport(attestation_report, product);
+}
+
+/**
+ * Detects the EPYC product (Milan, Genoa, Turin) that produced the attestation report.
+ * Version 2 reports don't identify Milan and Genoa, those throw `unknown_product`
+ * @param {Uint8Array} attestation_report
+ * @returns {SevProduct}
+ */
+export async function sevSnpDetectProduct(
+  attestation_report: Uint8Array
+): Promise<SevProduct> {
+  const wasm = await loadModules('sev-snp');
+  return wasm.sevSnpDetectProduct(attestation_report);
 }
 
 /**
@@ -243,7 +992,8 @@ export async function sevSnpGetVcekUrl(
  * @param {Record<string, Uint8Array>} attestation_data
  * @param {(Uint8Array)[]} signatures
  * @param {Uint8Array} challenge
- * @param {Uint8Array} vcek_certificate
+ * @param {Uint8Array} vcek_certificate the VCEK as DER or PEM, a PEM bundle with the ASK and ARK, or a blob from `sevSnpCertBundle`
+ * @param {SevSnpVerifyOptions} [options] product, policy, report data version, CRL, trusted certificates and verification time
  * @returns {Promise<void>}
  */
 export async function sevSnpVerify(
@@ -251,14 +1001,231 @@ export async function sevSnpVerify(
   attestation_data: Record<string, Uint8Array>,
   signatures: Uint8Array[],
   challenge: Uint8Array,
-  vcek_certificate: Uint8Array
+  vcek_certificate: Uint8Array,
+  options?: SevSnpVerifyOptions
 ): Promise<void> {
-  await loadModules();
-  return wasmInternal.sevSnpVerify(
+  const wasm = await loadModules('sev-snp');
+  return wasm.sevSnpVerify(
     attestation_report,
     attestation_data,
     signatures,
     challenge,
-    vcek_certificate
+    vcek_certificate,
+    options
+  );
+}
+
+/**
+ * Gets the url of the ASK (or ASVK for VLEKs) and ARK certificates of the product
+ * @param {SevProduct} product
+ * @param {SigningKey} signing_key defaults to `'Vcek'`
+ * @returns {Promise<string>}
+ */
+export async function sevSnpGetCertChainUrl(
+  product: SevProduct,
+  signing_key?: SigningKey
+): Promise<string> {
+  const wasm = await loadModules('sev-snp');
+  return wasm.sevSnpGetCertChainUrl(product, signing_key);
+}
+
+/**
+ * Gets the url of the certificate revocation list of the product
+ * @param {SevProduct} product
+ * @param {SigningKey} signing_key defaults to `'Vcek'`
+ * @returns {Promise<string>}
+ */
+export async function sevSnpGetCrlUrl(
+  product: SevProduct,
+  signing_key?: SigningKey
+): Promise<string> {
+  const wasm = await loadModules('sev-snp');
+  return wasm.sevSnpGetCrlUrl(product, signing_key);
+}
+
+/**
+ * Computes the report data a node commits to
+ * @param {ReportDataVersion} version
+ * @param {Uint8Array} challenge
+ * @param {Record<string, Uint8Array>} attestation_data
+ * @param {Uint8Array[]} signatures
+ * @returns {Promise<Uint8Array>} the 64 bytes of report data
+ */
+export async function sevSnpReportData(
+  version: ReportDataVersion,
+  challenge: Uint8Array,
+  attestation_data: Record<string, Uint8Array>,
+  signatures: Uint8Array[]
+): Promise<Uint8Array> {
+  const wasm = await loadModules('sev-snp');
+  return wasm.sevSnpReportData(
+    version,
+    challenge,
+    attestation_data,
+    signatures
+  );
+}
+
+/**
+ * Runs every check on the attestation a node returned for our challenge: nonce, external address and port,
+ * certificate chain, report data, and revocation and the policy when given in the options
+ * @param {NodeAttestation} attestation as returned by the node
+ * @param {Uint8Array} challenge the challenge we sent
+ * @param {string} url the URL we talked to
+ * @param {Uint8Array} vcek_certificate the VCEK as DER or PEM, a PEM bundle with the ASK and ARK, or a blob from `sevSnpCertBundle`
+ * @param {SevSnpVerifyOptions} [options] product, policy, report data version, CRL, trusted certificates and verification time
+ * @returns {Promise<NodeAttestationVerdict>} the outcome of each check, throws only on malformed input
+ */
+export async function sevSnpVerifyNodeAttestation(
+  attestation: NodeAttestation,
+  challenge: Uint8Array,
+  url: string,
+  vcek_certificate: Uint8Array,
+  options?: SevSnpVerifyOptions
+): Promise<NodeAttestationVerdict> {
+  const wasm = await loadModules('sev-snp');
+  return wasm.sevSnpVerifyNodeAttestation(
+    attestation,
+    challenge,
+    url,
+    vcek_certificate,
+    options
+  );
+}
+
+/**
+ * Verifies the attestations of a whole node set, each like `sevSnpVerifyNodeAttestation`,
+ * verifying the certificates nodes share only once
+ * @param {NodeAttestationInput[]} nodes the attestation, challenge, URL and certificates of each node
+ * @param {number} threshold how many nodes must attest
+ * @param {SevSnpVerifyOptions} [options] shared by every node, a node's own CRL replaces the one of the options
+ * @param {string[]} [unique_data_keys] data keys under which no two attested nodes may advertise the same value
+ * @returns {Promise<NodeSetVerdict>} the result of each node and whether the threshold is met, throws only on malformed input such as the same URL twice
+ */
+export async function sevSnpVerifyNodeAttestations(
+  nodes: NodeAttestationInput[],
+  threshold: number,
+  options?: SevSnpVerifyOptions,
+  unique_data_keys?: string[]
+): Promise<NodeSetVerdict> {
+  const wasm = await loadModules('sev-snp');
+  return wasm.sevSnpVerifyNodeAttestations(
+    nodes,
+    threshold,
+    options,
+    unique_data_keys
+  );
+}
+
+/**
+ * Verifies the certificate chain of the report and packs it into a blob to cache,
+ * which `sevSnpVerify` and `sevSnpVerifyNodeAttestation` accept in place of the VCEK
+ * @param {Uint8Array} attestation_report
+ * @param {Uint8Array} vcek_certificate the VCEK as DER or PEM, or a PEM bundle with the ASK and ARK
+ * @param {SevSnpVerifyOptions} [options] product, CRL, trusted certificates and verification time
+ * @returns {Promise<Uint8Array>}
+ */
+export async function sevSnpCertBundle(
+  attestation_report: Uint8Array,
+  vcek_certificate: Uint8Array,
+  options?: SevSnpVerifyOptions
+): Promise<Uint8Array> {
+  const wasm = await loadModules('sev-snp');
+  return wasm.sevSnpCertBundle(attestation_report, vcek_certificate, options);
+}
+
+/**
+ * Computes the launch measurement of a guest booted from the OVMF image, to compare with the report `measurement`
+ * @param {Uint8Array} ovmf the OVMF firmware image
+ * @param {SevSnpMeasurementOptions} options vCPUs, VMM and the kernel, initrd and command line for direct boot
+ * @returns {Promise<string>} the measurement, hex encoded
+ */
+export async function sevSnpLaunchMeasurement(
+  ovmf: Uint8Array,
+  options: SevSnpMeasurementOptions
+): Promise<string> {
+  const wasm = await loadModules('sev-snp');
+  return wasm.sevSnpLaunchMeasurement(ovmf, options);
+}
+
+/**
+ * Computes the measurement of the OVMF image alone, which `ovmfHash` takes to skip hashing it again
+ * @param {Uint8Array} ovmf the OVMF firmware image
+ * @returns {Promise<string>} the hash, hex encoded
+ */
+export async function sevSnpOvmfHash(ovmf: Uint8Array): Promise<string> {
+  const wasm = await loadModules('sev-snp');
+  return wasm.sevSnpOvmfHash(ovmf);
+}
+
+/**
+ * Checks the attestation report against a policy without verifying its signature
+ * @param {Uint8Array} attestation_report
+ * @param {SevSnpPolicy} policy
+ * @param {SevProduct} [product] detected from the report when omitted
+ * @returns {Promise<PolicyViolation[]>} every check the report fails, empty when it is accepted
+ */
+export async function sevSnpCheckPolicy(
+  attestation_report: Uint8Array,
+  policy: SevSnpPolicy,
+  product?: SevProduct
+): Promise<PolicyViolation[]> {
+  const wasm = await loadModules('sev-snp');
+  return wasm.sevSnpCheckPolicy(attestation_report, policy, product);
+}
+
+/**
+ * Parses a TDX quote without verifying it, for display
+ * @param {Uint8Array} quote
+ * @returns {Promise<TdxQuoteInfo>}
+ */
+export async function tdxParseQuote(quote: Uint8Array): Promise<TdxQuoteInfo> {
+  const wasm = await loadModules('tdx');
+  return wasm.tdxParseQuote(quote);
+}
+
+/**
+ * Gets the urls of the TCB info, QE identity and CRLs of the platform that produced the quote on the Intel PCS
+ * @param {Uint8Array} quote
+ * @returns {Promise<TdxCollateralUrls>}
+ */
+export async function tdxGetCollateralUrls(
+  quote: Uint8Array
+): Promise<TdxCollateralUrls> {
+  const wasm = await loadModules('tdx');
+  return wasm.tdxGetCollateralUrls(quote);
+}
+
+/**
+ * Verifies a TDX quote: its PCK certificate chain up to the Intel root, the quoting enclave,
+ * the TCB levels of the platform against the collateral, and the report data.
+ * The certificates are checked against the CRLs of the collateral when given
+ * @param {Uint8Array} quote
+ * @param {Record<string, Uint8Array>} attestation_data
+ * @param {Uint8Array[]} signatures
+ * @param {Uint8Array} challenge
+ * @param {Uint8Array} intel_root the Intel SGX root CA as DER or PEM
+ * @param {TdxCollateral} collateral TCB info, QE identity, their issuer chain and the CRLs from the Intel PCS
+ * @param {TdxVerifyOptions} [options] policy, report data version, accepted TCB statuses and verification time
+ * @returns {Promise<TdxVerification>} the TCB statuses and advisories of the platform
+ */
+export async function tdxVerify(
+  quote: Uint8Array,
+  attestation_data: Record<string, Uint8Array>,
+  signatures: Uint8Array[],
+  challenge: Uint8Array,
+  intel_root: Uint8Array,
+  collateral: TdxCollateral,
+  options?: TdxVerifyOptions
+): Promise<TdxVerification> {
+  const wasm = await loadModules('tdx');
+  return wasm.tdxVerify(
+    quote,
+    attestation_data,
+    signatures,
+    challenge,
+    intel_root,
+    collateral,
+    options
   );
 }
@@ -0,0 +1,178 @@
+/// <reference types="jest" />
+
+import { ethers } from 'ethers';
+import { publicKey, sign } from '@lit-protocol/wasm/testing';
+import {
+  btcAddress,
+  btcDerSignature,
+  btcPsbtAddSignature,
+  btcPsbtExtract,
+  btcPsbtFinalize,
+  btcSighash,
+} from '..';
+
+const { arrayify, hexlify, hexZeroPad } = ethers.utils;
+
+// Built with rust-bitcoin: P2PKH, P2WPKH, P2SH-P2WPKH and P2TR inputs of the
+// key, paying to its P2WPKH and P2PKH
+const psbt =
+  '0x70736274ff0100ef0200000004186fecfe1522b5eb583899ebc336c36d7499b51efda1cc6fc2ed04cc631671be0100000000fdffffff07070707070707070707070707070707070707070707070707070707070707070300000000fcffffff08080808080808080808080808080808080808080808080808080808080808080000000000fbffffff09090909090909090909090909090909090909090909090909090909090909090200000000faffffff02a086010000000000160014bd92088bb7e82d611a9b94fbb74a0908152b784f39300000000000001976a914bd92088bb7e82d611a9b94fbb74a0908152b784f88ac00350c000001005f01000000010000000000000000000000000000000000000000000000000000000000000000000000000151ffffffff02e8030000000000000050c30000000000001976a914bd92088bb7e82d611a9b94fbb74a0908152b784f88ac000000000001011f7011010000000000160014bd92088bb7e82d611a9b94fbb74a0908152b784f00010120803801000000000017a914fb71ddebb5454a966d5fecb83eef81b5339dd4d9870104160014bd92088bb7e82d611a9b94fbb74a0908152b784f0001012b905f010000000000225120c27ee9c5156e4bb60d24bad94e48d806b551e8ae6c7bfb86453c8b87b93c953a000000';
+// Its first three inputs, and the transaction they are signed into
+const signablePsbt =
+  '0x70736274ff0100c60200000003186fecfe1522b5eb583899ebc336c36d7499b51efda1cc6fc2ed04cc631671be0100000000fdffffff07070707070707070707070707070707070707070707070707070707070707070300000000fcffffff08080808080808080808080808080808080808080808080808080808080808080000000000fbffffff02a086010000000000160014bd92088bb7e82d611a9b94fbb74a0908152b784f39300000000000001976a914bd92088bb7e82d611a9b94fbb74a0908152b784f88ac00350c000001005f01000000010000000000000000000000000000000000000000000000000000000000000000000000000151ffffffff02e8030000000000000050c30000000000001976a914bd92088bb7e82d611a9b94fbb74a0908152b784f88ac000000000001011f7011010000000000160014bd92088bb7e82d611a9b94fbb74a0908152b784f00010120803801000000000017a914fb71ddebb5454a966d5fecb83eef81b5339dd4d9870104160014bd92088bb7e82d611a9b94fbb74a0908152b784f000000';
+const signedTransaction =
+  '0x02000000000103186fecfe1522b5eb583899ebc336c36d7499b51efda1cc6fc2ed04cc631671be010000006b483045022100c57a4bad95492563852bb9a1fc48f97b90f81b006a42beaf02cd1e53ec64b6d702203f2216f2f0038e8ed7eb926e44579db615d936a6f482392b2a205ae9bf555f8c0121024bc2a31265153f07e70e0bab08724e6b85e217f8cd628ceb62974247bb493382fdffffff07070707070707070707070707070707070707070707070707070707070707070300000000fcffffff08080808080808080808080808080808080808080808080808080808080808080000000017160014bd92088bb7e82d611a9b94fbb74a0908152b784ffbffffff02a086010000000000160014bd92088bb7e82d611a9b94fbb74a0908152b784f39300000000000001976a914bd92088bb7e82d611a9b94fbb74a0908152b784f88ac0002483045022100fe8f7236c4c8e51c802afeda581f3f55cbbaf65f4134c6e99f324ea163e383eb02205cb29d397a017e7df567135b7a7d9c9a0326776970eb2be1564ca6b3a7e9dba10121024bc2a31265153f07e70e0bab08724e6b85e217f8cd628ceb62974247bb493382024830450221008b8395f911cc19ae7fb9a7c7594cda5d0da5033c735f046f8866bae0bdf8c13a02205426f0164b28f29499ad113133d09c0850bef3557b9035184426e9c2ad229a420121024bc2a31265153f07e70e0bab08724e6b85e217f8cd628ceb62974247bb49338200350c00';
+
+describe('Bitcoin', () => {
+  it('should derive addresses', async () => {
+    expect(await btcAddress(publicKey, 'P2PKH', 'mainnet')).toEqual(
+      '1JHMeqKunF2Up6zxnMQGhJu5667BXz98YQ'
+    );
+    expect(await btcAddress(publicKey, 'P2WPKH', 'mainnet')).toEqual(
+      'bc1qhkfq3zahaqkkzx5mjnamwjsfpq2jk7z00ppggv'
+    );
+    expect(await btcAddress(publicKey, 'P2SH-P2WPKH', 'mainnet')).toEqual(
+      '3QcXwJW5QKR7F2Jje6LY6ZL1WFZ3MNz6Uf'
+    );
+    expect(await btcAddress(publicKey, 'P2PKH', 'testnet')).toEqual(
+      'mxoJwtQtbGTjbDUaVvNeXE7Px5htMzPw4T'
+    );
+    expect(await btcAddress(publicKey, 'P2WPKH', 'testnet')).toEqual(
+      'tb1qhkfq3zahaqkkzx5mjnamwjsfpq2jk7z0986mnl'
+    );
+    expect(await btcAddress(publicKey, 'P2WPKH', 'regtest')).toEqual(
+      'bcrt1qhkfq3zahaqkkzx5mjnamwjsfpq2jk7z08wrkyk'
+    );
+  });
+
+  it.each([
+    [
+      0,
+      'Legacy',
+      0x01,
+      '8653dc350fea5cc08deb7c7fb99aaff774d99d1f457cd7911e499cd34a58976f',
+    ],
+    [
+      0,
+      'Legacy',
+      0x83,
+      '5d6aeaaa18aa315640e47419cacb5d5bb8270dacbaa5ba4530c6abe2000c873f',
+    ],
+    [
+      1,
+      'SegwitV0',
+      0x01,
+      '51760636b09b0654197028dc58798466fd3079b30cc25fc99f26402ed11066dc',
+    ],
+    [
+      1,
+      'SegwitV0',
+      0x83,
+      '7081a295dd5f9f5a6c8856233923ed4329679227e233706934e158cc7d3ed8b8',
+    ],
+    [
+      2,
+      'SegwitV0',
+      0x01,
+      'bba918031ae85a6ccd24cce5d5a2b26c733462089f6353b170faccd690dc5b10',
+    ],
+    [
+      3,
+      'Taproot',
+      0x00,
+      'f1e22cfb83f68ac5cd39e311df64a62f5088057c1ab01295c942bc53fed21ad9',
+    ],
+    [
+      3,
+      'Taproot',
+      0x01,
+      '07deec60d9a6707fbfbed7bedc697530bd8ba5c7ccc9c179d9d5a374560117ca',
+    ],
+  ])(
+    'should hash input %i as %s with sighash type %i',
+    async (index, kind, sighashType, hash) => {
+      expect(
+        await btcSighash(arrayify(psbt), index as number, sighashType as number)
+      ).toEqual({ kind, sighashType, hash: arrayify('0x' + hash) });
+    }
+  );
+
+  it('should default to SIGHASH_ALL or SIGHASH_DEFAULT', async () => {
+    expect(await btcSighash(arrayify(psbt), 1)).toMatchObject({
+      sighashType: 0x01,
+    });
+    expect(await btcSighash(arrayify(psbt), 3)).toMatchObject({
+      sighashType: 0x00,
+    });
+    await expect(btcSighash(arrayify(psbt), 4)).rejects.toMatchObject({
+      code: 'invalid_transaction',
+    });
+  });
+
+  it('should encode DER signatures with a low s', async () => {
+    const digest = arrayify(ethers.utils.sha256('0x1234'));
+    const [r, s, recoveryId] = sign(digest);
+    const n = ethers.BigNumber.from(
+      '0xfffffffffffffffffffffffffffffffebaaedce6af48a03bbfd25e8cd0364141'
+    );
+    const highS = arrayify(hexZeroPad(n.sub(s).toHexString(), 32));
+
+    const signature = await btcDerSignature([r, highS, 1 - recoveryId], 0x83);
+    expect(signature[signature.length - 1]).toEqual(0x83);
+    expect(hexlify(signature.subarray(0, -1))).toEqual(
+      hexlify(await btcDerSignature([r, s, recoveryId])).slice(0, -2)
+    );
+  });
+
+  it('should sign, finalize and extract P2PKH and P2WPKH inputs', async () => {
+    let signed = arrayify(signablePsbt);
+    await expect(
+      btcPsbtExtract(await btcPsbtFinalize(signed))
+    ).rejects.toMatchObject({
+      code: 'invalid_transaction',
+      details: { reason: 'input 0 is not finalized' },
+    });
+
+    for (const index of [0, 1, 2]) {
+      const { hash } = await btcSighash(signed, index);
+      signed = await btcPsbtAddSignature(signed, index, publicKey, sign(hash));
+    }
+    const { transaction, txid } = await btcPsbtExtract(
+      await btcPsbtFinalize(signed)
+    );
+
+    expect(hexlify(transaction)).toEqual(signedTransaction);
+    expect(txid).toEqual(
+      'ee5fb3e9924b6c1b9e2c8642237f5493dcf77c0127c25d15e9ecf546e18d5618'
+    );
+  });
+
+  it('should finalize the inputs of the key and leave the taproot one', async () => {
+    let signed = arrayify(psbt);
+    for (const index of [0, 1, 2]) {
+      const { hash } = await btcSighash(signed, index);
+      signed = await btcPsbtAddSignature(signed, index, publicKey, sign(hash));
+    }
+
+    await expect(
+      btcPsbtExtract(await btcPsbtFinalize(signed))
+    ).rejects.toMatchObject({
+      code: 'invalid_transaction',
+      details: { reason: 'input 3 is not finalized' },
+    });
+  });
+
+  it('should reject signatures of another input', async () => {
+    const { hash } = await btcSighash(arrayify(signablePsbt), 1);
+    await expect(
+      btcPsbtAddSignature(arrayify(signablePsbt), 0, publicKey, sign(hash))
+    ).rejects.toMatchObject({ code: 'signature_mismatch' });
+  });
+
+  it('should not add ECDSA signatures to taproot inputs', async () => {
+    const { hash } = await btcSighash(arrayify(psbt), 3);
+    await expect(
+      btcPsbtAddSignature(arrayify(psbt), 3, publicKey, sign(hash))
+    ).rejects.toMatchObject({ code: 'invalid_transaction' });
+  });
+});
@@ -0,0 +1,123 @@
+/// <reference types="jest" />
+
+import {
+  encodeSecp256k1Signature,
+  pubkeyToAddress,
+  serializeSignDoc,
+} from '@cosmjs/amino';
+import { fromBase64 } from '@cosmjs/encoding';
+import { ethers } from 'ethers';
+import { publicKey, sign } from '@lit-protocol/wasm/testing';
+import {
+  cosmosAddress,
+  cosmosAminoSignBytes,
+  cosmosDirectSignBytes,
+  cosmosSignature,
+  cosmosStdSignature,
+  cosmosVerify,
+} from '..';
+
+const { arrayify, hexZeroPad, sha256 } = ethers.utils;
+
+const directSignDoc = {
+  bodyBytes: new Uint8Array([1, 2]),
+  authInfoBytes: new Uint8Array(),
+  chainId: 'cosmoshub-4',
+  accountNumber: 300,
+};
+
+const aminoSignDoc = {
+  chain_id: 'cosmoshub-4',
+  account_number: '300',
+  sequence: '7',
+  fee: { gas: '200000', amount: [{ denom: 'uatom', amount: '5000' }] },
+  msgs: [
+    {
+      type: 'cosmos-sdk/MsgSend',
+      value: {
+        to_address: 'cosmos1h806c7khnvmjlywdrkdgk2vrayy2mmvf9rxk2r',
+        from_address: 'cosmos1h806c7khnvmjlywdrkdgk2vrayy2mmvf9rxk2r',
+        amount: [{ denom: 'uatom', amount: '1' }],
+      },
+    },
+  ],
+  memo: '<a & b>',
+};
+
+describe('Cosmos', () => {
+  it('should derive bech32 addresses', async () => {
+    const pubkey = fromBase64('AtQaCqFnshaZQp6rIkvAPyzThvCvXSDO+9AzbxVErqJP');
+    expect(await cosmosAddress(pubkey, 'cosmos')).toEqual(
+      'cosmos1h806c7khnvmjlywdrkdgk2vrayy2mmvf9rxk2r'
+    );
+
+    const address = pubkeyToAddress(
+      encodeSecp256k1Signature(publicKey, new Uint8Array(64)).pub_key,
+      'osmo'
+    );
+    expect(await cosmosAddress(publicKey, 'osmo')).toEqual(address);
+
+    await expect(
+      cosmosAddress(publicKey, 'Not A Prefix')
+    ).rejects.toMatchObject({ code: 'invalid_address_prefix' });
+  });
+
+  it('should encode direct sign docs as protobuf', async () => {
+    const { signBytes, hash } = await cosmosDirectSignBytes(directSignDoc);
+    // The empty auth_info_bytes is left out
+    const expected = '0x0a0201021a0b636f736d6f736875622d3420ac02';
+
+    expect(ethers.utils.hexlify(signBytes)).toEqual(expected);
+    expect(ethers.utils.hexlify(hash)).toEqual(sha256(expected));
+
+    const { signBytes: large } = await cosmosDirectSignBytes({
+      ...directSignDoc,
+      accountNumber: '18446744073709551615',
+    });
+    expect(large.subarray(-11)).toEqual(
+      new Uint8Array([0x20, ...Array(9).fill(0xff), 0x01])
+    );
+  });
+
+  it('should serialize amino sign docs like cosmjs', async () => {
+    const { signBytes, hash } = await cosmosAminoSignBytes(aminoSignDoc);
+    const expected = serializeSignDoc(aminoSignDoc);
+
+    expect(signBytes).toEqual(expected);
+    expect(new TextDecoder().decode(signBytes)).toContain(
+      '"memo":"\\u003ca \\u0026 b\\u003e"'
+    );
+    expect(ethers.utils.hexlify(hash)).toEqual(sha256(expected));
+  });
+
+  it('should sign with a low s and verify', async () => {
+    const { signBytes, hash } = await cosmosAminoSignBytes(aminoSignDoc);
+    const [r, s, recoveryId] = sign(hash);
+    const n = ethers.BigNumber.from(
+      '0xfffffffffffffffffffffffffffffffebaaedce6af48a03bbfd25e8cd0364141'
+    );
+    const highS = arrayify(hexZeroPad(n.sub(s).toHexString(), 32));
+
+    const signature = await cosmosSignature([r, highS, 1 - recoveryId]);
+    expect(signature).toEqual(new Uint8Array([...r, ...s]));
+    await expect(
+      cosmosVerify(signBytes, signature, publicKey)
+    ).resolves.toBeUndefined();
+
+    await expect(
+      cosmosVerify(signBytes, new Uint8Array([...r, ...highS]), publicKey)
+    ).rejects.toMatchObject({ code: 'invalid_signature' });
+    await expect(
+      cosmosVerify(new Uint8Array([1]), signature, publicKey)
+    ).rejects.toMatchObject({ code: 'signature_mismatch' });
+  });
+
+  it('should encode std signatures like cosmjs', async () => {
+    const { hash } = await cosmosAminoSignBytes(aminoSignDoc);
+    const combined = sign(hash);
+
+    expect(await cosmosStdSignature(publicKey, combined)).toEqual(
+      encodeSecp256k1Signature(publicKey, await cosmosSignature(combined))
+    );
+  });
+});
@@ -1,5 +1,6 @@
 /// <reference types="jest" />
 
+import * as crypto from 'node:crypto';
 import { ethers } from 'ethers';
 import {
   messageHex,
@@ -8,7 +9,15 @@ import {
   signatureHex,
   signatureSharesHex,
 } from './ecdsa-data.spec.json';
-import { ecdsaCombine, ecdsaVerify, ecdsaDeriveKey } from '..';
+import {
+  ecdsaCombine,
+  ecdsaCombnieAndVerify,
+  ecdsaVerify,
+  ecdsaDeriveKey,
+  hdKeyDeriver,
+  pkpDerive,
+  pkpKeyId,
+} from '..';
 
 const publicKey = Buffer.from(publicKeyHex, 'hex');
 const uncompressedPublicKey = ethers.utils.computePublicKey(publicKey);
@@ -51,6 +60,55 @@ describe('ECDSA', () => {
     ]);
   });
 
+  it('should reject shares and signatures of the wrong length', async () => {
+    const shortShare = signatureShares[1].subarray(1);
+
+    await expect(
+      ecdsaCombine('K256', presignature, [signatureShares[0], shortShare])
+    ).rejects.toMatchObject({ code: 'invalid_share', details: { index: 1 } });
+    await expect(
+      ecdsaVerify('K256', message, publicKey, [
+        signature.r.subarray(1),
+        signature.s,
+        signature.v,
+      ])
+    ).rejects.toMatchObject({ code: 'invalid_scalar' });
+  });
+
+  it('should combine and verify shares that sum to a high s', async () => {
+    // The shares sum to s > n/2, which the low-s rule negates
+    const highSPublicKey = Buffer.from(
+      '024bc2a31265153f07e70e0bab08724e6b85e217f8cd628ceb62974247bb493382',
+      'hex'
+    );
+    const highSPresignature = Buffer.from(
+      '031b84c5567b126440995d3ed5aaba0565d71e1834604819ff9c17f5e9d5dd078f',
+      'hex'
+    );
+    const highSShares = [
+      '1010101010101010101010101010101010101010101010101010101010101010',
+      '2323232323232323232323232323232323232323232323232323232323232323',
+      'c5d3b51630d074186ca5216313ba6577b94112aa5a963c5a1680f9b044d8ef31',
+    ].map((s) => Buffer.from(s, 'hex'));
+
+    const [r, s, v] = await ecdsaCombnieAndVerify(
+      'K256',
+      highSPresignature,
+      highSShares,
+      message,
+      highSPublicKey
+    );
+
+    const combined = await ecdsaCombine('K256', highSPresignature, highSShares);
+    expect(combined).toEqual([r, s, v]);
+    expect(
+      ethers.utils.recoverPublicKey(
+        message,
+        Buffer.concat([r, s, Buffer.from([v])])
+      )
+    ).toEqual(ethers.utils.computePublicKey(highSPublicKey));
+  });
+
   it('should derive keys', async () => {
     const identity = Buffer.from('test', 'ascii');
     const derivedKey = await ecdsaDeriveKey('K256', identity, [
@@ -66,4 +124,94 @@ describe('ECDSA', () => {
       )
     );
   });
+
+  it('should derive the same keys with an hd key deriver', async () => {
+    const deriver = await hdKeyDeriver('K256', [publicKey, publicKey]);
+    const ids = ['test', 'other'].map((id) => Buffer.from(id, 'ascii'));
+
+    expect(Buffer.from(deriver.derive(ids[0]))).toEqual(
+      Buffer.from(
+        '0440b3dc3caa60584ad1297bc843075b30b04139bcc438a04401ed45d78526faac7fe86c033f34cac09959e1b7ad6e940028e0ed26277f5da454f9432ba7a02a8d',
+        'hex'
+      )
+    );
+    expect(deriver.deriveMany(ids)).toEqual(
+      await Promise.all(
+        ids.map((id) => ecdsaDeriveKey('K256', id, [publicKey, publicKey]))
+      )
+    );
+  });
+
+  it('should compute pkp key ids', async () => {
+    const { arrayify, keccak256, toUtf8Bytes } = ethers.utils;
+
+    expect(
+      await pkpKeyId({ authMethodType: 1, authMethodId: '0xabc' })
+    ).toEqual(arrayify(keccak256(toUtf8Bytes('1:0xabc'))));
+    expect(await pkpKeyId({ keccakInput: toUtf8Bytes('test') })).toEqual(
+      arrayify(keccak256(toUtf8Bytes('test')))
+    );
+  });
+
+  it('should derive pkps from auth methods', async () => {
+    const authMethod = { authMethodType: 1, authMethodId: '0xabc' };
+    const pkp = await pkpDerive(authMethod, [publicKey, publicKey]);
+
+    expect(pkp.publicKey).toEqual(
+      await ecdsaDeriveKey('K256', pkp.keyId, [publicKey, publicKey])
+    );
+    expect(pkp.ethAddress).toEqual(ethers.utils.computeAddress(pkp.publicKey));
+  });
+
+  it('should verify p384 signatures', async () => {
+    const keyPair = crypto.generateKeyPairSync('ec', {
+      namedCurve: 'secp384r1',
+    });
+    const data = Buffer.from('test', 'ascii');
+    const p1363 = crypto.sign('sha384', data, {
+      key: keyPair.privateKey,
+      dsaEncoding: 'ieee-p1363',
+    });
+    const p384Signature: [Buffer, Buffer, number] = [
+      p1363.subarray(0, 48),
+      p1363.subarray(48),
+      0,
+    ];
+    // The uncompressed point ends the SPKI encoding
+    const p384PublicKey = keyPair.publicKey
+      .export({ format: 'der', type: 'spki' })
+      .subarray(-97);
+
+    await ecdsaVerify(
+      'P384',
+      crypto.createHash('sha384').update(data).digest(),
+      p384PublicKey,
+      p384Signature
+    );
+    await expect(
+      ecdsaVerify(
+        'P384',
+        crypto.createHash('sha256').update(data).digest(),
+        p384PublicKey,
+        p384Signature
+      )
+    ).rejects.toMatchObject({
+      code: 'digest_length',
+      details: { expected: 48, actual: 32 },
+    });
+  });
+
+  it('should derive the same p384 keys with an hd key deriver', async () => {
+    const publicKeys = [1, 2, 3].map(() => {
+      const ecdh = crypto.createECDH('secp384r1');
+      ecdh.generateKeys();
+      return ecdh.getPublicKey(null, 'compressed');
+    });
+    const id = Buffer.from('test', 'ascii');
+    const deriver = await hdKeyDeriver('P384', publicKeys);
+
+    expect(deriver.derive(id)).toEqual(
+      await ecdsaDeriveKey('P384', id, publicKeys)
+    );
+  });
 });
@@ -0,0 +1,156 @@
+/// <reference types="jest" />
+
+import { ethers } from 'ethers';
+import { sign, wallet } from '@lit-protocol/wasm/testing';
+import { ethTxDecode, ethTxSerialize, ethTxSigningHash } from '..';
+
+// The example of EIP-155
+const legacyTransaction = {
+  nonce: 9,
+  gasPrice: '20000000000',
+  gasLimit: 21000,
+  to: '0x3535353535353535353535353535353535353535',
+  value: '1000000000000000000',
+  chainId: 1,
+};
+const legacyRaw =
+  '0xf86c098504a817c800825208943535353535353535353535353535353535353535880de0b6b3a76400008025a028ef61340bd939bc2195fe537567866003e1a15d3c71ff63e1590620aa636276a067cbe9d8997f761aecb703304b3800ccf555c9f3dc64214b297fb1966a3b6d83';
+
+const to = '0x000000000000000000000000000000000000dEaD';
+const transactions = {
+  'EIP-2930': {
+    type: 1,
+    chainId: 5,
+    nonce: 1,
+    gasPrice: 1000,
+    gasLimit: 30000,
+    to,
+    accessList: [{ address: to, storageKeys: ['0x' + '22'.repeat(32)] }],
+  },
+  'EIP-1559': {
+    type: 2,
+    chainId: 137,
+    nonce: 3,
+    maxFeePerGas: '100000000000',
+    maxPriorityFeePerGas: '0x3b9aca00',
+    gasLimit: 50000,
+    to,
+    value: '0x01',
+    data: '0xdeadbeef',
+  },
+};
+
+const hex = (bytes: Uint8Array) => ethers.utils.hexlify(bytes);
+
+describe('Ethereum transactions', () => {
+  it('should sign legacy transactions with EIP-155', async () => {
+    const digest = await ethTxSigningHash(legacyTransaction);
+    expect(hex(digest)).toEqual(
+      '0xdaf5a779ae972f972197303d7b574746c7ef83eadac0f2791ad23db92e4c8e53'
+    );
+
+    const raw = await ethTxSerialize(
+      { ...legacyTransaction, from: wallet.address },
+      sign(digest)
+    );
+    expect(hex(raw)).toEqual(legacyRaw);
+  });
+
+  it.each(Object.keys(transactions) as (keyof typeof transactions)[])(
+    'should serialize %s transactions like ethers',
+    async (name) => {
+      const transaction = transactions[name];
+      const digest = await ethTxSigningHash(transaction);
+      expect(hex(digest)).toEqual(
+        ethers.utils.keccak256(ethers.utils.serializeTransaction(transaction))
+      );
+
+      const raw = await ethTxSerialize(transaction, sign(digest));
+      expect(hex(raw)).toEqual(
+        ethers.utils.serializeTransaction(
+          transaction,
+          wallet._signingKey().signDigest(digest)
+        )
+      );
+    }
+  );
+
+  it('should decode signed transactions', async () => {
+    const decoded = await ethTxDecode(ethers.utils.arrayify(legacyRaw));
+    const parsed = ethers.utils.parseTransaction(legacyRaw);
+
+    expect(decoded.hash).toEqual(parsed.hash);
+    expect(decoded.transaction.from).toEqual(parsed.from);
+    expect(decoded.transaction.chainId).toEqual('0x1');
+    expect(decoded.v).toEqual('0x25');
+    expect(decoded.r).toEqual(parsed.r);
+    expect(decoded.s).toEqual(parsed.s);
+
+    // Decoded transactions serialize back to themselves
+    const { transaction, r, s, yParity } = decoded;
+    expect(
+      hex(
+        await ethTxSerialize(transaction, [
+          ethers.utils.arrayify(r),
+          ethers.utils.arrayify(s),
+          yParity,
+        ])
+      )
+    ).toEqual(legacyRaw);
+  });
+
+  it('should round trip blob transactions', async () => {
+    const transaction = {
+      chainId: 1,
+      maxFeePerGas: 2,
+      maxPriorityFeePerGas: 1,
+      gasLimit: 21000,
+      to,
+      maxFeePerBlobGas: 3,
+      blobVersionedHashes: ['0x01' + '11'.repeat(31)],
+    };
+    const raw = await ethTxSerialize(
+      transaction,
+      sign(await ethTxSigningHash(transaction))
+    );
+    const decoded = await ethTxDecode(raw);
+
+    expect(raw[0]).toEqual(3);
+    expect(decoded.transaction).toMatchObject({
+      type: 3,
+      from: wallet.address,
+      maxFeePerBlobGas: '0x3',
+      blobVersionedHashes: transaction.blobVersionedHashes,
+    });
+  });
+
+  it('should reject signatures of another sender', async () => {
+    const digest = await ethTxSigningHash(legacyTransaction);
+    await expect(
+      ethTxSerialize({ ...legacyTransaction, from: to }, sign(digest))
+    ).rejects.toMatchObject({ code: 'signature_mismatch' });
+  });
+
+  it('should reject lists nested deeper than transactions', async () => {
+    // Deep enough to overflow the stack of a recursive decoder
+    let nested = Uint8Array.from([0xc0]);
+    for (let i = 0; i < 20000; i++) {
+      const length = ethers.utils.arrayify(nested.length);
+      const header =
+        nested.length <= 55
+          ? [0xc0 + nested.length]
+          : [0xf7 + length.length, ...length];
+      nested = ethers.utils.concat([header, nested]);
+    }
+
+    await expect(ethTxDecode(nested)).rejects.toMatchObject({
+      code: 'invalid_transaction',
+    });
+  });
+
+  it('should reject fields of another transaction type', async () => {
+    await expect(
+      ethTxSigningHash({ ...transactions['EIP-1559'], gasPrice: 1 })
+    ).rejects.toMatchObject({ code: 'invalid_transaction', kind: 'Parser' });
+  });
+});
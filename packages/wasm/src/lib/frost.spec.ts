@@ -0,0 +1,72 @@
+/// <reference types="jest" />
+
+import { FrostVariant, frostDeriveKey } from '..';
+
+// `G`, `2G` and `3G` of each curve, compressed
+const rootKeys: Record<FrostVariant, string[]> = {
+  Ed25519: [
+    '5866666666666666666666666666666666666666666666666666666666666666',
+    'c9a3f86aae465f0e56513864510f3997561fa2c9e85ea21dc2292309f3cd6022',
+    'd4b4f5784868c3020403246717ec169ff79e26608ea126a1ab69ee77d1b16712',
+  ],
+  Ristretto255: [
+    'e2f2ae0a6abc4e71a884a961c500515f58e30b6aa582dd8db6a65945e08d2d76',
+    '6a493210f7499cd17fecb510ae0cea23a110e8d5b901f8acadd3095c73a3b919',
+    '94741f5d5d52755ece4f23f044ee27d5d1ea1e2bd196b462166b16152a9d0259',
+  ],
+  Bls12381G1: [
+    '97f1d3a73197d7942695638c4fa9ac0fc3688c4f9774b905a14e3a3f171bac586c55e83ff97a1aeffb3af00adb22c6bb',
+    'a572cbea904d67468808c8eb50a9450c9721db309128012543902d0ac358a62ae28f75bb8f1c7c42c39a8c5529bf0f4e',
+    '89ece308f9d1f0131765212deca99697b112d61f9be9a5f1f3780a51335b3ff981747a0b2ca2179b96d2c0c9024e5224',
+  ],
+  Bls12381G2: [
+    '93e02b6052719f607dacd3a088274f65596bd0d09920b61ab5da61bbdc7f5049334cf11213945d57e5ac7d055d042b7e024aa2b2f08f0a91260805272dc51051c6e47ad4fa403b02b4510b647ae3d1770bac0326a805bbefd48056c8c121bdb8',
+    'aa4edef9c1ed7f729f520e47730a124fd70662a904ba1074728114d1031e1572c6c886f6b57ec72a6178288c47c335771638533957d540a9d2370f17cc7ed5863bc0b995b8825e0ee1ea1e1e4d00dbae81f14b0bf3611b78c952aacab827a053',
+    '89380275bbc8e5dcea7dc4dd7e0550ff2ac480905396eda55062650f8d251c96eb480673937cc6d9d6a44aaa56ca66dc122915c824a0857e2ee414a3dccb23ae691ae54329781315a0c75df1c04d6d7a50a030fc866f09d516020ef82324afae',
+  ],
+  Jubjub: [
+    'cb550cd538ea0cc1138480408e6eaab9b36c613f0dd3f7784fdb6eea837b13d7',
+    '719af0e6e0c6d0aa680f3b7e97dee9c3cbc3a7815979f08e33a640fab8ca9ab1',
+    'c5295dd1cb37a4ae58005ac7019c958df01d0e5256e17e81ba9d94a2db339cc7',
+  ],
+};
+
+// The root keys derived with the id `test` by `get_derived_public_key` of the
+// `lit-sdk` 2.0.1 crate, which uses the contexts of the node (`id_sign_ctx` in
+// `lit-node-core`) for the Ed25519, Ristretto255, Bls12381 and RedJubjub schemes.
+// The node has no G2 derivation, that key is only checked against itself
+const derivedKeys: Record<FrostVariant, string> = {
+  Ed25519: 'd27029901d29cf7b946d6147048431240366e9b8667d44c4a8cc35cb1bd285ee',
+  Ristretto255:
+    '301855b918038c3adbcfe2c6987ee6e0d8f87a1a5ef42574ffa3235ec369932b',
+  Bls12381G1:
+    'b7ba607826744ea634eb2ebcca1e123cdf02341eb4e71163e267a876edbe1936bd97f6eb4d01678cf5ea5fda3501a9d1',
+  Bls12381G2:
+    '9474a1602ebc0de81c556f83d3566ca946691e9c4e5187ac3c36a36cbd233827bb6461eb1ef1677695d8e65e3cdd6fcf1812c7d645657a67be31f9c1ca690c5b392027814ad72b34962b5cb0f09b6ed32762d80c68658841fa7570166638dae4',
+  Jubjub: 'bde594f0217f2843063626c3d5a4839e4b3b3f320d8a7c7757127fe6dd4bcda6',
+};
+
+describe('FROST', () => {
+  it.each(Object.keys(rootKeys) as FrostVariant[])(
+    'should derive %s keys like the node',
+    async (variant) => {
+      const derivedKey = await frostDeriveKey(
+        variant,
+        Buffer.from('test', 'ascii'),
+        rootKeys[variant].map((key) => Buffer.from(key, 'hex'))
+      );
+
+      expect(Buffer.from(derivedKey).toString('hex')).toEqual(
+        derivedKeys[variant]
+      );
+    }
+  );
+
+  it('should reject keys of another curve', async () => {
+    await expect(
+      frostDeriveKey('Ed25519', Buffer.from('test', 'ascii'), [
+        Buffer.from(rootKeys.Bls12381G1[0], 'hex'),
+      ])
+    ).rejects.toMatchObject({ code: 'invalid_point' });
+  });
+});
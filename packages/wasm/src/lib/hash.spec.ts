@@ -0,0 +1,127 @@
+/// <reference types="jest" />
+
+import * as crypto from 'node:crypto';
+import { ethers } from 'ethers';
+import { eip712Hash, hashMessage } from '..';
+
+const message = Buffer.from('hello world', 'ascii');
+
+// The example of EIP-712
+const typedData = {
+  types: {
+    EIP712Domain: [
+      { name: 'name', type: 'string' },
+      { name: 'version', type: 'string' },
+      { name: 'chainId', type: 'uint256' },
+      { name: 'verifyingContract', type: 'address' },
+    ],
+    Person: [
+      { name: 'name', type: 'string' },
+      { name: 'wallet', type: 'address' },
+    ],
+    Mail: [
+      { name: 'from', type: 'Person' },
+      { name: 'to', type: 'Person' },
+      { name: 'contents', type: 'string' },
+    ],
+  },
+  primaryType: 'Mail',
+  domain: {
+    name: 'Ether Mail',
+    version: '1',
+    chainId: 1,
+    verifyingContract: '0xCcCCccccCCCCcCCCCCCcCcCccCcCCCcCcccccccC',
+  },
+  message: {
+    from: { name: 'Cow', wallet: '0xCD2a3d9F938E13CD947Ec05AbC7FE734Df8DD826' },
+    to: { name: 'Bob', wallet: '0xbBbBBBBbbBBBbbbBbbBbbbbBBbBbbbbBbBbbBBbB' },
+    contents: 'Hello, Bob!',
+  },
+};
+
+const hex = (bytes: Uint8Array) => ethers.utils.hexlify(bytes);
+
+describe('hash', () => {
+  it('should hash messages like ethers', async () => {
+    expect(hex(await hashMessage('Keccak256', message))).toEqual(
+      ethers.utils.keccak256(message)
+    );
+    expect(hex(await hashMessage('Sha256', message))).toEqual(
+      ethers.utils.sha256(message)
+    );
+    expect(hex(await hashMessage('Eip191', message))).toEqual(
+      ethers.utils.hashMessage(message)
+    );
+  });
+
+  it('should hash messages with sha384', async () => {
+    expect(Buffer.from(await hashMessage('Sha384', message))).toEqual(
+      crypto.createHash('sha384').update(message).digest()
+    );
+  });
+
+  it('should hash typed data', async () => {
+    const { Person, Mail } = typedData.types;
+    const types = { Person, Mail };
+    const expected =
+      '0xbe609aee343fb3c4b28e1df9e632fca64fcfaede20f02e86244efddf30957bd2';
+
+    expect(
+      ethers.utils._TypedDataEncoder.hash(
+        typedData.domain,
+        types,
+        typedData.message
+      )
+    ).toEqual(expected);
+    expect(hex(await eip712Hash(typedData))).toEqual(expected);
+    // The domain type is inferred from the domain
+    expect(hex(await eip712Hash({ ...typedData, types }))).toEqual(expected);
+    expect(
+      hex(
+        await hashMessage(
+          'Eip712',
+          Buffer.from(JSON.stringify(typedData), 'utf8')
+        )
+      )
+    ).toEqual(expected);
+  });
+
+  it('should encode integers like ethers', async () => {
+    const types = {
+      Values: [
+        { name: 'small', type: 'int8' },
+        { name: 'large', type: 'uint256' },
+        { name: 'items', type: 'int64[]' },
+      ],
+    };
+    const values = {
+      small: -128,
+      large: '0xffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff',
+      items: [-1, '12345678901234567', 0],
+    };
+
+    expect(
+      hex(
+        await eip712Hash({
+          types,
+          primaryType: 'Values',
+          domain: { chainId: 1 },
+          message: values,
+        })
+      )
+    ).toEqual(
+      ethers.utils._TypedDataEncoder.hash({ chainId: 1 }, types, values)
+    );
+  });
+
+  it('should reject values that do not fit their type', async () => {
+    await expect(
+      eip712Hash({
+        types: { Value: [{ name: 'value', type: 'uint8' }] },
+        primaryType: 'Value',
+        domain: {},
+        message: { value: 256 },
+      })
+    ).rejects.toMatchObject({ code: 'invalid_typed_data', kind: 'Parser' });
+  });
+});
@@ -0,0 +1,242 @@
+/// <reference types="jest" />
+
+import { SevSnpMockChain, sevSnpMockChain } from '@lit-protocol/wasm/testing';
+import {
+  sevSnpCertBundle,
+  sevSnpReportData,
+  sevSnpVerify,
+  sevSnpVerifyNodeAttestation,
+  sevSnpVerifyNodeAttestations,
+} from '..';
+
+const challenge = Buffer.alloc(32, 7);
+const data = { EXTERNAL_ADDR: Buffer.from('127.0.0.1:7470') };
+const signatures = [Buffer.from('first'), Buffer.from('second')];
+const measurement = 'ab'.repeat(48);
+
+describe('wasm sev-snp mock', () => {
+  let chain: SevSnpMockChain;
+  let reportData: Uint8Array;
+  let report: Uint8Array;
+
+  beforeAll(async () => {
+    chain = await sevSnpMockChain({ product: 'Genoa' });
+    reportData = await sevSnpReportData('V1', challenge, data, signatures);
+    report = chain.report({ reportData, measurement, guestSvn: 2 });
+  });
+
+  const trusted = () => ({
+    ark: chain.ark(),
+    ask: chain.ask(),
+    reportDataVersion: 'V1' as const,
+  });
+
+  it('should verify mock reports with the mock roots', async () => {
+    await sevSnpVerify(report, data, signatures, challenge, chain.vcek(), {
+      ...trusted(),
+      policy: { allowedMeasurements: [measurement], minGuestSvn: 2 },
+    });
+  });
+
+  it('should reject mock reports without the mock roots', async () => {
+    await expect(
+      sevSnpVerify(report, data, signatures, challenge, chain.vcek(), {
+        reportDataVersion: 'V1',
+      })
+    ).rejects.toMatchObject({ code: 'attestation_chain' });
+  });
+
+  it('should reject policy violations', async () => {
+    const debugReport = chain.report({ reportData, debug: true, vmpl: 1 });
+    await expect(
+      sevSnpVerify(debugReport, data, signatures, challenge, chain.vcek(), {
+        ...trusted(),
+        policy: { vmpl: 0 },
+      })
+    ).rejects.toMatchObject({
+      code: 'attestation_policy',
+      details: {
+        violations: [
+          { field: 'policy.debug', expected: 'false', actual: 'true' },
+          { field: 'vmpl', expected: '0', actual: '1' },
+        ],
+      },
+    });
+  });
+
+  it('should reject revoked certificates', async () => {
+    await expect(
+      sevSnpVerify(report, data, signatures, challenge, chain.vcek(), {
+        ...trusted(),
+        crl: chain.crl({ revokeVcek: true }),
+      })
+    ).rejects.toMatchObject({
+      code: 'certificate_revoked',
+      details: { certificate: 'VCEK' },
+    });
+
+    await sevSnpVerify(report, data, signatures, challenge, chain.vcek(), {
+      ...trusted(),
+      crl: chain.crl(),
+    });
+  });
+
+  it('should reject expired revocation lists', async () => {
+    await expect(
+      sevSnpVerify(report, data, signatures, challenge, chain.vcek(), {
+        ...trusted(),
+        crl: chain.crl({ thisUpdate: 1000000000, nextUpdate: 1000086400 }),
+      })
+    ).rejects.toMatchObject({ code: 'invalid_crl' });
+  });
+
+  it('should reject expired certificates', async () => {
+    const expired = await sevSnpMockChain({
+      notBefore: 1000000000,
+      notAfter: 1000086400,
+    });
+    await expect(
+      sevSnpVerify(
+        expired.report({ reportData }),
+        data,
+        signatures,
+        challenge,
+        expired.vcek(),
+        { ark: expired.ark(), ask: expired.ask(), reportDataVersion: 'V1' }
+      )
+    ).rejects.toMatchObject({
+      code: 'certificate_validity',
+      details: { certificate: 'VCEK', notAfter: 1000086400 },
+    });
+  });
+
+  it('should reject reports binding other data', async () => {
+    await expect(
+      sevSnpVerify(
+        report,
+        data,
+        signatures.slice(1),
+        challenge,
+        chain.vcek(),
+        trusted()
+      )
+    ).rejects.toMatchObject({ code: 'report_data_mismatch' });
+  });
+
+  it('should verify vlek reports with the asvk', async () => {
+    const vlek = await sevSnpMockChain({ signingKey: 'Vlek' });
+    const vlekReport = vlek.report({ reportData });
+    await sevSnpVerify(vlekReport, data, signatures, challenge, vlek.vcek(), {
+      ark: vlek.ark(),
+      asvk: vlek.ask(),
+      reportDataVersion: 'V1',
+    });
+  });
+
+  it('should verify node attestations with a cached bundle', async () => {
+    const bundle = await sevSnpCertBundle(report, chain.vcek(), trusted());
+    const verdict = await sevSnpVerifyNodeAttestation(
+      {
+        type: 'AMD_SEV_SNP',
+        noonce: challenge.toString('base64'),
+        data: { EXTERNAL_ADDR: data.EXTERNAL_ADDR.toString('base64') },
+        signatures: signatures.map((s) => s.toString('base64')),
+        report: Buffer.from(report).toString('base64'),
+      },
+      challenge,
+      'https://127.0.0.1:7470',
+      bundle,
+      { ark: chain.ark(), reportDataVersion: 'V1' }
+    );
+    expect(verdict).toMatchObject({ valid: true, product: 'Genoa' });
+  });
+
+  it('should compare external host names case insensitively', async () => {
+    const nodeData = { EXTERNAL_ADDR: Buffer.from('Node1.Example.com:7470') };
+    const nodeReport = chain.report({
+      reportData: await sevSnpReportData('V1', challenge, nodeData, signatures),
+    });
+    const verdict = await sevSnpVerifyNodeAttestation(
+      {
+        type: 'AMD_SEV_SNP',
+        noonce: challenge.toString('base64'),
+        data: { EXTERNAL_ADDR: nodeData.EXTERNAL_ADDR.toString('base64') },
+        signatures: signatures.map((s) => s.toString('base64')),
+        report: Buffer.from(nodeReport).toString('base64'),
+      },
+      challenge,
+      'https://node1.example.com:7470',
+      chain.vcek(),
+      trusted()
+    );
+    expect(verdict.checks).toContainEqual(
+      expect.objectContaining({ check: 'externalAddress', passed: true })
+    );
+    expect(verdict.valid).toBe(true);
+  });
+
+  it('should verify node sets against a threshold', async () => {
+    const node = async (i: number, instanceId: string, source = chain) => {
+      const nodeChallenge = Buffer.alloc(32, i);
+      const nodeData = {
+        EXTERNAL_ADDR: Buffer.from(`127.0.0.${i}:7470`),
+        INSTANCE_ID: Buffer.from(instanceId),
+      };
+      const nodeReport = source.report({
+        reportData: await sevSnpReportData(
+          'V1',
+          nodeChallenge,
+          nodeData,
+          signatures
+        ),
+      });
+      return {
+        url: `https://127.0.0.${i}:7470`,
+        challenge: nodeChallenge,
+        certificates: source.vcek(),
+        attestation: {
+          type: 'AMD_SEV_SNP',
+          noonce: nodeChallenge.toString('base64'),
+          data: Object.fromEntries(
+            Object.entries(nodeData).map(([key, value]) => [
+              key,
+              value.toString('base64'),
+            ])
+          ),
+          signatures: signatures.map((s) => s.toString('base64')),
+          report: Buffer.from(nodeReport).toString('base64'),
+        },
+      };
+    };
+    const untrusted = await sevSnpMockChain({ product: 'Genoa' });
+    const nodes = [
+      await node(1, 'a'),
+      await node(2, 'b'),
+      await node(3, 'a'),
+      await node(4, 'd', untrusted),
+    ];
+
+    const verdict = await sevSnpVerifyNodeAttestations(nodes, 2, trusted(), [
+      'INSTANCE_ID',
+    ]);
+    expect(verdict).toMatchObject({
+      attested: 1,
+      threshold: 2,
+      thresholdMet: false,
+      failed: [nodes[0].url, nodes[2].url, nodes[3].url],
+    });
+    expect(verdict.nodes[0].reasons).toEqual([
+      `INSTANCE_ID is also advertised by ${nodes[2].url}`,
+    ]);
+
+    expect(
+      await sevSnpVerifyNodeAttestations(nodes.slice(0, 2), 2, trusted(), [
+        'INSTANCE_ID',
+      ])
+    ).toMatchObject({ attested: 2, thresholdMet: true, failed: [] });
+
+    await expect(
+      sevSnpVerifyNodeAttestations([nodes[0], nodes[0]], 1, trusted())
+    ).rejects.toMatchObject({ code: 'cannot_deserialize' });
+  });
+});
@@ -1,7 +1,22 @@
 /// <reference types="jest" />
 
 import * as fs from 'node:fs';
-import { sevSnpGetVcekUrl, sevSnpVerify } from '..';
+import {
+  sevSnpCertBundle,
+  sevSnpCheckPolicy,
+  sevSnpDetectProduct,
+  sevSnpGetCertChainUrl,
+  sevSnpGetCrlUrl,
+  sevSnpGetVcekUrl,
+  sevSnpLaunchMeasurement,
+  sevSnpOvmfHash,
+  sevSnpParseReport,
+  sevSnpReportData,
+  sevSnpVerify,
+  sevSnpVerifyNodeAttestation,
+  SevSnpMeasurementOptions,
+  SevSnpVerifyOptions,
+} from '..';
 import {
   attestation,
   challenge as challengeHex,
@@ -18,13 +33,269 @@ const signatures = attestation.signatures.map((s) => Buffer.from(s, 'base64'));
 const challenge = Buffer.from(challengeHex, 'hex');
 const report = Buffer.from(attestation.report, 'base64');
 const vcek = fs.readFileSync(`${__dirname}/sev-snp.spec/vcek.crt`);
+// The fixture is a version 2 report, which doesn't identify its product
+const milan: SevSnpVerifyOptions = { product: 'Milan' };
+// `tests/measurement/ovmf_AmdSev_suffix.bin` of virtee/sev 7.1.0, the last page of an
+// AmdSev OVMF build which its measurement tests pass as the whole OVMF file
+const ovmf = fs.readFileSync(
+  `${__dirname}/sev-snp.spec/ovmf_AmdSev_suffix.bin`
+);
+const emptyHash =
+  'e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855';
 
 describe('wasm sev-snp', () => {
   it('should get the vcek url', async () => {
-    expect(await sevSnpGetVcekUrl(report)).toEqual(vcekUrl);
+    expect(await sevSnpGetVcekUrl(report, 'Milan')).toEqual(vcekUrl);
+  });
+
+  it('should not guess the product of version 2 reports', async () => {
+    await expect(sevSnpDetectProduct(report)).rejects.toMatchObject({
+      code: 'unknown_product',
+    });
+    await expect(sevSnpGetVcekUrl(report)).rejects.toMatchObject({
+      code: 'unknown_product',
+    });
+  });
+
+  it('should parse attestation reports', async () => {
+    expect(await sevSnpParseReport(report, 'Milan')).toMatchObject({
+      product: 'Milan',
+      version: 2,
+      vmpl: 0,
+      measurement:
+        '9f11f74d7ac0ca9f88c4caf230a69faf8a0bd114f03f280f0ece156177bdbf5969fb674baa32afc4c818419c93338e6a',
+      reportedTcb: { bootloader: 3, tee: 0, snp: 10, microcode: 169 },
+      policy: { debugAllowed: false, migrateMaAllowed: false },
+      keyInfo: { signingKey: 'Vcek' },
+    });
   });
 
   it('should verify attestation reports', async () => {
-    await sevSnpVerify(report, data, signatures, challenge, vcek);
+    await sevSnpVerify(report, data, signatures, challenge, vcek, milan);
+  });
+
+  it('should reject truncated attestation reports', async () => {
+    await expect(
+      sevSnpVerify(report.subarray(0, 512), data, signatures, challenge, vcek)
+    ).rejects.toMatchObject({
+      code: 'invalid_attestation_report',
+      details: { reason: 'length', expected: 1184, actual: 512 },
+    });
+  });
+
+  it('should accept reports matching the policy', async () => {
+    await sevSnpVerify(report, data, signatures, challenge, vcek, {
+      ...milan,
+      policy: { minTcb: { bootloader: 3, snp: 10 }, vmpl: 0 },
+    });
+  });
+
+  it('should report every policy violation', async () => {
+    expect(
+      await sevSnpCheckPolicy(
+        report,
+        {
+          allowedMeasurements: ['00'],
+          minTcb: { snp: 255 },
+          vmpl: 3,
+        },
+        'Milan'
+      )
+    ).toMatchObject([
+      { field: 'measurement', expected: 'one of [00]' },
+      { field: 'reportedTcb.snp', expected: '>= 255', actual: '10' },
+      { field: 'vmpl', expected: '3', actual: '0' },
+    ]);
+  });
+
+  it('should report each failed check of a node attestation', async () => {
+    const verdict = await sevSnpVerifyNodeAttestation(
+      attestation,
+      challenge,
+      'https://127.0.0.1:7470',
+      vcek,
+      milan
+    );
+
+    expect(verdict.valid).toBe(false);
+    expect(verdict.checks).toMatchObject([
+      { check: 'nonce', passed: true },
+      // the fixture predates EXTERNAL_ADDR
+      { check: 'externalAddress', passed: false, expected: '127.0.0.1' },
+      { check: 'externalPort', passed: false, expected: '7470' },
+      { check: 'certificateChain', passed: true },
+      { check: 'reportData', passed: true },
+    ]);
+  });
+
+  it('should compute the legacy report data', async () => {
+    expect(
+      Buffer.from(await sevSnpReportData('V0', challenge, data, signatures))
+    ).toEqual(report.subarray(0x50, 0x90));
+  });
+
+  it('should tell which binding version the report data matches', async () => {
+    await expect(
+      sevSnpVerify(report, data, signatures, challenge, vcek, {
+        ...milan,
+        reportDataVersion: 'V1',
+      })
+    ).rejects.toMatchObject({
+      code: 'report_data_mismatch',
+      details: { version: 1, matchingVersion: 0 },
+    });
+  });
+
+  it('should get the crl url', async () => {
+    expect(await sevSnpGetCrlUrl('Milan')).toEqual(
+      'https://kdsintf.amd.com/vcek/v1/Milan/crl'
+    );
+  });
+
+  it('should get the vlek urls', async () => {
+    expect(await sevSnpGetCertChainUrl('Genoa', 'Vlek')).toEqual(
+      'https://kdsintf.amd.com/vlek/v1/Genoa/cert_chain'
+    );
+    expect(await sevSnpGetCrlUrl('Genoa', 'Vlek')).toEqual(
+      'https://kdsintf.amd.com/vlek/v1/Genoa/crl'
+    );
+  });
+
+  it('should reject certificates outside of their validity period', async () => {
+    await expect(
+      sevSnpVerify(report, data, signatures, challenge, vcek, {
+        ...milan,
+        verificationTime: 1700000000,
+      })
+    ).rejects.toMatchObject({
+      code: 'certificate_validity',
+      details: { certificate: 'VCEK', notBefore: 1707137684 },
+    });
+  });
+
+  it('should reject malformed revocation lists', async () => {
+    await expect(
+      sevSnpVerify(report, data, signatures, challenge, vcek, {
+        ...milan,
+        crl: Buffer.from('not a crl'),
+      })
+    ).rejects.toMatchObject({ code: 'invalid_crl' });
+  });
+
+  it('should verify with a cached certificate bundle', async () => {
+    const bundle = await sevSnpCertBundle(report, vcek, milan);
+    expect(Buffer.from(bundle.subarray(0, 4)).toString()).toEqual('LSNP');
+    await sevSnpVerify(report, data, signatures, challenge, bundle, milan);
+  });
+
+  it('should reject truncated certificate bundles', async () => {
+    const bundle = await sevSnpCertBundle(report, vcek, milan);
+    await expect(
+      sevSnpVerify(
+        report,
+        data,
+        signatures,
+        challenge,
+        bundle.subarray(0, 100),
+        milan
+      )
+    ).rejects.toMatchObject({ code: 'cannot_deserialize' });
+  });
+
+  // `test_snp_ovmf_hash_full_default` in virtee/sev `tests/measurement.rs`
+  it('should hash the OVMF', async () => {
+    expect(await sevSnpOvmfHash(ovmf)).toEqual(
+      '086e2e9149ebf45abdc3445fba5b2da8270bdbb04094d7a2c37faaa4b24af3aa16aff8c374c2a55c467a50da6d466b74'
+    );
+  });
+
+  // The tests of virtee/sev `tests/measurement.rs` with an EPYC-v4 vCPU, their
+  // `/dev/null` kernel is the empty hash and a kernel without `append` has no cmdline
+  it.each<[string, SevSnpMeasurementOptions, string]>([
+    [
+      'test_snp_default',
+      {
+        vcpus: 1,
+        vcpuType: 'EPYC-v4',
+        guestFeatures: 0x21,
+        kernelHash: emptyHash,
+        cmdline: 'console=ttyS0 loglevel=7',
+      },
+      '803f691094946e42068aaa3a8f9e26a5c89f36f7b73ecfb28c653360fe4b3aba7e534442e7e1e17895dfe778d0228977',
+    ],
+    [
+      'test_sev_snp_only',
+      {
+        vcpus: 1,
+        vcpuType: 'EPYC-v4',
+        guestFeatures: 0x1,
+        kernelHash: emptyHash,
+        cmdline: 'console=ttyS0 loglevel=7',
+      },
+      '6d287813eb5222d770f75005c664e34c204f385ce832cc2ce7d0d6f354454362f390ef83a92046c042e706363b4b08fa',
+    ],
+    [
+      'test_snp_without_kernel_default',
+      { vcpus: 1, vcpuType: 'EPYC-v4', guestFeatures: 0x21 },
+      'e1e1ca029dd7973ab9513295be68198472dcd4fc834bd9af9b63f6e8a1674dbf281a9278a4a2ebe0eed9f22adbcd0e2b',
+    ],
+    [
+      'test_snp_with_multiple_vcpus_default',
+      {
+        vcpus: 4,
+        vcpuType: 'EPYC-v4',
+        guestFeatures: 0x21,
+        kernelHash: emptyHash,
+      },
+      '4953b1fb416fa874980e8442b3706d345926d5f38879134e00813c5d7abcbe78eafe7b422907be0b4698e2414a631942',
+    ],
+    [
+      'test_snp_ec2_default',
+      {
+        vcpus: 1,
+        vcpuType: 'EPYC-v4',
+        guestFeatures: 0x21,
+        kernelHash: emptyHash,
+        vmmType: 'Ec2',
+      },
+      '6ae80856486b1396af8c82a40351d6ed76a20c785e9c7fa4ffa27c22d5d6313b4b3b458cd3c9968e6f89fb5d8450d7a6',
+    ],
+    [
+      'test_snp_ovmf_hash_gen_default',
+      {
+        vcpus: 1,
+        vcpuType: 'EPYC-v4',
+        guestFeatures: 0x21,
+        kernelHash: emptyHash,
+        ovmfHash:
+          '086e2e9149ebf45abdc3445fba5b2da8270bdbb04094d7a2c37faaa4b24af3aa16aff8c374c2a55c467a50da6d466b74',
+      },
+      '329c8ce0972ae52343b64d34a434a86f245dfd74f5ed7aae15d22efc78fb9683632b9b50e4e1d7fa41179ef98a7ef198',
+    ],
+  ])(
+    'should compute the launch measurement of %s',
+    async (_, options, measurement) => {
+      expect(await sevSnpLaunchMeasurement(ovmf, options)).toEqual(measurement);
+    }
+  );
+
+  it('should reject an OVMF without a footer table', async () => {
+    await expect(
+      sevSnpLaunchMeasurement(Buffer.alloc(4096), { vcpus: 1, vcpuType: 'EPYC' })
+    ).rejects.toMatchObject({ code: 'invalid_measurement_input' });
+  });
+
+  it('should reject several vCPUs without an AP reset vector', async () => {
+    // the SEV-ES reset block entry ends with its GUID, after the AP EIP and its size
+    const resetBlockGuid = Buffer.from(
+      'de71f7007e1acb4f890e68c77e2fb44e',
+      'hex'
+    );
+    const noApEip = Buffer.from(ovmf);
+    noApEip.writeUInt32LE(0, noApEip.lastIndexOf(resetBlockGuid) - 6);
+
+    await expect(
+      sevSnpLaunchMeasurement(noApEip, { vcpus: 2, vcpuType: 'EPYC-v4' })
+    ).rejects.toMatchObject({ code: 'invalid_measurement_input' });
   });
 });
@@ -0,0 +1,127 @@
+/// <reference types="jest" />
+
+import * as crypto from 'node:crypto';
+import {
+  Secp256k1PublicKey,
+  fromB64,
+  toSerializedSignature,
+} from '@mysten/sui.js';
+import { blake2b } from '@noble/hashes/blake2b';
+import { ethers } from 'ethers';
+import { publicKey, sign } from '@lit-protocol/wasm/testing';
+import {
+  suiAddress,
+  suiSignBytes,
+  suiSignature,
+  suiTransactionDigest,
+  suiVerify,
+} from '..';
+
+const { arrayify, hexZeroPad } = ethers.utils;
+
+const message = new TextEncoder().encode('hello');
+
+describe('Sui', () => {
+  it('should prefix messages with their intent', async () => {
+    const { intentMessage, digest, hash } = await suiSignBytes(
+      'PersonalMessage',
+      message
+    );
+
+    // Personal messages are BCS `vector<u8>`, prefixed with their length
+    expect(intentMessage).toEqual(new Uint8Array([3, 0, 0, 5, ...message]));
+    expect(digest).toEqual(blake2b(intentMessage, { dkLen: 32 }));
+    expect(Buffer.from(hash)).toEqual(
+      crypto.createHash('sha256').update(digest).digest()
+    );
+
+    const transactionData = new Uint8Array([0, 1, 2]);
+    const { intentMessage: transaction } = await suiSignBytes(
+      'TransactionData',
+      transactionData
+    );
+    expect(transaction).toEqual(new Uint8Array([0, 0, 0, 0, 1, 2]));
+  });
+
+  it('should hash transaction data', async () => {
+    const transactionData = new Uint8Array([0, 1, 2]);
+    const typed = new Uint8Array([
+      ...new TextEncoder().encode('TransactionData::'),
+      ...transactionData,
+    ]);
+
+    expect(await suiTransactionDigest(transactionData)).toEqual(
+      blake2b(typed, { dkLen: 32 })
+    );
+  });
+
+  it('should derive addresses like sui.js', async () => {
+    expect(await suiAddress('K256', publicKey)).toEqual(
+      new Secp256k1PublicKey(publicKey).toSuiAddress()
+    );
+    await expect(suiAddress('P384', publicKey)).rejects.toMatchObject({
+      code: 'invalid_serialized_signature',
+    });
+  });
+
+  it('should serialize K256 signatures with a low s', async () => {
+    const { hash } = await suiSignBytes('PersonalMessage', message);
+    const [r, s, recoveryId] = sign(hash);
+    const n = ethers.BigNumber.from(
+      '0xfffffffffffffffffffffffffffffffebaaedce6af48a03bbfd25e8cd0364141'
+    );
+    const highS = arrayify(hexZeroPad(n.sub(s).toHexString(), 32));
+
+    const signature = await suiSignature(
+      'K256',
+      [r, highS, 1 - recoveryId],
+      publicKey
+    );
+    expect(signature).toEqual(
+      toSerializedSignature({
+        signature: new Uint8Array([...r, ...s]),
+        signatureScheme: 'Secp256k1',
+        pubKey: new Secp256k1PublicKey(publicKey),
+      })
+    );
+    expect(await suiVerify('PersonalMessage', message, signature)).toEqual(
+      await suiAddress('K256', publicKey)
+    );
+
+    await expect(
+      suiVerify('TransactionData', message, signature)
+    ).rejects.toMatchObject({ code: 'signature_mismatch' });
+
+    const serialized = fromB64(signature);
+    serialized.set(highS, 33);
+    const highSignature = Buffer.from(serialized).toString('base64');
+    await expect(
+      suiVerify('PersonalMessage', message, highSignature)
+    ).rejects.toMatchObject({ code: 'invalid_signature' });
+  });
+
+  it('should serialize and verify P256 signatures', async () => {
+    const { privateKey, publicKey: p256PublicKey } =
+      crypto.generateKeyPairSync('ec', { namedCurve: 'P-256' });
+    const { digest } = await suiSignBytes('TransactionData', message);
+    // Sui signs the SHA-256 of the digest
+    const rs = crypto.sign('sha256', digest, {
+      key: privateKey,
+      dsaEncoding: 'ieee-p1363',
+    });
+    const uncompressedKey = p256PublicKey
+      .export({ format: 'der', type: 'spki' })
+      .subarray(-65);
+
+    const signature = await suiSignature(
+      'P256',
+      [rs.subarray(0, 32), rs.subarray(32), 0],
+      uncompressedKey
+    );
+
+    expect(fromB64(signature)[0]).toEqual(2);
+    expect(await suiVerify('TransactionData', message, signature)).toEqual(
+      await suiAddress('P256', uncompressedKey)
+    );
+  });
+});
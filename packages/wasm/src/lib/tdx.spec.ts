@@ -0,0 +1,370 @@
+/// <reference types="jest" />
+
+import { TdxMockChain, tdxMockChain } from '@lit-protocol/wasm/testing';
+import {
+  TdxCollateral,
+  TdxVerifyOptions,
+  sevSnpReportData,
+  tdxGetCollateralUrls,
+  tdxParseQuote,
+  tdxVerify,
+} from '..';
+
+// Header, v4 body, signature data length, quote signature and attestation key,
+// then the type and length of the QE report certification data
+const BODY_OFFSET = 48;
+const QE_REPORT_OFFSET = 48 + 584 + 4 + 64 + 64 + 2 + 4;
+
+const challenge = Buffer.alloc(32, 7);
+const data = { EXTERNAL_ADDR: Buffer.from('127.0.0.1:7470') };
+const signatures = [Buffer.from('first'), Buffer.from('second')];
+const mrTd = 'ab'.repeat(48);
+
+function header(version: number, teeType: number): Buffer {
+  const quote = Buffer.alloc(48);
+  quote.writeUInt16LE(version, 0);
+  quote.writeUInt16LE(2, 2);
+  quote.writeUInt32LE(teeType, 4);
+  return quote;
+}
+
+describe('wasm tdx', () => {
+  it('should reject quotes of other TEEs', async () => {
+    await expect(tdxParseQuote(header(4, 0))).rejects.toMatchObject({
+      code: 'invalid_attestation_report',
+      details: { reason: 'tee_type', teeType: 0 },
+    });
+  });
+
+  it('should reject unsupported quote versions', async () => {
+    await expect(tdxParseQuote(header(3, 0x81))).rejects.toMatchObject({
+      code: 'invalid_attestation_report',
+      details: { reason: 'version', version: 3 },
+    });
+  });
+
+  it('should reject truncated quotes', async () => {
+    await expect(tdxParseQuote(header(4, 0x81))).rejects.toMatchObject({
+      code: 'invalid_attestation_report',
+      details: { reason: 'length', expected: 64, actual: 48 },
+    });
+  });
+
+  describe('tdxVerify', () => {
+    let chain: TdxMockChain;
+    let reportData: Uint8Array;
+    let quote: Uint8Array;
+    let collateral: TdxCollateral;
+
+    beforeAll(async () => {
+      chain = await tdxMockChain();
+      reportData = await sevSnpReportData('V1', challenge, data, signatures);
+      quote = chain.quote({ reportData, mrTd });
+      collateral = chain.collateral();
+    });
+
+    const verify = (
+      quote: Uint8Array,
+      collateral: TdxCollateral,
+      options: TdxVerifyOptions = {}
+    ) =>
+      tdxVerify(quote, data, signatures, challenge, chain.root(), collateral, {
+        reportDataVersion: 'V1',
+        ...options,
+      });
+
+    it('should verify a quote against its collateral', async () => {
+      await expect(verify(quote, collateral)).resolves.toEqual({
+        fmspc: '00806f050000',
+        tcbStatus: 'UpToDate',
+        tdxModuleTcbStatus: 'UpToDate',
+        qeTcbStatus: 'UpToDate',
+        advisoryIds: [],
+      });
+      await expect(
+        verify(chain.quote({ version: 5, reportData }), collateral)
+      ).resolves.toMatchObject({ tcbStatus: 'UpToDate' });
+    });
+
+    it('should give the collateral urls of the platform', async () => {
+      expect(await tdxGetCollateralUrls(quote)).toEqual({
+        tcbInfo:
+          'https://api.trustedservices.intel.com/tdx/certification/v4/tcb?fmspc=00806F050000',
+        qeIdentity:
+          'https://api.trustedservices.intel.com/tdx/certification/v4/qe/identity',
+        pckCrl:
+          'https://api.trustedservices.intel.com/sgx/certification/v4/pckcrl?ca=platform&encoding=der',
+        rootCaCrl:
+          'https://certificates.trustedservices.intel.com/IntelSGXRootCA.der',
+      });
+      expect((await tdxParseQuote(quote)).mrTd).toEqual(mrTd);
+    });
+
+    it('should reject PCK chains of another root', async () => {
+      const other = await tdxMockChain();
+      await expect(
+        tdxVerify(
+          quote,
+          data,
+          signatures,
+          challenge,
+          other.root(),
+          collateral,
+          { reportDataVersion: 'V1' }
+        )
+      ).rejects.toMatchObject({ code: 'attestation_chain' });
+    });
+
+    it('should reject expired PCK certificates', async () => {
+      const expired = await tdxMockChain({ notAfter: 1_000_000_000 });
+      await expect(
+        tdxVerify(
+          expired.quote({ reportData }),
+          data,
+          signatures,
+          challenge,
+          expired.root(),
+          expired.collateral(),
+          { reportDataVersion: 'V1' }
+        )
+      ).rejects.toMatchObject({
+        code: 'certificate_validity',
+        details: { certificate: 'PCK', notAfter: 1_000_000_000 },
+      });
+    });
+
+    it('should reject QE reports the PCK did not sign', async () => {
+      const tampered = Buffer.from(quote);
+      tampered[QE_REPORT_OFFSET + 128] ^= 1;
+
+      await expect(verify(tampered, collateral)).rejects.toMatchObject({
+        code: 'attestation_chain',
+        details: { reason: 'the QE report is not signed by the PCK' },
+      });
+    });
+
+    it('should reject QE reports that do not commit to the attestation key', async () => {
+      const unbound = chain.quote({
+        reportData,
+        qeReportData: new Uint8Array(64).fill(1),
+      });
+
+      await expect(verify(unbound, collateral)).rejects.toMatchObject({
+        code: 'attestation_chain',
+        details: {
+          reason: 'the QE report does not commit to the attestation key',
+        },
+      });
+    });
+
+    it('should reject quotes the attestation key did not sign', async () => {
+      const tampered = Buffer.from(quote);
+      tampered[BODY_OFFSET] ^= 1;
+
+      await expect(verify(tampered, collateral)).rejects.toMatchObject({
+        code: 'attestation_chain',
+        details: { reason: 'the quote is not signed by the attestation key' },
+      });
+    });
+
+    it('should match the TCB levels of the platform, module and quoting enclave', async () => {
+      // One TDX TCB component below the up to date level
+      const outOfDate = chain.quote({
+        reportData,
+        teeTcbSvn: '03010100000000000000000000000000',
+      });
+      await expect(verify(outOfDate, collateral)).rejects.toMatchObject({
+        code: 'tcb_status',
+        details: {
+          component: 'platform',
+          status: 'OutOfDate',
+          advisoryIds: ['INTEL-SA-00837'],
+        },
+      });
+      await expect(
+        verify(outOfDate, collateral, {
+          allowedTcbStatuses: ['UpToDate', 'OutOfDate'],
+        })
+      ).resolves.toMatchObject({
+        tcbStatus: 'OutOfDate',
+        advisoryIds: ['INTEL-SA-00837'],
+      });
+
+      const belowEvery = chain.quote({
+        reportData,
+        teeTcbSvn: '03010000000000000000000000000000',
+      });
+      await expect(verify(belowEvery, collateral)).rejects.toMatchObject({
+        code: 'invalid_collateral',
+        details: { reason: 'the platform is below every TCB level' },
+      });
+
+      const oldModule = chain.quote({
+        reportData,
+        teeTcbSvn: '01010200000000000000000000000000',
+      });
+      await expect(verify(oldModule, collateral)).rejects.toMatchObject({
+        code: 'tcb_status',
+        details: { component: 'TDX module', status: 'OutOfDate' },
+      });
+
+      await expect(
+        verify(chain.quote({ reportData, qeIsvSvn: 2 }), collateral)
+      ).rejects.toMatchObject({
+        code: 'tcb_status',
+        details: { component: 'quoting enclave', status: 'OutOfDate' },
+      });
+      await expect(
+        verify(chain.quote({ reportData, qeIsvSvn: 1 }), collateral)
+      ).rejects.toMatchObject({
+        code: 'invalid_collateral',
+        details: { reason: 'the quoting enclave is below every TCB level' },
+      });
+
+      await expect(
+        verify(
+          quote,
+          chain.collateral({
+            tcbStatus: 'SWHardeningNeeded',
+            advisoryIds: ['INTEL-SA-00615'],
+          }),
+          { allowedTcbStatuses: ['UpToDate', 'SWHardeningNeeded'] }
+        )
+      ).resolves.toMatchObject({
+        tcbStatus: 'SWHardeningNeeded',
+        advisoryIds: ['INTEL-SA-00615'],
+      });
+    });
+
+    it('should reject collateral of another platform or quoting enclave', async () => {
+      await expect(
+        verify(quote, chain.collateral({ fmspc: '00906ed50000' }))
+      ).rejects.toMatchObject({ code: 'invalid_collateral' });
+      await expect(
+        verify(quote, chain.collateral({ qeMrSigner: '00'.repeat(32) }))
+      ).rejects.toMatchObject({
+        code: 'invalid_collateral',
+        details: { reason: 'the quoting enclave is not signed by Intel' },
+      });
+
+      // Signed by the TCB signing key of another root
+      const other = await tdxMockChain();
+      await expect(verify(quote, other.collateral())).rejects.toMatchObject({
+        code: 'attestation_chain',
+      });
+    });
+
+    it('should check the dates of the collateral', async () => {
+      await expect(
+        verify(quote, chain.collateral({ nextUpdate: 1000 }))
+      ).rejects.toMatchObject({
+        code: 'invalid_collateral',
+        details: { reason: 'TCB info expired at 1000, fetch a new one' },
+      });
+      await expect(
+        verify(quote, chain.collateral({ issueDate: 4_000_000_000 }))
+      ).rejects.toMatchObject({ code: 'invalid_collateral' });
+      await expect(
+        verify(quote, collateral, { verificationTime: 1_000_000_000 })
+      ).rejects.toMatchObject({ code: 'certificate_validity' });
+    });
+
+    it('should check the certificates against the CRLs', async () => {
+      await expect(
+        verify(quote, {
+          ...collateral,
+          pckCrl: chain.pckCrl(),
+          rootCaCrl: chain.rootCaCrl(),
+        })
+      ).resolves.toMatchObject({ tcbStatus: 'UpToDate' });
+
+      await expect(
+        verify(quote, {
+          ...collateral,
+          pckCrl: chain.pckCrl({ revokePck: true }),
+        })
+      ).rejects.toMatchObject({
+        code: 'certificate_revoked',
+        details: { certificate: 'PCK' },
+      });
+      await expect(
+        verify(quote, {
+          ...collateral,
+          rootCaCrl: chain.rootCaCrl({ revokePckCa: true }),
+        })
+      ).rejects.toMatchObject({
+        code: 'certificate_revoked',
+        details: { certificate: 'PCK CA' },
+      });
+      await expect(
+        verify(quote, {
+          ...collateral,
+          rootCaCrl: chain.rootCaCrl({ revokeTcbSigning: true }),
+        })
+      ).rejects.toMatchObject({
+        code: 'certificate_revoked',
+        details: { certificate: 'TCB signing' },
+      });
+
+      await expect(
+        verify(quote, { ...collateral, pckCrl: chain.rootCaCrl() })
+      ).rejects.toMatchObject({ code: 'invalid_crl' });
+      await expect(
+        verify(quote, {
+          ...collateral,
+          pckCrl: chain.pckCrl({ nextUpdate: 1000 }),
+        })
+      ).rejects.toMatchObject({ code: 'invalid_crl' });
+    });
+
+    it('should bind the report data to the challenge', async () => {
+      await expect(
+        tdxVerify(
+          quote,
+          data,
+          signatures,
+          Buffer.alloc(32, 8),
+          chain.root(),
+          collateral,
+          { reportDataVersion: 'V1' }
+        )
+      ).rejects.toMatchObject({
+        code: 'report_data_mismatch',
+        details: { version: 1 },
+      });
+    });
+
+    it('should check the TD against the policy', async () => {
+      await expect(
+        verify(quote, collateral, { policy: { allowedMrtds: [mrTd] } })
+      ).resolves.toMatchObject({ tcbStatus: 'UpToDate' });
+
+      const debug = chain.quote({ reportData, mrTd, debug: true });
+      await expect(
+        verify(debug, collateral, {
+          policy: { allowedMrtds: ['cd'.repeat(48)] },
+        })
+      ).rejects.toMatchObject({
+        code: 'attestation_policy',
+        details: {
+          violations: [
+            { field: 'mrTd', actual: mrTd },
+            { field: 'tdAttributes.debug', expected: 'false', actual: 'true' },
+          ],
+        },
+      });
+
+      // a TD only has four RTMRs, a fifth entry can't be enforced
+      await expect(
+        verify(quote, collateral, {
+          policy: { rtmrs: [null, null, null, null, 'ab'.repeat(48)] },
+        })
+      ).rejects.toMatchObject({
+        code: 'attestation_policy',
+        details: {
+          violations: [{ field: 'rtmrs[4]', expected: 'at most 4 rtmrs' }],
+        },
+      });
+    });
+  });
+});
@@ -0,0 +1,227 @@
+/// <reference types="jest" />
+
+import * as crypto from 'node:crypto';
+import { ethers } from 'ethers';
+import {
+  userOpHashPacked,
+  userOpHashV06,
+  userOpHashV07,
+  userOpPack,
+  userOpRip7212Input,
+  userOpSignature,
+} from '..';
+
+const {
+  arrayify,
+  defaultAbiCoder,
+  hexConcat,
+  hexZeroPad,
+  hexlify,
+  keccak256,
+} = ethers.utils;
+
+const entryPointV06 = '0x5FF137D4b0FDCD49DcA30c7CF57E578a026d2789';
+const entryPointV07 = '0x0000000071727De22E5E9d8BAf0edAc6f37da032';
+const sender = '0x1306b01bC3e4AD202612D3843387e94737673F53';
+
+const userOperationV06 = {
+  sender,
+  nonce: 8,
+  initCode: '0x',
+  callData: '0xb61d27f6',
+  callGasLimit: 100000,
+  verificationGasLimit: '0x186a0',
+  preVerificationGas: 21000,
+  maxFeePerGas: '1000000000',
+  maxPriorityFeePerGas: 1,
+  paymasterAndData: '0x',
+};
+
+const userOperationV07 = {
+  sender,
+  nonce: 8,
+  factory: '0x9406Cc6185a346906296840746125a0E44976454',
+  factoryData: '0xabcd',
+  callData: '0xb61d27f6',
+  callGasLimit: 100000,
+  verificationGasLimit: 200000,
+  preVerificationGas: 21000,
+  maxFeePerGas: '1000000000',
+  maxPriorityFeePerGas: 1,
+  paymaster: '0x0000000000000000000000000000000000000001',
+  paymasterVerificationGasLimit: 3,
+  paymasterPostOpGasLimit: 4,
+  paymasterData: '0xff',
+};
+
+// `getUserOpHash` of the entry points
+const userOpHash = (packed: string, entryPoint: string, chainId: number) =>
+  keccak256(
+    defaultAbiCoder.encode(
+      ['bytes32', 'address', 'uint256'],
+      [keccak256(packed), entryPoint, chainId]
+    )
+  );
+
+const packU128 = (high: ethers.BigNumberish, low: ethers.BigNumberish) =>
+  hexConcat([hexZeroPad(hexlify(high), 16), hexZeroPad(hexlify(low), 16)]);
+
+describe('ERC-4337 user operations', () => {
+  it('should hash v0.6 user operations', async () => {
+    const op = userOperationV06;
+    const packed = defaultAbiCoder.encode(
+      [
+        'address',
+        'uint256',
+        'bytes32',
+        'bytes32',
+        'uint256',
+        'uint256',
+        'uint256',
+        'uint256',
+        'uint256',
+        'bytes32',
+      ],
+      [
+        op.sender,
+        op.nonce,
+        keccak256(op.initCode),
+        keccak256(op.callData),
+        op.callGasLimit,
+        op.verificationGasLimit,
+        op.preVerificationGas,
+        op.maxFeePerGas,
+        op.maxPriorityFeePerGas,
+        keccak256(op.paymasterAndData),
+      ]
+    );
+
+    expect(hexlify(await userOpHashV06(op, entryPointV06, 137))).toEqual(
+      userOpHash(packed, entryPointV06, 137)
+    );
+  });
+
+  it('should pack and hash v0.7 user operations', async () => {
+    const op = userOperationV07;
+    const packedUserOperation = await userOpPack(op);
+
+    expect(packedUserOperation).toMatchObject({
+      initCode: hexConcat([op.factory, op.factoryData]),
+      accountGasLimits: packU128(op.verificationGasLimit, op.callGasLimit),
+      gasFees: packU128(op.maxPriorityFeePerGas, +op.maxFeePerGas),
+      paymasterAndData: hexConcat([
+        op.paymaster,
+        packU128(op.paymasterVerificationGasLimit, op.paymasterPostOpGasLimit),
+        op.paymasterData,
+      ]),
+    });
+
+    const packed = defaultAbiCoder.encode(
+      [
+        'address',
+        'uint256',
+        'bytes32',
+        'bytes32',
+        'bytes32',
+        'uint256',
+        'bytes32',
+        'bytes32',
+      ],
+      [
+        op.sender,
+        op.nonce,
+        keccak256(packedUserOperation.initCode),
+        keccak256(op.callData),
+        packedUserOperation.accountGasLimits,
+        op.preVerificationGas,
+        packedUserOperation.gasFees,
+        keccak256(packedUserOperation.paymasterAndData),
+      ]
+    );
+    const expected = userOpHash(packed, entryPointV07, 1);
+
+    expect(hexlify(await userOpHashV07(op, entryPointV07, '0x1'))).toEqual(
+      expected
+    );
+    expect(
+      hexlify(await userOpHashPacked(packedUserOperation, entryPointV07, 1))
+    ).toEqual(expected);
+  });
+
+  it('should reject gas values that do not fit their packing', async () => {
+    await expect(
+      userOpHashV07(
+        { ...userOperationV07, callGasLimit: '0x1' + '00'.repeat(16) },
+        entryPointV07,
+        1
+      )
+    ).rejects.toMatchObject({ code: 'invalid_user_operation' });
+  });
+
+  it('should encode K256 signatures with a low s', async () => {
+    const wallet = ethers.Wallet.createRandom();
+    const digest = arrayify(keccak256('0x1234'));
+    const { r, s, recoveryParam } = wallet._signingKey().signDigest(digest);
+    const n = ethers.BigNumber.from(
+      '0xfffffffffffffffffffffffffffffffebaaedce6af48a03bbfd25e8cd0364141'
+    );
+    const highS = arrayify(hexZeroPad(n.sub(s).toHexString(), 32));
+
+    const signature = await userOpSignature('K256', [
+      arrayify(r),
+      highS,
+      1 - recoveryParam,
+    ]);
+
+    expect(signature.length).toEqual(65);
+    expect(ethers.utils.recoverAddress(digest, hexlify(signature))).toEqual(
+      wallet.address
+    );
+  });
+
+  it('should encode P256 signatures for the RIP-7212 precompile', async () => {
+    const { privateKey, publicKey } = crypto.generateKeyPairSync('ec', {
+      namedCurve: 'P-256',
+    });
+    const message = Buffer.from('user operation', 'utf8');
+    const messageHash = crypto.createHash('sha256').update(message).digest();
+    const signature = crypto.sign('sha256', message, {
+      key: privateKey,
+      dsaEncoding: 'ieee-p1363',
+    });
+    const uncompressedKey = publicKey
+      .export({ format: 'der', type: 'spki' })
+      .subarray(-65);
+    const combined: [Uint8Array, Uint8Array, number] = [
+      signature.subarray(0, 32),
+      signature.subarray(32),
+      0,
+    ];
+
+    const rs = await userOpSignature('P256', combined);
+    const input = await userOpRip7212Input(
+      messageHash,
+      combined,
+      uncompressedKey
+    );
+
+    expect(input.length).toEqual(160);
+    expect(Buffer.from(input.subarray(0, 32))).toEqual(messageHash);
+    expect(input.subarray(32, 96)).toEqual(rs);
+    expect(Buffer.from(input.subarray(96))).toEqual(
+      uncompressedKey.subarray(1)
+    );
+    expect(
+      crypto.verify(
+        'sha256',
+        message,
+        { key: publicKey, dsaEncoding: 'ieee-p1363' },
+        rs
+      )
+    ).toBe(true);
+
+    await expect(
+      userOpRip7212Input(Buffer.alloc(32, 1), combined, uncompressedKey)
+    ).rejects.toMatchObject({ code: 'signature_mismatch' });
+  });
+});
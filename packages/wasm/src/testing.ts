@@ -0,0 +1,100 @@
+// Test-only entry point, imported as `@lit-protocol/wasm/testing` by the specs for the mocks and shared fixtures.
+// Its bundles are only built by `rust:build:testing` and it is left out of the published package.
+
+export type {
+  SevSnpMockChain,
+  SevSnpMockChainOptions,
+  SevSnpMockCrlOptions,
+  SevSnpMockReport,
+} from './pkg/wasm-sev-snp-mock';
+export type {
+  TdxMockChain,
+  TdxMockChainOptions,
+  TdxMockCollateralOptions,
+  TdxMockCrlOptions,
+  TdxMockQuote,
+} from './pkg/wasm-tdx-mock';
+
+import { ethers } from 'ethers';
+import type {
+  SevSnpMockChain,
+  SevSnpMockChainOptions,
+} from './pkg/wasm-sev-snp-mock';
+import type { TdxMockChain, TdxMockChainOptions } from './pkg/wasm-tdx-mock';
+
+interface TestBundles {
+  'sev-snp-mock': typeof import('./pkg/wasm-sev-snp-mock');
+  'tdx-mock': typeof import('./pkg/wasm-tdx-mock');
+}
+
+const TEST_BUNDLES: {
+  [K in keyof TestBundles]: () => Promise<TestBundles[K]>;
+} = {
+  'sev-snp-mock': () => import('./pkg/wasm-sev-snp-mock'),
+  'tdx-mock': () => import('./pkg/wasm-tdx-mock'),
+};
+
+const loadingPromises: {
+  [K in keyof TestBundles]?: Promise<TestBundles[K]>;
+} = {};
+
+async function loadTestBundle<K extends keyof TestBundles>(
+  name: K
+): Promise<TestBundles[K]> {
+  const loading =
+    (loadingPromises[name] as Promise<TestBundles[K]> | undefined) ??
+    TEST_BUNDLES[name]().then((bundle) => {
+      //@ts-ignore getModule is prepended to the bindings by copyWasmBinary.mjs
+      bundle.initSync(bundle.getModule());
+      return bundle;
+    });
+  (loadingPromises as Record<K, Promise<TestBundles[K]>>)[name] = loading;
+  return loading;
+}
+
+/**
+ * Creates a throwaway ARK, ASK and VCEK that sign attestation reports with chosen fields, for tests only.
+ * Verification only accepts its reports when given its ARK (and ASK) in the options
+ * @param {SevSnpMockChainOptions} [options] product, signing key and validity period of the VCEK
+ * @returns {Promise<SevSnpMockChain>}
+ */
+export async function sevSnpMockChain(
+  options?: SevSnpMockChainOptions
+): Promise<SevSnpMockChain> {
+  const wasm = await loadTestBundle('sev-snp-mock');
+  return new wasm.SevSnpMockChain(options);
+}
+
+/**
+ * Creates a throwaway Intel root, PCK chain and TCB signing certificate that sign TDX quotes,
+ * collateral and CRLs with chosen fields, for tests only.
+ * Verification only accepts its quotes when given its root as `intel_root`
+ * @param {TdxMockChainOptions} [options] validity period of the PCK certificate
+ * @returns {Promise<TdxMockChain>}
+ */
+export async function tdxMockChain(
+  options?: TdxMockChainOptions
+): Promise<TdxMockChain> {
+  const wasm = await loadTestBundle('tdx-mock');
+  return new wasm.TdxMockChain(options);
+}
+
+/**
+ * The secp256k1 key of the EIP-155 example, standing in for a PKP in the specs of the chains
+ */
+export const wallet = new ethers.Wallet('0x' + '46'.repeat(32));
+
+/** The compressed public key of `wallet` */
+export const publicKey = ethers.utils.arrayify(
+  ethers.utils.computePublicKey(wallet.publicKey, true)
+);
+
+/**
+ * Signs a digest with `wallet`, in the `[r, s, v]` form of combined ECDSA signatures
+ * @param {Uint8Array} digest
+ * @returns {[Uint8Array, Uint8Array, number]}
+ */
+export function sign(digest: Uint8Array): [Uint8Array, Uint8Array, number] {
+  const { r, s, recoveryParam } = wallet._signingKey().signDigest(digest);
+  return [ethers.utils.arrayify(r), ethers.utils.arrayify(s), recoveryParam];
+}